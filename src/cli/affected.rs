@@ -0,0 +1,70 @@
+use super::print_json;
+use crate::db::queries;
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use rusqlite::Connection;
+use std::path::Path;
+use std::process::Command;
+
+/// Output formats for `shire affected`, shaped for CI job matrices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AffectedFormat {
+    /// One package name per line
+    Lines,
+    /// JSON array of package names (e.g. for `fromJSON` in a matrix)
+    Json,
+    /// JSON object with changed, affected and unowned files
+    Detail,
+}
+
+/// List files changed between `base` and `head`, relative to `root`.
+/// With a `head`, uses merge-base semantics (`base...head`) like a PR diff;
+/// without one, compares `base` against the working tree.
+pub fn changed_files(root: &Path, base: &str, head: Option<&str>) -> Result<Vec<String>> {
+    let range = match head {
+        Some(head) => format!("{base}...{head}"),
+        None => base.to_string(),
+    };
+    let output = Command::new("git")
+        .args([
+            "diff",
+            "--name-only",
+            "--no-renames",
+            "--relative",
+            &range,
+            "--",
+        ])
+        .current_dir(root)
+        .output()
+        .context("Failed to run git diff")?;
+    if !output.status.success() {
+        bail!(
+            "git diff {} failed: {}",
+            range,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Print the packages affected by `files` in the requested format.
+pub fn run(conn: &Connection, files: &[String], format: AffectedFormat) -> Result<()> {
+    let result = queries::affected_packages(conn, files)?;
+    match format {
+        AffectedFormat::Lines => {
+            for name in &result.affected {
+                println!("{name}");
+            }
+            Ok(())
+        }
+        AffectedFormat::Json => {
+            println!("{}", serde_json::to_string(&result.affected)?);
+            Ok(())
+        }
+        AffectedFormat::Detail => print_json(&result),
+    }
+}
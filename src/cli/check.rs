@@ -0,0 +1,143 @@
+use super::{OutputFormat, print_json};
+use crate::config::Config;
+use crate::db::queries::{self, Page};
+use anyhow::{Result, bail};
+use clap::Subcommand;
+use rusqlite::Connection;
+
+/// `shire check` subcommands. Each check prints its findings and exits
+/// non-zero when it finds a problem, so it can gate CI.
+#[derive(Subcommand)]
+pub enum CheckCommand {
+    /// Fail if internal packages form dependency cycles
+    Cycles {
+        /// Ignore dev dependency edges
+        #[arg(long)]
+        exclude_dev: bool,
+    },
+    /// Fail if a library listed in `[drift] single_version` is declared with
+    /// more than one version requirement
+    Drift {
+        /// Additional library that must have a single version requirement (can be repeated)
+        #[arg(long)]
+        lib: Vec<String>,
+    },
+    /// Fail if an internal dependency breaks a `[[rules]]` layering rule
+    Rules,
+}
+
+pub fn run(
+    conn: &Connection,
+    config: &Config,
+    command: CheckCommand,
+    format: OutputFormat,
+) -> Result<()> {
+    match command {
+        CheckCommand::Cycles { exclude_dev } => {
+            let cycles = queries::find_cycles(conn, exclude_dev, Page::ALL)?.results;
+            match format {
+                OutputFormat::Json => print_json(&cycles)?,
+                OutputFormat::Table => print_cycles(&cycles),
+            }
+            if !cycles.is_empty() {
+                bail!("Found {} dependency cycle(s)", cycles.len());
+            }
+            Ok(())
+        }
+        CheckCommand::Drift { lib } => {
+            let mut libraries = config.drift.single_version.clone();
+            libraries.extend(lib);
+            if libraries.is_empty() {
+                bail!("No libraries to check; list them under [drift] single_version in shire.toml or pass --lib");
+            }
+            let drift: Vec<_> = queries::version_drift(conn, None, 2, Page::ALL)?
+                .into_iter()
+                .filter(|d| libraries.contains(&d.dependency))
+                .collect();
+            match format {
+                OutputFormat::Json => print_json(&drift)?,
+                OutputFormat::Table => print_drift(&drift),
+            }
+            if !drift.is_empty() {
+                bail!(
+                    "Found {} library(ies) declared with more than one version",
+                    drift.len()
+                );
+            }
+            Ok(())
+        }
+        CheckCommand::Rules => {
+            if config.rules.is_empty() {
+                bail!("No layering rules configured; add [[rules]] to shire.toml");
+            }
+            let violations = queries::check_rules(conn, &config.rules, &[], Page::ALL)?.results;
+            match format {
+                OutputFormat::Json => print_json(&violations)?,
+                OutputFormat::Table => print_violations(&violations),
+            }
+            if !violations.is_empty() {
+                bail!("Found {} layering rule violation(s)", violations.len());
+            }
+            Ok(())
+        }
+    }
+}
+
+fn print_cycles(cycles: &[queries::DependencyCycle]) {
+    if cycles.is_empty() {
+        println!("No dependency cycles found.");
+        return;
+    }
+    for (i, cycle) in cycles.iter().enumerate() {
+        println!(
+            "Cycle {} ({} packages): {}",
+            i + 1,
+            cycle.packages.len(),
+            cycle.packages.join(", ")
+        );
+        for edge in &cycle.edges {
+            println!("  {} -> {} ({})", edge.from, edge.to, edge.dep_kind);
+        }
+    }
+}
+
+fn print_drift(drift: &[queries::VersionDrift]) {
+    if drift.is_empty() {
+        println!("No version drift found.");
+        return;
+    }
+    for library in drift {
+        println!(
+            "{} ({}): {} versions across {} packages",
+            library.dependency, library.kind, library.version_count, library.package_count
+        );
+        for version in &library.versions {
+            println!("  {}: {}", version.version_req, version.packages.join(", "));
+        }
+    }
+}
+
+fn print_violations(violations: &[queries::RuleViolation]) {
+    if violations.is_empty() {
+        println!("No layering rule violations found.");
+        return;
+    }
+    let mut current_rule: Option<&str> = None;
+    for violation in violations {
+        if current_rule != Some(violation.rule.as_str()) {
+            current_rule = Some(&violation.rule);
+            match &violation.description {
+                Some(description) => println!("Rule {}: {}", violation.rule, description),
+                None => println!("Rule {}", violation.rule),
+            }
+        }
+        let reason = match violation.reason {
+            queries::ViolationReason::Denied => "denied",
+            queries::ViolationReason::NotAllowed => "not allowed",
+        };
+        println!(
+            "  {} -> {} ({}): {}",
+            violation.from, violation.to, violation.dep_kind, reason
+        );
+    }
+}
@@ -0,0 +1,496 @@
+use crate::db::queries::{self, GraphEdge, Page};
+use anyhow::{Result, bail};
+use clap::{Subcommand, ValueEnum};
+use quick_xml::escape::escape;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum ExportCommand {
+    /// Export the dependency graph for visualization or pipelines
+    Graph {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+        /// Start from this package instead of exporting the whole graph
+        #[arg(long)]
+        root: Option<String>,
+        /// With --root, follow dependents (who depends on root) instead of dependencies
+        #[arg(long, requires = "root")]
+        reverse: bool,
+        /// With --root, maximum depth to traverse (capped at 20)
+        #[arg(long, default_value_t = 20)]
+        depth: u32,
+        /// Only include dependencies between packages in this repo
+        #[arg(long)]
+        internal_only: bool,
+        /// Only include edges of this dependency kind (runtime, dev, peer, build); repeatable
+        #[arg(long = "dep-kind")]
+        dep_kinds: Vec<String>,
+        /// Only include packages of this kind (npm, go, cargo, ...); repeatable
+        #[arg(long = "kind")]
+        kinds: Vec<String>,
+        /// Group nodes into clusters by path prefix or package kind
+        #[arg(long, value_enum)]
+        cluster: Option<ClusterBy>,
+        /// Number of leading path segments used by --cluster path
+        #[arg(long, default_value_t = 1)]
+        cluster_depth: usize,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Graphml,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ClusterBy {
+    /// Leading segments of the package path (see --cluster-depth)
+    Path,
+    /// Package kind (npm, go, cargo, ...)
+    Kind,
+}
+
+/// Cluster label used for dependencies that aren't packages in this repo.
+const EXTERNAL_CLUSTER: &str = "external";
+
+#[derive(Debug, Serialize)]
+pub struct GraphNode {
+    pub name: String,
+    /// Package kind; `None` for external dependencies.
+    pub kind: Option<String>,
+    /// Package path relative to repo root; `None` for external dependencies.
+    pub path: Option<String>,
+    pub internal: bool,
+    pub cluster: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphExport {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+pub fn run(conn: &Connection, command: ExportCommand) -> Result<()> {
+    match command {
+        ExportCommand::Graph {
+            format,
+            root,
+            reverse,
+            depth,
+            internal_only,
+            dep_kinds,
+            kinds,
+            cluster,
+            cluster_depth,
+            output,
+        } => {
+            let filter = GraphFilter {
+                root,
+                reverse,
+                depth: depth.min(20),
+                internal_only,
+                dep_kinds,
+                kinds,
+            };
+            let graph = collect_graph(conn, &filter, cluster, cluster_depth)?;
+            let rendered = match format {
+                GraphFormat::Dot => render_dot(&graph),
+                GraphFormat::Mermaid => render_mermaid(&graph),
+                GraphFormat::Graphml => render_graphml(&graph),
+                GraphFormat::Json => serde_json::to_string_pretty(&graph)? + "\n",
+            };
+            match output {
+                Some(path) => std::fs::write(&path, rendered)?,
+                None => print!("{rendered}"),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Which slice of the dependency graph to export.
+pub struct GraphFilter {
+    pub root: Option<String>,
+    pub reverse: bool,
+    pub depth: u32,
+    pub internal_only: bool,
+    pub dep_kinds: Vec<String>,
+    pub kinds: Vec<String>,
+}
+
+/// Load edges and package metadata from the index and apply the filters.
+pub fn collect_graph(
+    conn: &Connection,
+    filter: &GraphFilter,
+    cluster: Option<ClusterBy>,
+    cluster_depth: usize,
+) -> Result<GraphExport> {
+    let packages: HashMap<String, queries::PackageRow> = queries::list_packages(conn, None, None, Page::ALL)?
+        .into_iter()
+        .map(|p| (p.name.clone(), p))
+        .collect();
+
+    let mut edges = match (&filter.root, filter.reverse) {
+        (Some(root), _) if !packages.contains_key(root) => {
+            bail!("Package '{root}' not found");
+        }
+        (Some(root), false) => {
+            queries::dependency_graph(conn, root, filter.depth, filter.internal_only, Page::ALL)?
+        }
+        (Some(root), true) => queries::reverse_dependency_graph(conn, root, filter.depth, Page::ALL)?,
+        (None, _) => queries::all_dependency_edges(conn, filter.internal_only, Page::ALL)?,
+    }
+    .results;
+
+    if !filter.dep_kinds.is_empty() {
+        edges.retain(|e| filter.dep_kinds.contains(&e.dep_kind));
+    }
+    if !filter.kinds.is_empty() {
+        // External endpoints have no kind, so they are kept when their internal side matches.
+        let keep = |name: &str| match packages.get(name) {
+            Some(pkg) => filter.kinds.contains(&pkg.kind),
+            None => true,
+        };
+        edges.retain(|e| keep(&e.from) && keep(&e.to));
+    }
+    if let Some(root) = &filter.root {
+        edges = prune_unreachable(root, edges, filter.reverse);
+    }
+
+    // Nodes: every edge endpoint, plus the root (which may have no edges left)
+    let mut names: Vec<&str> = edges
+        .iter()
+        .flat_map(|e| [e.from.as_str(), e.to.as_str()])
+        .chain(filter.root.as_deref())
+        .collect();
+    if filter.root.is_none() {
+        // Whole-graph export also shows isolated packages
+        names.extend(
+            packages
+                .values()
+                .filter(|p| filter.kinds.is_empty() || filter.kinds.contains(&p.kind))
+                .map(|p| p.name.as_str()),
+        );
+    }
+    names.sort();
+    names.dedup();
+
+    let nodes = names
+        .into_iter()
+        .map(|name| {
+            let pkg = packages.get(name);
+            let cluster = cluster.map(|by| match (by, pkg) {
+                (_, None) => EXTERNAL_CLUSTER.to_string(),
+                (ClusterBy::Kind, Some(p)) => p.kind.clone(),
+                (ClusterBy::Path, Some(p)) => path_cluster(&p.path, cluster_depth),
+            });
+            GraphNode {
+                name: name.to_string(),
+                kind: pkg.map(|p| p.kind.clone()),
+                path: pkg.map(|p| p.path.clone()),
+                internal: pkg.is_some(),
+                cluster,
+            }
+        })
+        .collect();
+
+    Ok(GraphExport { nodes, edges })
+}
+
+/// After edge filtering, drop edges no longer reachable from the root so the
+/// export doesn't contain islands that were only connected via filtered edges.
+fn prune_unreachable(root: &str, edges: Vec<GraphEdge>, reverse: bool) -> Vec<GraphEdge> {
+    // Reverse graph edges point dependent -> root, so walk them backwards
+    let walk = |e: &GraphEdge| -> (String, String) {
+        if reverse {
+            (e.to.clone(), e.from.clone())
+        } else {
+            (e.from.clone(), e.to.clone())
+        }
+    };
+
+    let mut adjacency: HashMap<String, Vec<String>> = HashMap::new();
+    for edge in &edges {
+        let (src, dst) = walk(edge);
+        adjacency.entry(src).or_default().push(dst);
+    }
+
+    let mut reached: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<&str> = VecDeque::new();
+    reached.insert(root.to_string());
+    queue.push_back(root);
+    while let Some(current) = queue.pop_front() {
+        for next in adjacency.get(current).into_iter().flatten() {
+            if reached.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    edges
+        .into_iter()
+        .filter(|e| reached.contains(&walk(e).0))
+        .collect()
+}
+
+/// First `depth` segments of a package path; the repo-root package is "(root)".
+fn path_cluster(path: &str, depth: usize) -> String {
+    if path.is_empty() {
+        return "(root)".to_string();
+    }
+    path.split('/')
+        .take(depth.max(1))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Group node indices by cluster label (BTreeMap for stable output order).
+fn clusters(graph: &GraphExport) -> (BTreeMap<&str, Vec<usize>>, Vec<usize>) {
+    let mut grouped: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    let mut loose = Vec::new();
+    for (i, node) in graph.nodes.iter().enumerate() {
+        match &node.cluster {
+            Some(c) => grouped.entry(c.as_str()).or_default().push(i),
+            None => loose.push(i),
+        }
+    }
+    (grouped, loose)
+}
+
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn render_dot(graph: &GraphExport) -> String {
+    let mut out = String::from("digraph shire {\n    rankdir=LR;\n    node [shape=box];\n");
+
+    let node_line = |node: &GraphNode| {
+        if node.internal {
+            format!("{};", dot_quote(&node.name))
+        } else {
+            format!("{} [style=dashed];", dot_quote(&node.name))
+        }
+    };
+
+    let (grouped, loose) = clusters(graph);
+    for (i, (label, members)) in grouped.iter().enumerate() {
+        let _ = writeln!(out, "    subgraph cluster_{i} {{");
+        let _ = writeln!(out, "        label={};", dot_quote(label));
+        for &m in members {
+            let _ = writeln!(out, "        {}", node_line(&graph.nodes[m]));
+        }
+        out.push_str("    }\n");
+    }
+    for &m in &loose {
+        let _ = writeln!(out, "    {}", node_line(&graph.nodes[m]));
+    }
+
+    for edge in &graph.edges {
+        let attrs = match edge.dep_kind.as_str() {
+            "runtime" => String::new(),
+            "dev" => format!(" [label={}, style=dashed]", dot_quote(&edge.dep_kind)),
+            other => format!(" [label={}]", dot_quote(other)),
+        };
+        let _ = writeln!(out, "    {} -> {}{};", dot_quote(&edge.from), dot_quote(&edge.to), attrs);
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn mermaid_label(s: &str) -> String {
+    // Mermaid has no escape for double quotes inside labels; use the HTML entity
+    format!("\"{}\"", s.replace('"', "#quot;"))
+}
+
+pub fn render_mermaid(graph: &GraphExport) -> String {
+    // Package names contain characters Mermaid ids can't (@, /, :), so use
+    // positional ids and put the real name in the label.
+    let ids: HashMap<&str, String> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (n.name.as_str(), format!("n{i}")))
+        .collect();
+
+    let mut out = String::from("graph LR\n");
+    let node_line = |i: usize| {
+        let node = &graph.nodes[i];
+        let label = mermaid_label(&node.name);
+        if node.internal {
+            format!("n{i}[{label}]")
+        } else {
+            format!("n{i}([{label}])")
+        }
+    };
+
+    let (grouped, loose) = clusters(graph);
+    for (c, (label, members)) in grouped.iter().enumerate() {
+        let _ = writeln!(out, "    subgraph c{c}[{}]", mermaid_label(label));
+        for &m in members {
+            let _ = writeln!(out, "        {}", node_line(m));
+        }
+        out.push_str("    end\n");
+    }
+    for &m in &loose {
+        let _ = writeln!(out, "    {}", node_line(m));
+    }
+
+    for edge in &graph.edges {
+        let (Some(from), Some(to)) = (ids.get(edge.from.as_str()), ids.get(edge.to.as_str())) else {
+            continue;
+        };
+        if edge.dep_kind == "runtime" {
+            let _ = writeln!(out, "    {from} --> {to}");
+        } else {
+            let _ = writeln!(out, "    {from} -.->|{}| {to}", edge.dep_kind);
+        }
+    }
+    out
+}
+
+pub fn render_graphml(graph: &GraphExport) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+         \x20 <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"path\" for=\"node\" attr.name=\"path\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"internal\" for=\"node\" attr.name=\"internal\" attr.type=\"boolean\"/>\n\
+         \x20 <key id=\"cluster\" for=\"node\" attr.name=\"cluster\" attr.type=\"string\"/>\n\
+         \x20 <key id=\"dep_kind\" for=\"edge\" attr.name=\"dep_kind\" attr.type=\"string\"/>\n\
+         \x20 <graph id=\"shire\" edgedefault=\"directed\">\n",
+    );
+
+    for node in &graph.nodes {
+        let _ = writeln!(out, "    <node id=\"{}\">", escape(node.name.as_str()));
+        if let Some(kind) = &node.kind {
+            let _ = writeln!(out, "      <data key=\"kind\">{}</data>", escape(kind.as_str()));
+        }
+        if let Some(path) = &node.path {
+            let _ = writeln!(out, "      <data key=\"path\">{}</data>", escape(path.as_str()));
+        }
+        let _ = writeln!(out, "      <data key=\"internal\">{}</data>", node.internal);
+        if let Some(cluster) = &node.cluster {
+            let _ = writeln!(out, "      <data key=\"cluster\">{}</data>", escape(cluster.as_str()));
+        }
+        out.push_str("    </node>\n");
+    }
+
+    for (i, edge) in graph.edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\">",
+            escape(edge.from.as_str()),
+            escape(edge.to.as_str())
+        );
+        let _ = writeln!(out, "      <data key=\"dep_kind\">{}</data>", escape(edge.dep_kind.as_str()));
+        out.push_str("    </edge>\n");
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> GraphExport {
+        GraphExport {
+            nodes: vec![
+                GraphNode {
+                    name: "@scope/web".into(),
+                    kind: Some("npm".into()),
+                    path: Some("apps/web".into()),
+                    internal: true,
+                    cluster: Some("apps".into()),
+                },
+                GraphNode {
+                    name: "shared".into(),
+                    kind: Some("npm".into()),
+                    path: Some("libs/shared".into()),
+                    internal: true,
+                    cluster: Some("libs".into()),
+                },
+                GraphNode {
+                    name: "jest".into(),
+                    kind: None,
+                    path: None,
+                    internal: false,
+                    cluster: None,
+                },
+            ],
+            edges: vec![
+                GraphEdge {
+                    from: "@scope/web".into(),
+                    to: "shared".into(),
+                    dep_kind: "runtime".into(),
+                },
+                GraphEdge {
+                    from: "@scope/web".into(),
+                    to: "jest".into(),
+                    dep_kind: "dev".into(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let dot = render_dot(&sample_graph());
+        assert!(dot.starts_with("digraph shire {"));
+        assert!(dot.contains("subgraph cluster_0 {"));
+        assert!(dot.contains("label=\"apps\";"));
+        assert!(dot.contains("\"jest\" [style=dashed];"));
+        assert!(dot.contains("\"@scope/web\" -> \"shared\";"));
+        assert!(dot.contains("\"@scope/web\" -> \"jest\" [label=\"dev\", style=dashed];"));
+    }
+
+    #[test]
+    fn test_render_mermaid_uses_safe_ids() {
+        let mermaid = render_mermaid(&sample_graph());
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains("subgraph c0[\"apps\"]"));
+        assert!(mermaid.contains("n0[\"@scope/web\"]"));
+        assert!(mermaid.contains("n2([\"jest\"])"));
+        assert!(mermaid.contains("n0 --> n1"));
+        assert!(mermaid.contains("n0 -.->|dev| n2"));
+    }
+
+    #[test]
+    fn test_render_graphml() {
+        let xml = render_graphml(&sample_graph());
+        assert!(xml.contains("<node id=\"@scope/web\">"));
+        assert!(xml.contains("<data key=\"internal\">false</data>"));
+        assert!(xml.contains("<edge id=\"e1\" source=\"@scope/web\" target=\"jest\">"));
+        assert!(xml.trim_end().ends_with("</graphml>"));
+    }
+
+    #[test]
+    fn test_path_cluster() {
+        assert_eq!(path_cluster("services/auth/api", 1), "services");
+        assert_eq!(path_cluster("services/auth/api", 2), "services/auth");
+        assert_eq!(path_cluster("", 1), "(root)");
+    }
+
+    #[test]
+    fn test_prune_unreachable_after_filtering() {
+        let edges = vec![
+            GraphEdge { from: "a".into(), to: "b".into(), dep_kind: "runtime".into() },
+            GraphEdge { from: "c".into(), to: "d".into(), dep_kind: "runtime".into() },
+        ];
+        let kept = prune_unreachable("a", edges, false);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].to, "b");
+    }
+}
@@ -0,0 +1,96 @@
+pub mod affected;
+pub mod check;
+pub mod export;
+pub mod query;
+
+use anyhow::Result;
+use serde::Serialize;
+
+/// How CLI commands render their results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn from_json_flag(json: bool) -> Self {
+        if json { OutputFormat::Json } else { OutputFormat::Table }
+    }
+}
+
+/// Print any serializable value as pretty JSON (same shape the MCP tools return).
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Print rows as a left-aligned, space-padded table with an uppercase header row.
+/// The last column is never padded so long descriptions don't add trailing whitespace.
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    if rows.is_empty() {
+        println!("No results.");
+        return;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| -> String {
+        let last = cells.len().saturating_sub(1);
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                if i == last {
+                    cell.to_string()
+                } else {
+                    format!("{:<width$}", cell, width = widths[i])
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(headers.to_vec()));
+    for row in rows {
+        println!("{}", format_row(row.iter().map(|s| s.as_str()).collect()));
+    }
+}
+
+/// Render an optional value as a table cell, using "-" for missing values.
+pub fn cell(value: Option<&str>) -> String {
+    value.unwrap_or("-").to_string()
+}
+
+/// Comma-separated list cell, `-` when empty.
+pub fn list_cell(values: &[String]) -> String {
+    if values.is_empty() {
+        "-".to_string()
+    } else {
+        values.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_from_flag() {
+        assert_eq!(OutputFormat::from_json_flag(true), OutputFormat::Json);
+        assert_eq!(OutputFormat::from_json_flag(false), OutputFormat::Table);
+    }
+
+    #[test]
+    fn test_cell_placeholder() {
+        assert_eq!(cell(Some("1.0.0")), "1.0.0");
+        assert_eq!(cell(None), "-");
+    }
+}
@@ -0,0 +1,520 @@
+use super::{OutputFormat, cell, list_cell, print_json, print_table};
+use crate::db::queries::{self, Page, Paged};
+use anyhow::{Result, bail};
+use clap::Subcommand;
+use rusqlite::Connection;
+
+/// `shire query` subcommands. Each one maps onto the MCP tool of the same
+/// name and calls the same `db::queries` function, so CLI and MCP answers match.
+#[derive(Subcommand)]
+pub enum QueryCommand {
+    /// Search packages by name, description, or path (full-text)
+    Search {
+        /// Search query
+        query: String,
+        /// Only show packages with this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Treat the query as raw SQLite FTS5 syntax (phrases, prefix*, AND/OR/NOT, NEAR, column:term)
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Show full details for a package by exact name
+    Package {
+        /// Exact package name
+        name: String,
+    },
+    /// List all indexed packages
+    List {
+        /// Filter by package kind (npm, go, cargo, python, ...)
+        #[arg(long)]
+        kind: Option<String>,
+        /// Only show packages with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// List what a package depends on
+    Deps {
+        /// Package name
+        name: String,
+        /// Only show dependencies that are packages in this repo
+        #[arg(long)]
+        internal_only: bool,
+    },
+    /// List packages that depend on a package
+    Dependents {
+        /// Package name
+        name: String,
+    },
+    /// List lockfile-resolved versions pulled in by a package, or which
+    /// packages resolve a given dependency
+    Resolved {
+        /// Package name
+        #[arg(required_unless_present = "dependency")]
+        name: Option<String>,
+        /// Only show resolutions of this dependency
+        #[arg(long)]
+        dependency: Option<String>,
+        /// Leave out transitive dependencies
+        #[arg(long)]
+        direct_only: bool,
+    },
+    /// Report external libraries declared with several different version requirements
+    Drift {
+        /// Only consider packages of this kind (npm, go, cargo, ...)
+        #[arg(long)]
+        kind: Option<String>,
+        /// Only show libraries with at least this many distinct version requirements
+        #[arg(long, default_value_t = 2)]
+        min_versions: usize,
+    },
+    /// Show the CODEOWNERS owners of a file, directory or package
+    WhoOwns {
+        /// File or directory path relative to the repo root, or a package name
+        path: String,
+    },
+    /// List packages owned by a team or user (from CODEOWNERS or package overrides)
+    PackagesByOwner {
+        /// Owner handle, e.g. "@acme/payments"
+        owner: String,
+    },
+    /// Rank packages (or files) by commit count over the `[history]` window
+    Hotspots {
+        /// Rank files instead of packages
+        #[arg(long)]
+        files: bool,
+        /// Only consider this package (or its files with --files)
+        #[arg(long)]
+        package: Option<String>,
+    },
+    /// List packages with no commits in the given number of days
+    Stale {
+        /// Days since the last commit
+        #[arg(long, default_value_t = 365)]
+        days: u32,
+    },
+    /// Show the transitive dependency graph from a package as edges
+    Graph {
+        /// Root package
+        name: String,
+        /// Maximum depth to traverse (capped at 20)
+        #[arg(long, default_value_t = 3)]
+        depth: u32,
+        /// Only follow internal dependencies
+        #[arg(long)]
+        internal_only: bool,
+    },
+    /// Show the shortest dependency chain(s) from one package to another
+    Path {
+        /// Source package (the one that depends)
+        from: String,
+        /// Target package or external dependency name
+        to: String,
+        /// Number of paths to show, shortest first
+        #[arg(long, default_value_t = 1)]
+        max_paths: usize,
+        /// Maximum path length in hops (capped at 20)
+        #[arg(long, default_value_t = 10)]
+        max_depth: u32,
+        /// Only follow internal dependencies
+        #[arg(long)]
+        internal_only: bool,
+    },
+    /// Topologically sort internal packages into a build order (dependencies first)
+    BuildOrder {
+        /// Only include these packages and their transitive internal dependencies
+        packages: Vec<String>,
+        /// Print waves of packages that can build in parallel instead of a sequential order
+        #[arg(long)]
+        waves: bool,
+        /// Ignore dev dependency edges
+        #[arg(long)]
+        exclude_dev: bool,
+    },
+    /// Search symbols, or list them for a package or file when no query is given
+    Symbols {
+        /// Full-text query over symbol names and signatures
+        query: Option<String>,
+        /// Restrict to a package (lists all of its symbols when no query is given)
+        #[arg(long)]
+        package: Option<String>,
+        /// List all symbols defined in this file (path relative to repo root)
+        #[arg(long, conflicts_with_all = ["query", "package"])]
+        file: Option<String>,
+        /// Filter by symbol kind (function, class, struct, interface, ...)
+        #[arg(long)]
+        kind: Option<String>,
+        /// Treat the query as raw SQLite FTS5 syntax (phrases, prefix*, AND/OR/NOT, NEAR, column:term)
+        #[arg(long, requires = "query")]
+        raw: bool,
+    },
+    /// Look up a symbol by exact name
+    Symbol {
+        /// Exact symbol name
+        name: String,
+        /// Restrict to a package
+        #[arg(long)]
+        package: Option<String>,
+    },
+    /// Search files by path, or list a package's files when no query is given
+    Files {
+        /// Full-text query over file paths
+        query: Option<String>,
+        /// Restrict to a package (lists all of its files when no query is given)
+        #[arg(long)]
+        package: Option<String>,
+        /// Filter by file extension (e.g. "ts", "go")
+        #[arg(long)]
+        extension: Option<String>,
+        /// Treat the query as raw SQLite FTS5 syntax (phrases, prefix*, AND/OR/NOT, NEAR, column:term)
+        #[arg(long, requires = "query")]
+        raw: bool,
+    },
+    /// Show index status: build time, git commit, counts, duration
+    Status,
+}
+
+/// Run a query subcommand against an open index and print the result. List
+/// and search subcommands return `page` of their results.
+pub fn run(conn: &Connection, command: QueryCommand, page: Page, format: OutputFormat) -> Result<()> {
+    match command {
+        QueryCommand::Search { query, tag, raw } => {
+            let results = queries::search_packages(conn, &query, tag.as_deref(), raw, page)?;
+            print_packages(&results, format)
+        }
+        QueryCommand::Package { name } => {
+            let Some(pkg) = queries::get_package(conn, &name)? else {
+                bail!("Package '{name}' not found");
+            };
+            match format {
+                OutputFormat::Json => print_json(&pkg),
+                OutputFormat::Table => {
+                    let rows = vec![
+                        vec!["name".into(), pkg.name.clone()],
+                        vec!["path".into(), pkg.path.clone()],
+                        vec!["kind".into(), pkg.kind.clone()],
+                        vec!["version".into(), cell(pkg.version.as_deref())],
+                        vec!["description".into(), cell(pkg.description.as_deref())],
+                        vec!["metadata".into(), cell(pkg.metadata.as_deref())],
+                        vec!["tags".into(), list_cell(&pkg.tags)],
+                        vec!["owners".into(), list_cell(&pkg.owners)],
+                        vec!["lifecycle".into(), cell(pkg.lifecycle.as_deref())],
+                        vec![
+                            "labels".into(),
+                            list_cell(&pkg.labels.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>()),
+                        ],
+                    ];
+                    print_table(&["FIELD", "VALUE"], &rows);
+                    Ok(())
+                }
+            }
+        }
+        QueryCommand::List { kind, tag } => {
+            let results = queries::list_packages(conn, kind.as_deref(), tag.as_deref(), page)?;
+            print_packages(&results, format)
+        }
+        QueryCommand::Deps { name, internal_only } => {
+            let results = queries::package_dependencies(conn, &name, internal_only, page)?;
+            print_dependencies(&results, |d| d.dependency.clone(), "DEPENDENCY", format)
+        }
+        QueryCommand::Dependents { name } => {
+            let results = queries::package_dependents(conn, &name, page)?;
+            print_dependencies(&results, |d| d.package.clone(), "PACKAGE", format)
+        }
+        QueryCommand::Resolved {
+            name,
+            dependency,
+            direct_only,
+        } => {
+            let results =
+                queries::resolved_dependencies(conn, name.as_deref(), dependency.as_deref(), direct_only, page)?;
+            print_paged(&results, format, &["PACKAGE", "DEPENDENCY", "VERSION", "DIRECT", "LOCKFILE"], |r| {
+                vec![
+                    r.package.clone(),
+                    r.dependency.clone(),
+                    r.version.clone(),
+                    if r.is_direct { "yes" } else { "no" }.to_string(),
+                    r.lockfile.clone(),
+                ]
+            })
+        }
+        QueryCommand::Drift { kind, min_versions } => {
+            let drift = queries::version_drift(conn, kind.as_deref(), min_versions, page)?;
+            match format {
+                OutputFormat::Json => print_json(&drift),
+                OutputFormat::Table => {
+                    let rows: Vec<Vec<String>> = drift
+                        .iter()
+                        .flat_map(|d| {
+                            d.versions.iter().map(move |v| {
+                                vec![
+                                    d.dependency.clone(),
+                                    d.kind.clone(),
+                                    d.version_count.to_string(),
+                                    v.version_req.clone(),
+                                    v.packages.join(", "),
+                                ]
+                            })
+                        })
+                        .collect();
+                    print_table(&["DEPENDENCY", "KIND", "VERSIONS", "VERSION_REQ", "PACKAGES"], &rows);
+                    print_more(&drift);
+                    Ok(())
+                }
+            }
+        }
+        QueryCommand::WhoOwns { path } => {
+            let Some(ownership) = queries::who_owns(conn, &path)? else {
+                bail!("'{path}' is not an indexed file, directory or package");
+            };
+            match format {
+                OutputFormat::Json => print_json(&ownership),
+                OutputFormat::Table => {
+                    let rows = vec![
+                        vec!["path".into(), ownership.path.clone()],
+                        vec!["owners".into(), list_cell(&ownership.owners)],
+                        vec!["package".into(), cell(ownership.package.as_deref())],
+                        vec!["package_owners".into(), list_cell(&ownership.package_owners)],
+                    ];
+                    print_table(&["FIELD", "VALUE"], &rows);
+                    Ok(())
+                }
+            }
+        }
+        QueryCommand::PackagesByOwner { owner } => {
+            let results = queries::packages_by_owner(conn, &owner, page)?;
+            print_packages(&results, format)
+        }
+        QueryCommand::Hotspots { files, package } => {
+            let page = Page {
+                limit: page.limit.or(Some(20)),
+                ..page
+            };
+            let results = queries::churn_hotspots(conn, files, package.as_deref(), page)?;
+            print_churn(&results, format)
+        }
+        QueryCommand::Stale { days } => {
+            let results = queries::stale_packages(conn, days, page)?;
+            print_churn(&results, format)
+        }
+        QueryCommand::Graph {
+            name,
+            depth,
+            internal_only,
+        } => {
+            let edges = queries::dependency_graph(conn, &name, depth.min(20), internal_only, page)?;
+            print_paged(&edges, format, &["FROM", "TO", "KIND"], |e| {
+                vec![e.from.clone(), e.to.clone(), e.dep_kind.clone()]
+            })
+        }
+        QueryCommand::Path {
+            from,
+            to,
+            max_paths,
+            max_depth,
+            internal_only,
+        } => {
+            let paths = queries::dependency_paths(
+                conn,
+                &from,
+                &to,
+                max_paths.min(50),
+                max_depth.min(20),
+                internal_only,
+            )?;
+            match format {
+                OutputFormat::Json => print_json(&paths),
+                OutputFormat::Table => {
+                    if paths.is_empty() && !paths.truncated {
+                        println!("No dependency path from {from} to {to}.");
+                    }
+                    for path in paths.iter() {
+                        let hops: Vec<String> = path
+                            .edges
+                            .iter()
+                            .map(|e| format!(" -[{}]-> {}", e.dep_kind, e.to))
+                            .collect();
+                        println!("{}{}", from, hops.concat());
+                    }
+                    if paths.truncated {
+                        println!("Search stopped early on a large graph; more paths may exist.");
+                    }
+                    Ok(())
+                }
+            }
+        }
+        QueryCommand::BuildOrder {
+            packages,
+            waves,
+            exclude_dev,
+        } => {
+            let result = queries::build_order(conn, &packages, exclude_dev)?;
+            match (format, waves) {
+                (OutputFormat::Json, true) => print_json(&result.waves),
+                (OutputFormat::Json, false) => print_json(&result.order),
+                (OutputFormat::Table, true) => {
+                    for (i, wave) in result.waves.iter().enumerate() {
+                        println!("Wave {}: {}", i + 1, wave.join(" "));
+                    }
+                    Ok(())
+                }
+                (OutputFormat::Table, false) => {
+                    for name in &result.order {
+                        println!("{name}");
+                    }
+                    Ok(())
+                }
+            }
+        }
+        QueryCommand::Symbols {
+            query,
+            package,
+            file,
+            kind,
+            raw,
+        } => {
+            let results = match (query, package, file) {
+                (_, _, Some(file)) => queries::get_file_symbols(conn, &file, kind.as_deref(), page)?,
+                (Some(query), package, None) => {
+                    queries::search_symbols(conn, &query, package.as_deref(), kind.as_deref(), raw, page)?
+                }
+                (None, Some(package), None) => {
+                    queries::get_package_symbols(conn, &package, kind.as_deref(), page)?
+                }
+                (None, None, None) => bail!("Provide a search query, --package, or --file"),
+            };
+            print_symbols(&results, format)
+        }
+        QueryCommand::Symbol { name, package } => {
+            let results = queries::get_symbol(conn, &name, package.as_deref(), page)?;
+            print_symbols(&results, format)
+        }
+        QueryCommand::Files {
+            query,
+            package,
+            extension,
+            raw,
+        } => {
+            let results = match (query, package) {
+                (Some(query), package) => {
+                    queries::search_files(conn, &query, package.as_deref(), extension.as_deref(), raw, page)?
+                }
+                (None, Some(package)) => {
+                    queries::list_package_files(conn, &package, extension.as_deref(), page)?
+                }
+                (None, None) => bail!("Provide a search query or --package"),
+            };
+            print_paged(&results, format, &["PATH", "PACKAGE", "EXT", "SIZE"], |f| {
+                vec![
+                    f.path.clone(),
+                    cell(f.package.as_deref()),
+                    f.extension.clone(),
+                    f.size_bytes.to_string(),
+                ]
+            })
+        }
+        QueryCommand::Status => {
+            let status = queries::index_status(conn)?;
+            match format {
+                OutputFormat::Json => print_json(&status),
+                OutputFormat::Table => {
+                    let rows = vec![
+                        vec!["indexed_at".into(), cell(status.indexed_at.as_deref())],
+                        vec!["git_commit".into(), cell(status.git_commit.as_deref())],
+                        vec!["package_count".into(), cell(status.package_count.as_deref())],
+                        vec!["symbol_count".into(), cell(status.symbol_count.as_deref())],
+                        vec!["file_count".into(), cell(status.file_count.as_deref())],
+                        vec!["total_duration_ms".into(), cell(status.total_duration_ms.as_deref())],
+                    ];
+                    print_table(&["FIELD", "VALUE"], &rows);
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Print one page of results: the whole envelope as JSON, or a table of the
+/// rows with a note on how to get the next page when there are more.
+fn print_paged<T: serde::Serialize>(
+    paged: &Paged<T>,
+    format: OutputFormat,
+    headers: &[&str],
+    row: impl Fn(&T) -> Vec<String>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => print_json(paged),
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = paged.iter().map(row).collect();
+            print_table(headers, &rows);
+            print_more(paged);
+            Ok(())
+        }
+    }
+}
+
+fn print_more<T>(paged: &Paged<T>) {
+    if let Some(next) = paged.next_offset {
+        println!(
+            "Showing {}-{} of {}; pass --offset {next} for more.",
+            paged.offset + 1,
+            next,
+            paged.total
+        );
+    }
+}
+
+fn print_packages(packages: &Paged<queries::PackageRow>, format: OutputFormat) -> Result<()> {
+    print_paged(packages, format, &["NAME", "KIND", "VERSION", "PATH", "TAGS", "DESCRIPTION"], |p| {
+        vec![
+            p.name.clone(),
+            p.kind.clone(),
+            cell(p.version.as_deref()),
+            p.path.clone(),
+            list_cell(&p.tags),
+            cell(p.description.as_deref()),
+        ]
+    })
+}
+
+fn print_churn(rows: &Paged<queries::ChurnRow>, format: OutputFormat) -> Result<()> {
+    print_paged(rows, format, &["PATH", "PACKAGE", "COMMITS", "LAST_COMMIT", "TOP_AUTHORS"], |r| {
+        let authors: Vec<String> = r.top_authors.iter().map(|a| format!("{} ({})", a.name, a.commits)).collect();
+        vec![
+            r.path.clone(),
+            cell(r.package.as_deref()),
+            r.commit_count.to_string(),
+            cell(r.last_commit_at.as_deref()),
+            list_cell(&authors),
+        ]
+    })
+}
+
+fn print_dependencies(
+    deps: &Paged<queries::DependencyRow>,
+    name_of: impl Fn(&queries::DependencyRow) -> String,
+    name_header: &str,
+    format: OutputFormat,
+) -> Result<()> {
+    print_paged(deps, format, &[name_header, "KIND", "VERSION", "RESOLVED", "INTERNAL"], |d| {
+        vec![
+            name_of(d),
+            d.dep_kind.clone(),
+            cell(d.version_req.as_deref()),
+            list_cell(&d.resolved_versions),
+            if d.is_internal { "yes" } else { "no" }.to_string(),
+        ]
+    })
+}
+
+fn print_symbols(symbols: &Paged<queries::SymbolRow>, format: OutputFormat) -> Result<()> {
+    print_paged(symbols, format, &["NAME", "KIND", "PACKAGE", "LOCATION", "SIGNATURE"], |s| {
+        vec![
+            s.name.clone(),
+            s.kind.clone(),
+            s.package.clone(),
+            format!("{}:{}", s.file_path, s.line),
+            cell(s.signature.as_deref()),
+        ]
+    })
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 #[derive(Debug, Deserialize, Default, Clone)]
@@ -14,6 +15,31 @@ pub struct Config {
     pub symbols: SymbolsConfig,
     #[serde(default)]
     pub watch: WatchConfig,
+    #[serde(default)]
+    pub drift: DriftConfig,
+    #[serde(default)]
+    pub rules: Vec<LayeringRule>,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub federation: Vec<FederatedIndex>,
+}
+
+/// Another repo's index that `shire serve` answers queries across.
+#[derive(Debug, Deserialize, Clone)]
+pub struct FederatedIndex {
+    /// Name results from this repo are tagged with (defaults to the repo's directory name)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Path to the repo's index database, relative to shire.toml
+    pub db: String,
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct DriftConfig {
+    /// External libraries that must be declared with a single version requirement
+    #[serde(default)]
+    pub single_version: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Default, Clone)]
@@ -30,12 +56,47 @@ fn default_debounce_ms() -> u64 {
 pub struct WatchConfig {
     #[serde(default = "default_debounce_ms")]
     pub debounce_ms: u64,
+    /// Watch the filesystem (inotify) instead of relying only on rebuild signals
+    #[serde(default)]
+    pub native: bool,
 }
 
 impl Default for WatchConfig {
     fn default() -> Self {
         Self {
             debounce_ms: default_debounce_ms(),
+            native: false,
+        }
+    }
+}
+
+fn default_history_window_days() -> u32 {
+    90
+}
+
+fn default_top_authors() -> usize {
+    3
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct HistoryConfig {
+    /// Run `git log` during builds to record churn, last-commit dates and authors
+    #[serde(default)]
+    pub enabled: bool,
+    /// Days of history counted towards commit counts and top authors
+    #[serde(default = "default_history_window_days")]
+    pub window_days: u32,
+    /// Number of top authors kept per file and package
+    #[serde(default = "default_top_authors")]
+    pub top_authors: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_days: default_history_window_days(),
+            top_authors: default_top_authors(),
         }
     }
 }
@@ -79,6 +140,7 @@ impl Default for DiscoveryConfig {
 fn default_manifests() -> Vec<String> {
     vec![
         "package.json".into(),
+        "pnpm-workspace.yaml".into(),
         "go.mod".into(),
         "go.work".into(),
         "Cargo.toml".into(),
@@ -90,6 +152,16 @@ fn default_manifests() -> Vec<String> {
         "settings.gradle.kts".into(),
         "cpanfile".into(),
         "Gemfile".into(),
+        "*.csproj".into(),
+        "*.fsproj".into(),
+        "*.sln".into(),
+        "Package.swift".into(),
+        "Cargo.lock".into(),
+        "package-lock.json".into(),
+        "pnpm-lock.yaml".into(),
+        "yarn.lock".into(),
+        "go.sum".into(),
+        "poetry.lock".into(),
     ]
 }
 
@@ -107,10 +179,55 @@ fn default_exclude() -> Vec<String> {
     ]
 }
 
+/// A `[[rules]]` entry: internal dependencies of packages matching `from` must
+/// not match any `deny` selector and, when `allow` is non-empty, must match one
+/// of the `allow` selectors.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LayeringRule {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub from: RuleSelector,
+    #[serde(default)]
+    pub allow: Vec<RuleSelector>,
+    #[serde(default)]
+    pub deny: Vec<RuleSelector>,
+    /// Don't apply the rule to dev dependency edges
+    #[serde(default)]
+    pub exclude_dev: bool,
+}
+
+/// Selects packages by path glob, name glob and/or kind. Every field that is
+/// set must match; an empty selector matches every package.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct RuleSelector {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+/// A `[[packages]]` entry. Applies to the package named `name`, or to every
+/// package whose path or name matches the `match` glob.
 #[derive(Debug, Deserialize, Clone)]
 pub struct PackageOverride {
-    pub name: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "match")]
+    pub pattern: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub owners: Vec<String>,
+    /// Lifecycle status, e.g. "active", "experimental" or "deprecated"
+    #[serde(default)]
+    pub lifecycle: Option<String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
 }
 
 pub fn load_config(repo_root: &Path) -> Result<Config> {
@@ -131,7 +248,7 @@ mod tests {
     #[test]
     fn test_default_config() {
         let config = Config::default();
-        assert_eq!(config.discovery.manifests.len(), 12);
+        assert_eq!(config.discovery.manifests.len(), 23);
         assert!(config.discovery.exclude.contains(&"node_modules".to_string()));
         assert!(config.discovery.exclude.contains(&".gradle".to_string()));
         assert!(config.discovery.exclude.contains(&"build".to_string()));
@@ -152,7 +269,31 @@ description = "Deprecated auth service"
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.discovery.manifests.len(), 2);
         assert_eq!(config.packages.len(), 1);
-        assert_eq!(config.packages[0].name, "legacy-auth");
+        assert_eq!(config.packages[0].name.as_deref(), Some("legacy-auth"));
+    }
+
+    #[test]
+    fn test_parse_package_override_tags() {
+        let toml_str = r#"
+[[packages]]
+name = "legacy-auth"
+tags = ["deprecated", "security"]
+owners = ["@acme/identity"]
+lifecycle = "deprecated"
+labels = { tier = "1", oncall = "identity-primary" }
+
+[[packages]]
+match = "services/*"
+tags = ["service"]
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let legacy = &config.packages[0];
+        assert_eq!(legacy.tags, vec!["deprecated", "security"]);
+        assert_eq!(legacy.owners, vec!["@acme/identity"]);
+        assert_eq!(legacy.lifecycle.as_deref(), Some("deprecated"));
+        assert_eq!(legacy.labels.get("tier").map(String::as_str), Some("1"));
+        assert_eq!(config.packages[1].name, None);
+        assert_eq!(config.packages[1].pattern.as_deref(), Some("services/*"));
     }
 
     #[test]
@@ -178,11 +319,77 @@ exclude_extensions = [".proto", ".pl"]
         assert_eq!(config.symbols.exclude_extensions, vec![".proto", ".pl"]);
     }
 
+    #[test]
+    fn test_parse_drift_config() {
+        let toml_str = r#"
+[drift]
+single_version = ["react", "lodash"]
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.drift.single_version, vec!["react", "lodash"]);
+        assert!(Config::default().drift.single_version.is_empty());
+    }
+
+    #[test]
+    fn test_parse_history_config() {
+        let config: Config = toml::from_str("[history]\nenabled = true\nwindow_days = 30\n").unwrap();
+        assert!(config.history.enabled);
+        assert_eq!(config.history.window_days, 30);
+        assert_eq!(config.history.top_authors, 3);
+
+        let default = Config::default();
+        assert!(!default.history.enabled);
+        assert_eq!(default.history.window_days, 90);
+    }
+
+    #[test]
+    fn test_parse_layering_rules() {
+        let toml_str = r#"
+[[rules]]
+name = "core-is-foundational"
+description = "Core libraries must not reach into services"
+from = { path = "libs/core/**" }
+deny = [{ path = "services/**" }]
+
+[[rules]]
+name = "ui-boundary"
+from = { path = "ui/**", kind = "npm" }
+allow = [{ path = "ui/**" }, { path = "shared/**" }]
+exclude_dev = true
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.rules[0].from.path.as_deref(), Some("libs/core/**"));
+        assert_eq!(config.rules[0].deny.len(), 1);
+        assert!(config.rules[0].allow.is_empty());
+        assert_eq!(config.rules[1].from.kind.as_deref(), Some("npm"));
+        assert_eq!(config.rules[1].allow.len(), 2);
+        assert!(config.rules[1].exclude_dev);
+    }
+
+    #[test]
+    fn test_parse_federation() {
+        let toml_str = r#"
+[[federation]]
+db = "../auth/.shire/index.db"
+
+[[federation]]
+name = "web"
+db = "../web-frontend/.shire/index.db"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.federation.len(), 2);
+        assert_eq!(config.federation[0].name, None);
+        assert_eq!(config.federation[0].db, "../auth/.shire/index.db");
+        assert_eq!(config.federation[1].name.as_deref(), Some("web"));
+        assert!(Config::default().federation.is_empty());
+    }
+
     #[test]
     fn test_load_missing_config_returns_default() {
         let dir = tempfile::TempDir::new().unwrap();
         let config = load_config(dir.path()).unwrap();
-        assert_eq!(config.discovery.manifests.len(), 12);
+        assert_eq!(config.discovery.manifests.len(), 23);
     }
 
     #[test]
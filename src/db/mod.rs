@@ -1,3 +1,4 @@
+pub mod pool;
 pub mod queries;
 
 use anyhow::Result;
@@ -21,7 +22,29 @@ pub fn open_readonly(path: &std::path::Path) -> Result<Connection> {
     Ok(conn)
 }
 
+/// Indexes built before symbols had `name_words` get their symbol tables
+/// recreated. Clearing the source hashes makes the next build re-extract
+/// every package's symbols into them.
+fn migrate(conn: &Connection) -> Result<()> {
+    let symbol_columns: Vec<String> = conn
+        .prepare("SELECT name FROM pragma_table_info('symbols')")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    if !symbol_columns.is_empty() && !symbol_columns.iter().any(|c| c == "name_words") {
+        conn.execute_batch(
+            "DROP TRIGGER IF EXISTS symbols_ai;
+             DROP TRIGGER IF EXISTS symbols_ad;
+             DROP TRIGGER IF EXISTS symbols_au;
+             DROP TABLE IF EXISTS symbols_fts;
+             DROP TABLE symbols;
+             DROP TABLE IF EXISTS source_hashes;",
+        )?;
+    }
+    Ok(())
+}
+
 fn create_schema(conn: &Connection) -> Result<()> {
+    migrate(conn)?;
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS packages (
@@ -42,6 +65,28 @@ fn create_schema(conn: &Connection) -> Result<()> {
             PRIMARY KEY (package, dependency, dep_kind)
         );
 
+        CREATE TABLE IF NOT EXISTS resolved_dependencies (
+            package     TEXT NOT NULL REFERENCES packages(name),
+            dependency  TEXT NOT NULL,
+            version     TEXT NOT NULL,
+            is_direct   INTEGER NOT NULL DEFAULT 0,
+            lockfile    TEXT NOT NULL,
+            PRIMARY KEY (package, dependency, version)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_resolved_dependencies_dependency
+            ON resolved_dependencies(dependency);
+
+        CREATE TABLE IF NOT EXISTS package_tags (
+            package TEXT NOT NULL REFERENCES packages(name),
+            kind    TEXT NOT NULL,
+            name    TEXT NOT NULL,
+            value   TEXT,
+            PRIMARY KEY (package, kind, name)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_package_tags_name ON package_tags(kind, name);
+
         CREATE VIRTUAL TABLE IF NOT EXISTS packages_fts USING fts5(
             name, description, path,
             content='packages',
@@ -70,6 +115,19 @@ fn create_schema(conn: &Connection) -> Result<()> {
             value TEXT
         );
 
+        CREATE TABLE IF NOT EXISTS federated_repos (
+            name       TEXT PRIMARY KEY,
+            db_path    TEXT NOT NULL,
+            repo_root  TEXT,
+            indexed_at TEXT,
+            git_commit TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS package_repos (
+            package TEXT PRIMARY KEY REFERENCES packages(name),
+            repo    TEXT NOT NULL REFERENCES federated_repos(name)
+        );
+
         CREATE TABLE IF NOT EXISTS manifest_hashes (
             path         TEXT PRIMARY KEY,
             content_hash TEXT NOT NULL
@@ -92,6 +150,28 @@ fn create_schema(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_files_package ON files(package);
         CREATE INDEX IF NOT EXISTS idx_files_extension ON files(extension);
 
+        CREATE TABLE IF NOT EXISTS file_owners (
+            path  TEXT NOT NULL,
+            owner TEXT NOT NULL,
+            PRIMARY KEY (path, owner)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_file_owners_owner ON file_owners(owner COLLATE NOCASE);
+
+        CREATE TABLE IF NOT EXISTS file_history (
+            path           TEXT PRIMARY KEY,
+            commit_count   INTEGER NOT NULL,
+            last_commit_at TEXT,
+            top_authors    TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS package_history (
+            package        TEXT PRIMARY KEY REFERENCES packages(name),
+            commit_count   INTEGER NOT NULL,
+            last_commit_at TEXT,
+            top_authors    TEXT
+        );
+
         CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(
             path,
             content='files',
@@ -126,7 +206,8 @@ fn create_schema(conn: &Connection) -> Result<()> {
             visibility    TEXT NOT NULL DEFAULT 'public',
             parent_symbol TEXT,
             return_type   TEXT,
-            parameters    TEXT
+            parameters    TEXT,
+            name_words    TEXT
         );
 
         CREATE INDEX IF NOT EXISTS idx_symbols_package ON symbols(package);
@@ -134,26 +215,26 @@ fn create_schema(conn: &Connection) -> Result<()> {
         CREATE INDEX IF NOT EXISTS idx_symbols_file_path ON symbols(file_path);
 
         CREATE VIRTUAL TABLE IF NOT EXISTS symbols_fts USING fts5(
-            name, kind, signature, file_path,
+            name, name_words, kind, signature, file_path,
             content='symbols',
             content_rowid='rowid'
         );
 
         CREATE TRIGGER IF NOT EXISTS symbols_ai AFTER INSERT ON symbols BEGIN
-            INSERT INTO symbols_fts(rowid, name, kind, signature, file_path)
-            VALUES (new.rowid, new.name, new.kind, new.signature, new.file_path);
+            INSERT INTO symbols_fts(rowid, name, name_words, kind, signature, file_path)
+            VALUES (new.rowid, new.name, new.name_words, new.kind, new.signature, new.file_path);
         END;
 
         CREATE TRIGGER IF NOT EXISTS symbols_ad AFTER DELETE ON symbols BEGIN
-            INSERT INTO symbols_fts(symbols_fts, rowid, name, kind, signature, file_path)
-            VALUES ('delete', old.rowid, old.name, old.kind, old.signature, old.file_path);
+            INSERT INTO symbols_fts(symbols_fts, rowid, name, name_words, kind, signature, file_path)
+            VALUES ('delete', old.rowid, old.name, old.name_words, old.kind, old.signature, old.file_path);
         END;
 
         CREATE TRIGGER IF NOT EXISTS symbols_au AFTER UPDATE ON symbols BEGIN
-            INSERT INTO symbols_fts(symbols_fts, rowid, name, kind, signature, file_path)
-            VALUES ('delete', old.rowid, old.name, old.kind, old.signature, old.file_path);
-            INSERT INTO symbols_fts(rowid, name, kind, signature, file_path)
-            VALUES (new.rowid, new.name, new.kind, new.signature, new.file_path);
+            INSERT INTO symbols_fts(symbols_fts, rowid, name, name_words, kind, signature, file_path)
+            VALUES ('delete', old.rowid, old.name, old.name_words, old.kind, old.signature, old.file_path);
+            INSERT INTO symbols_fts(rowid, name, name_words, kind, signature, file_path)
+            VALUES (new.rowid, new.name, new.name_words, new.kind, new.signature, new.file_path);
         END;
         ",
     )?;
@@ -188,7 +269,14 @@ mod tests {
             .unwrap();
         assert!(tables.contains(&"packages".to_string()));
         assert!(tables.contains(&"dependencies".to_string()));
+        assert!(tables.contains(&"resolved_dependencies".to_string()));
+        assert!(tables.contains(&"package_tags".to_string()));
+        assert!(tables.contains(&"file_owners".to_string()));
+        assert!(tables.contains(&"file_history".to_string()));
+        assert!(tables.contains(&"package_history".to_string()));
         assert!(tables.contains(&"shire_meta".to_string()));
+        assert!(tables.contains(&"federated_repos".to_string()));
+        assert!(tables.contains(&"package_repos".to_string()));
         assert!(tables.contains(&"manifest_hashes".to_string()));
         assert!(tables.contains(&"source_hashes".to_string()));
         assert!(tables.contains(&"files".to_string()));
@@ -214,6 +302,36 @@ mod tests {
         assert_eq!(results, vec!["auth-service"]);
     }
 
+    #[test]
+    fn test_migrate_recreates_symbols_without_name_words() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE symbols (id INTEGER PRIMARY KEY, package TEXT, name TEXT, kind TEXT,
+                                   signature TEXT, file_path TEXT, line INTEGER);
+             CREATE VIRTUAL TABLE symbols_fts USING fts5(name, kind, signature, file_path,
+                                                         content='symbols', content_rowid='rowid');
+             CREATE TABLE source_hashes (package TEXT PRIMARY KEY, content_hash TEXT NOT NULL, hashed_at TEXT);
+             INSERT INTO source_hashes (package, content_hash) VALUES ('app', 'abc');",
+        )
+        .unwrap();
+        create_schema(&conn).unwrap();
+
+        let hashes: i64 = conn
+            .query_row("SELECT COUNT(*) FROM source_hashes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(hashes, 0, "symbols are re-extracted on the next build");
+        conn.execute_batch(
+            "INSERT INTO packages (name, path, kind) VALUES ('app', 'app', 'npm');
+             INSERT INTO symbols (package, name, kind, file_path, line, name_words)
+             VALUES ('app', 'parseConfig', 'function', 'app/main.ts', 1, 'parse config');",
+        )
+        .unwrap();
+        let found: String = conn
+            .query_row("SELECT name FROM symbols_fts WHERE symbols_fts MATCH 'config'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(found, "parseConfig");
+    }
+
     #[test]
     fn test_schema_is_idempotent() {
         let conn = in_memory_db();
@@ -0,0 +1,236 @@
+use anyhow::{Result, anyhow};
+use rusqlite::Connection;
+use std::ops::Deref;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Idle connections kept for reuse; busier moments open extra ones.
+const MAX_IDLE: usize = 8;
+
+/// Read-only connections to the index, shared by concurrent MCP sessions so
+/// they don't serialize on one connection. When [`Pool::refresh`] sees a new
+/// build, the generation is bumped and older connections are reopened on
+/// their next checkout, so nobody keeps reading an old snapshot.
+#[derive(Debug)]
+pub struct Pool {
+    db_path: PathBuf,
+    idle: Mutex<Vec<Idle>>,
+    /// Bumped whenever a new build is detected.
+    generation: AtomicU64,
+    loaded: Mutex<Loaded>,
+}
+
+#[derive(Debug)]
+struct Idle {
+    generation: u64,
+    conn: Connection,
+}
+
+/// The build the current generation was opened on.
+#[derive(Debug, PartialEq)]
+struct Loaded {
+    file_id: Option<(u64, u64)>,
+    indexed_at: Option<String>,
+}
+
+/// A connection checked out of the pool; returned to it on drop.
+pub struct PooledConnection<'a> {
+    pool: &'a Pool,
+    generation: u64,
+    conn: Option<Connection>,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection present until drop")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else {
+            return;
+        };
+        if self.generation != self.pool.generation() {
+            return;
+        }
+        if let Ok(mut idle) = self.pool.idle.lock()
+            && idle.len() < MAX_IDLE
+        {
+            idle.push(Idle {
+                generation: self.generation,
+                conn,
+            });
+        }
+    }
+}
+
+/// Device and inode of the database file, so a rebuild that replaces the
+/// file (rather than writing to it) is noticed too.
+pub(crate) fn file_id(path: &Path) -> Option<(u64, u64)> {
+    std::fs::metadata(path).ok().map(|m| (m.dev(), m.ino()))
+}
+
+pub(crate) fn indexed_at(conn: &Connection) -> Option<String> {
+    conn.query_row(
+        "SELECT value FROM shire_meta WHERE key = 'indexed_at'",
+        [],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+impl Pool {
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let conn = super::open_readonly(db_path)?;
+        let loaded = Loaded {
+            file_id: file_id(db_path),
+            indexed_at: indexed_at(&conn),
+        };
+        Ok(Self {
+            db_path: db_path.to_path_buf(),
+            idle: Mutex::new(vec![Idle {
+                generation: 0,
+                conn,
+            }]),
+            generation: AtomicU64::new(0),
+            loaded: Mutex::new(loaded),
+        })
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Check out a connection on the current generation of the index.
+    pub fn get(&self) -> Result<PooledConnection<'_>> {
+        let generation = self.generation();
+        let reused = {
+            let mut idle = self.idle.lock().map_err(|e| anyhow!(e.to_string()))?;
+            // Connections from older generations are dropped on the way
+            std::iter::from_fn(|| idle.pop()).find(|c| c.generation == generation)
+        };
+        let conn = match reused {
+            Some(idle) => idle.conn,
+            None => super::open_readonly(&self.db_path)?,
+        };
+        Ok(PooledConnection {
+            pool: self,
+            generation,
+            conn: Some(conn),
+        })
+    }
+
+    /// Start a new generation if the index was rebuilt since the current one
+    /// was opened. Returns the current generation.
+    pub fn refresh(&self) -> Result<u64> {
+        let mut loaded = self.loaded.lock().map_err(|e| anyhow!(e.to_string()))?;
+        let Some(id) = file_id(&self.db_path) else {
+            // Missing while being replaced; keep serving the old snapshot
+            return Ok(self.generation());
+        };
+        let current = Loaded {
+            file_id: Some(id),
+            indexed_at: indexed_at(&*self.get()?),
+        };
+        if *loaded == current {
+            return Ok(self.generation());
+        }
+
+        let generation = self.generation.fetch_add(1, Ordering::AcqRel) + 1;
+        self.idle
+            .lock()
+            .map_err(|e| anyhow!(e.to_string()))?
+            .clear();
+        *loaded = Loaded {
+            file_id: Some(id),
+            indexed_at: indexed_at(&*self.get()?),
+        };
+        Ok(generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_meta(path: &Path, key: &str, value: &str) {
+        let conn = crate::db::open_or_create(path).unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO shire_meta (key, value) VALUES (?1, ?2)",
+            [key, value],
+        )
+        .unwrap();
+    }
+
+    fn meta(conn: &Connection, key: &str) -> Option<String> {
+        conn.query_row(
+            "SELECT value FROM shire_meta WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn test_concurrent_checkouts_reuse_connections() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        set_meta(&db_path, "indexed_at", "2026-01-01T00:00:00Z");
+        let pool = Pool::open(&db_path).unwrap();
+
+        {
+            let a = pool.get().unwrap();
+            let b = pool.get().unwrap();
+            assert_eq!(meta(&a, "indexed_at"), meta(&b, "indexed_at"));
+            assert!(pool.idle.lock().unwrap().is_empty());
+        }
+        assert_eq!(pool.idle.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_refresh_after_rebuild() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        set_meta(&db_path, "indexed_at", "2026-01-01T00:00:00Z");
+        let pool = Pool::open(&db_path).unwrap();
+        assert_eq!(pool.refresh().unwrap(), 0);
+
+        let held = pool.get().unwrap();
+        set_meta(&db_path, "indexed_at", "2026-01-01T00:05:00Z");
+        assert_eq!(pool.refresh().unwrap(), 1);
+        assert_eq!(pool.refresh().unwrap(), 1, "only one generation per build");
+
+        // A connection checked out before the rebuild isn't reused after it
+        drop(held);
+        assert!(pool.idle.lock().unwrap().iter().all(|c| c.generation == 1));
+        assert_eq!(
+            meta(&pool.get().unwrap(), "indexed_at").as_deref(),
+            Some("2026-01-01T00:05:00Z")
+        );
+    }
+
+    #[test]
+    fn test_refresh_after_file_replaced() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        set_meta(&db_path, "indexed_at", "2026-01-01T00:00:00Z");
+        let pool = Pool::open(&db_path).unwrap();
+
+        // Build a new index elsewhere and move it over the old one
+        let other = dir.path().join("new.db");
+        set_meta(&other, "indexed_at", "2026-01-01T00:00:00Z");
+        set_meta(&other, "package_count", "7");
+        std::fs::rename(&other, &db_path).unwrap();
+
+        assert_eq!(pool.refresh().unwrap(), 1);
+        assert_eq!(
+            meta(&pool.get().unwrap(), "package_count").as_deref(),
+            Some("7")
+        );
+    }
+}
@@ -1,7 +1,175 @@
-use anyhow::Result;
+use crate::config::{LayeringRule, RuleSelector};
+use anyhow::{Context, Result};
 use rusqlite::Connection;
-use serde::Serialize;
-use std::collections::{HashSet, VecDeque};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::ops::Deref;
+
+/// Page size when a caller doesn't ask for one.
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Which slice of a result set a query returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    /// `None` returns every result from `offset` on.
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl Page {
+    /// Every result, for callers that post-process the whole set.
+    pub const ALL: Page = Page {
+        limit: None,
+        offset: 0,
+    };
+
+    pub fn new(limit: usize, offset: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            offset,
+        }
+    }
+
+    /// Slice a result set computed in memory (graph walks, cycles, drift).
+    pub fn apply<T>(self, items: Vec<T>) -> Paged<T> {
+        let total = items.len();
+        let results = items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        Paged::new(results, total, self)
+    }
+
+    /// `LIMIT ... OFFSET ...` clause; SQLite reads a negative limit as none.
+    fn sql(self) -> String {
+        let limit = self.limit.map_or(-1, |l| l as i64);
+        format!(" LIMIT {limit} OFFSET {}", self.offset)
+    }
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT, 0)
+    }
+}
+
+/// One page of results. `total` counts every match, so callers can tell
+/// that more exist instead of getting silently truncated results.
+#[derive(Debug, Serialize)]
+pub struct Paged<T> {
+    pub total: usize,
+    pub offset: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    pub has_more: bool,
+    /// Offset of the next page, when there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<usize>,
+    pub results: Vec<T>,
+}
+
+impl<T> Paged<T> {
+    pub fn new(results: Vec<T>, total: usize, page: Page) -> Self {
+        let end = page.offset + results.len();
+        let has_more = end < total;
+        Self {
+            total,
+            offset: page.offset,
+            limit: page.limit,
+            has_more,
+            next_offset: has_more.then_some(end),
+            results,
+        }
+    }
+
+    /// An empty result set (e.g. for a blank search query).
+    pub fn empty(page: Page) -> Self {
+        Self::new(Vec::new(), 0, page)
+    }
+}
+
+impl<T> Deref for Paged<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.results
+    }
+}
+
+impl<T> IntoIterator for Paged<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Paged<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.iter()
+    }
+}
+
+/// Run `sql` (which must not have its own LIMIT) for one page of rows, and
+/// count every row it matches.
+fn paged_query<T, P: rusqlite::Params + Clone>(
+    conn: &Connection,
+    sql: &str,
+    params: P,
+    page: Page,
+    map: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> Result<Paged<T>> {
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM ({sql})"),
+        params.clone(),
+        |row| row.get(0),
+    )?;
+    let mut stmt = conn.prepare(&format!("{sql}{}", page.sql()))?;
+    let rows = stmt.query_map(params, map)?;
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(Paged::new(results, total as usize, page))
+}
+
+/// Turn free text into an FTS5 query in which every word must match as a
+/// prefix. Identifiers also match by their parts, so `parseConfig` finds
+/// `parse_config` and `auth` finds `AuthenticationService`. With `raw`, the
+/// text is FTS5 syntax and passed through as is. `None` for a blank query.
+fn fts_query(text: &str, raw: bool) -> Option<String> {
+    if raw {
+        return (!text.trim().is_empty()).then(|| text.to_string());
+    }
+    let terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let parts = crate::symbols::identifier_words(word);
+            if parts.len() > 1 {
+                let parts: Vec<String> = parts.iter().map(|p| format!("\"{p}\"*")).collect();
+                format!("(\"{word}\"* OR ({}))", parts.join(" "))
+            } else {
+                format!("\"{word}\"*")
+            }
+        })
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Errors from raw FTS5 syntax, which the caller wrote, name the query.
+fn check_raw<T>(result: Result<T>, query: &str, raw: bool) -> Result<T> {
+    if raw {
+        result.with_context(|| format!("Invalid FTS5 query: {query}"))
+    } else {
+        result
+    }
+}
 
 #[derive(Debug, Serialize)]
 pub struct PackageRow {
@@ -11,6 +179,76 @@ pub struct PackageRow {
     pub version: Option<String>,
     pub description: Option<String>,
     pub metadata: Option<String>,
+    /// Tags, owners, lifecycle and labels from `[[packages]]` overrides.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Repo the package comes from, when serving several repos' indexes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+fn package_row(row: &rusqlite::Row) -> rusqlite::Result<PackageRow> {
+    Ok(PackageRow {
+        name: row.get(0)?,
+        path: row.get(1)?,
+        kind: row.get(2)?,
+        version: row.get(3)?,
+        description: row.get(4)?,
+        metadata: row.get(5)?,
+        tags: Vec::new(),
+        owners: Vec::new(),
+        lifecycle: None,
+        labels: BTreeMap::new(),
+        repo: None,
+    })
+}
+
+/// Fill in tags, owners, lifecycle and labels from `package_tags`, and the
+/// repo from `package_repos`.
+fn attach_tags(conn: &Connection, packages: &mut [PackageRow]) -> Result<()> {
+    if packages.is_empty() {
+        return Ok(());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT kind, name, value FROM package_tags WHERE package = ?1 ORDER BY kind, name",
+    )?;
+    for pkg in packages.iter_mut() {
+        let rows = stmt.query_map([&pkg.name], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+        for row in rows {
+            let (kind, name, value) = row?;
+            match kind.as_str() {
+                "tag" => pkg.tags.push(name),
+                "owner" => pkg.owners.push(name),
+                "lifecycle" => pkg.lifecycle = Some(name),
+                "label" => {
+                    pkg.labels.insert(name, value.unwrap_or_default());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Only federated indexes, or ones built since federation existed, have repos
+    let Ok(mut stmt) = conn.prepare("SELECT repo FROM package_repos WHERE package = ?1") else {
+        return Ok(());
+    };
+    for pkg in packages.iter_mut() {
+        let mut rows = stmt.query_map([&pkg.name], |row| row.get::<_, String>(0))?;
+        pkg.repo = rows.next().transpose()?;
+    }
+    Ok(())
 }
 
 #[derive(Debug, Serialize)]
@@ -20,9 +258,22 @@ pub struct DependencyRow {
     pub dep_kind: String,
     pub version_req: Option<String>,
     pub is_internal: bool,
+    /// Exact versions pinned by the package's lockfile, lowest first. A
+    /// dependency can be locked at several, e.g. once as a dev dependency.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resolved_versions: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
+pub struct ResolvedDependencyRow {
+    pub package: String,
+    pub dependency: String,
+    pub version: String,
+    pub is_direct: bool,
+    pub lockfile: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct GraphEdge {
     pub from: String,
     pub to: String,
@@ -37,6 +288,17 @@ pub struct IndexStatus {
     pub symbol_count: Option<String>,
     pub file_count: Option<String>,
     pub total_duration_ms: Option<String>,
+    /// Source indexes, when serving several repos' indexes merged into one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub repos: Vec<FederatedRepo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FederatedRepo {
+    pub name: String,
+    pub repo_root: Option<String>,
+    pub indexed_at: Option<String>,
+    pub git_commit: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,77 +315,49 @@ pub struct SymbolRow {
     pub parameters: Option<String>,
 }
 
-/// FTS5 search across symbol names and signatures. Returns up to 50 results.
+/// FTS5 search across symbol names and signatures, best matches first.
+/// Names weigh more than their parts, which weigh more than signatures.
 pub fn search_symbols(
     conn: &Connection,
     query: &str,
     package_filter: Option<&str>,
     kind_filter: Option<&str>,
-) -> Result<Vec<SymbolRow>> {
-    if query.trim().is_empty() {
-        return Ok(Vec::new());
-    }
-    let sanitized = format!("\"{}\"", query.replace('"', "\"\""));
-
-    let (sql, params): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = match (package_filter, kind_filter) {
-        (Some(pkg), Some(kind)) => (
-            "SELECT s.name, s.kind, s.signature, s.package, s.file_path, s.line,
-                    s.visibility, s.parent_symbol, s.return_type, s.parameters
-             FROM symbols_fts f
-             JOIN symbols s ON s.rowid = f.rowid
-             WHERE symbols_fts MATCH ?1 AND s.package = ?2 AND s.kind = ?3
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>, Box::new(pkg.to_string()), Box::new(kind.to_string())],
-        ),
-        (Some(pkg), None) => (
-            "SELECT s.name, s.kind, s.signature, s.package, s.file_path, s.line,
-                    s.visibility, s.parent_symbol, s.return_type, s.parameters
-             FROM symbols_fts f
-             JOIN symbols s ON s.rowid = f.rowid
-             WHERE symbols_fts MATCH ?1 AND s.package = ?2
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>, Box::new(pkg.to_string())],
-        ),
-        (None, Some(kind)) => (
-            "SELECT s.name, s.kind, s.signature, s.package, s.file_path, s.line,
-                    s.visibility, s.parent_symbol, s.return_type, s.parameters
-             FROM symbols_fts f
-             JOIN symbols s ON s.rowid = f.rowid
-             WHERE symbols_fts MATCH ?1 AND s.kind = ?2
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>, Box::new(kind.to_string())],
-        ),
-        (None, None) => (
-            "SELECT s.name, s.kind, s.signature, s.package, s.file_path, s.line,
-                    s.visibility, s.parent_symbol, s.return_type, s.parameters
-             FROM symbols_fts f
-             JOIN symbols s ON s.rowid = f.rowid
-             WHERE symbols_fts MATCH ?1
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>],
-        ),
+    raw: bool,
+    page: Page,
+) -> Result<Paged<SymbolRow>> {
+    let Some(fts) = fts_query(query, raw) else {
+        return Ok(Paged::empty(page));
     };
+    let result = paged_query(
+        conn,
+        "SELECT s.name, s.kind, s.signature, s.package, s.file_path, s.line,
+                s.visibility, s.parent_symbol, s.return_type, s.parameters
+         FROM symbols_fts f
+         JOIN symbols s ON s.rowid = f.rowid
+         WHERE symbols_fts MATCH ?1
+           AND (?2 IS NULL OR s.package = ?2)
+           AND (?3 IS NULL OR s.kind = ?3)
+         ORDER BY bm25(symbols_fts, 10.0, 5.0, 1.0, 2.0, 1.0), s.name, s.file_path, s.line",
+        rusqlite::params![fts, package_filter, kind_filter],
+        page,
+        symbol_row,
+    );
+    check_raw(result, query, raw)
+}
 
-    let mut stmt = conn.prepare(&sql)?;
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(SymbolRow {
-            name: row.get(0)?,
-            kind: row.get(1)?,
-            signature: row.get(2)?,
-            package: row.get(3)?,
-            file_path: row.get(4)?,
-            line: row.get(5)?,
-            visibility: row.get(6)?,
-            parent_symbol: row.get(7)?,
-            return_type: row.get(8)?,
-            parameters: row.get(9)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+fn symbol_row(row: &rusqlite::Row) -> rusqlite::Result<SymbolRow> {
+    Ok(SymbolRow {
+        name: row.get(0)?,
+        kind: row.get(1)?,
+        signature: row.get(2)?,
+        package: row.get(3)?,
+        file_path: row.get(4)?,
+        line: row.get(5)?,
+        visibility: row.get(6)?,
+        parent_symbol: row.get(7)?,
+        return_type: row.get(8)?,
+        parameters: row.get(9)?,
+    })
 }
 
 /// List all symbols in a package, optionally filtered by kind.
@@ -131,45 +365,19 @@ pub fn get_package_symbols(
     conn: &Connection,
     package: &str,
     kind_filter: Option<&str>,
-) -> Result<Vec<SymbolRow>> {
-    let (sql, params): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = match kind_filter {
-        Some(kind) => (
-            "SELECT name, kind, signature, package, file_path, line,
-                    visibility, parent_symbol, return_type, parameters
-             FROM symbols
-             WHERE package = ?1 AND kind = ?2
-             ORDER BY file_path, line",
-            vec![Box::new(package.to_string()), Box::new(kind.to_string())],
-        ),
-        None => (
-            "SELECT name, kind, signature, package, file_path, line,
-                    visibility, parent_symbol, return_type, parameters
-             FROM symbols
-             WHERE package = ?1
-             ORDER BY file_path, line",
-            vec![Box::new(package.to_string())],
-        ),
-    };
-    let mut stmt = conn.prepare(sql)?;
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(SymbolRow {
-            name: row.get(0)?,
-            kind: row.get(1)?,
-            signature: row.get(2)?,
-            package: row.get(3)?,
-            file_path: row.get(4)?,
-            line: row.get(5)?,
-            visibility: row.get(6)?,
-            parent_symbol: row.get(7)?,
-            return_type: row.get(8)?,
-            parameters: row.get(9)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+    page: Page,
+) -> Result<Paged<SymbolRow>> {
+    paged_query(
+        conn,
+        "SELECT name, kind, signature, package, file_path, line,
+                visibility, parent_symbol, return_type, parameters
+         FROM symbols
+         WHERE package = ?1 AND (?2 IS NULL OR kind = ?2)
+         ORDER BY file_path, line",
+        rusqlite::params![package, kind_filter],
+        page,
+        symbol_row,
+    )
 }
 
 /// List all symbols defined in a specific file, optionally filtered by kind.
@@ -177,45 +385,19 @@ pub fn get_file_symbols(
     conn: &Connection,
     file_path: &str,
     kind_filter: Option<&str>,
-) -> Result<Vec<SymbolRow>> {
-    let (sql, params): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = match kind_filter {
-        Some(kind) => (
-            "SELECT name, kind, signature, package, file_path, line,
-                    visibility, parent_symbol, return_type, parameters
-             FROM symbols
-             WHERE file_path = ?1 AND kind = ?2
-             ORDER BY line",
-            vec![Box::new(file_path.to_string()), Box::new(kind.to_string())],
-        ),
-        None => (
-            "SELECT name, kind, signature, package, file_path, line,
-                    visibility, parent_symbol, return_type, parameters
-             FROM symbols
-             WHERE file_path = ?1
-             ORDER BY line",
-            vec![Box::new(file_path.to_string())],
-        ),
-    };
-    let mut stmt = conn.prepare(sql)?;
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(SymbolRow {
-            name: row.get(0)?,
-            kind: row.get(1)?,
-            signature: row.get(2)?,
-            package: row.get(3)?,
-            file_path: row.get(4)?,
-            line: row.get(5)?,
-            visibility: row.get(6)?,
-            parent_symbol: row.get(7)?,
-            return_type: row.get(8)?,
-            parameters: row.get(9)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+    page: Page,
+) -> Result<Paged<SymbolRow>> {
+    paged_query(
+        conn,
+        "SELECT name, kind, signature, package, file_path, line,
+                visibility, parent_symbol, return_type, parameters
+         FROM symbols
+         WHERE file_path = ?1 AND (?2 IS NULL OR kind = ?2)
+         ORDER BY line",
+        rusqlite::params![file_path, kind_filter],
+        page,
+        symbol_row,
+    )
 }
 
 /// Look up symbols by exact name, optionally scoped to a package.
@@ -223,43 +405,19 @@ pub fn get_symbol(
     conn: &Connection,
     name: &str,
     package_filter: Option<&str>,
-) -> Result<Vec<SymbolRow>> {
-    let (sql, params): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = match package_filter {
-        Some(pkg) => (
-            "SELECT name, kind, signature, package, file_path, line,
-                    visibility, parent_symbol, return_type, parameters
-             FROM symbols
-             WHERE name = ?1 AND package = ?2",
-            vec![Box::new(name.to_string()), Box::new(pkg.to_string())],
-        ),
-        None => (
-            "SELECT name, kind, signature, package, file_path, line,
-                    visibility, parent_symbol, return_type, parameters
-             FROM symbols
-             WHERE name = ?1",
-            vec![Box::new(name.to_string())],
-        ),
-    };
-    let mut stmt = conn.prepare(sql)?;
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(SymbolRow {
-            name: row.get(0)?,
-            kind: row.get(1)?,
-            signature: row.get(2)?,
-            package: row.get(3)?,
-            file_path: row.get(4)?,
-            line: row.get(5)?,
-            visibility: row.get(6)?,
-            parent_symbol: row.get(7)?,
-            return_type: row.get(8)?,
-            parameters: row.get(9)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+    page: Page,
+) -> Result<Paged<SymbolRow>> {
+    paged_query(
+        conn,
+        "SELECT name, kind, signature, package, file_path, line,
+                visibility, parent_symbol, return_type, parameters
+         FROM symbols
+         WHERE name = ?1 AND (?2 IS NULL OR package = ?2)
+         ORDER BY package, file_path, line",
+        rusqlite::params![name, package_filter],
+        page,
+        symbol_row,
+    )
 }
 
 #[derive(Debug, Serialize)]
@@ -270,67 +428,41 @@ pub struct FileRow {
     pub size_bytes: i64,
 }
 
-/// FTS5 search across file paths. Returns up to 50 results.
+/// FTS5 search across file paths, best matches first.
 pub fn search_files(
     conn: &Connection,
     query: &str,
     package_filter: Option<&str>,
     extension_filter: Option<&str>,
-) -> Result<Vec<FileRow>> {
-    if query.trim().is_empty() {
-        return Ok(Vec::new());
-    }
-    let sanitized = format!("\"{}\"", query.replace('"', "\"\""));
-
-    let (sql, params): (String, Vec<Box<dyn rusqlite::types::ToSql>>) = match (package_filter, extension_filter) {
-        (Some(pkg), Some(ext)) => (
-            "SELECT f.path, f.package, f.extension, f.size_bytes
-             FROM files_fts fts
-             JOIN files f ON f.rowid = fts.rowid
-             WHERE files_fts MATCH ?1 AND f.package = ?2 AND f.extension = ?3
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>, Box::new(pkg.to_string()), Box::new(ext.to_string())],
-        ),
-        (Some(pkg), None) => (
-            "SELECT f.path, f.package, f.extension, f.size_bytes
-             FROM files_fts fts
-             JOIN files f ON f.rowid = fts.rowid
-             WHERE files_fts MATCH ?1 AND f.package = ?2
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>, Box::new(pkg.to_string())],
-        ),
-        (None, Some(ext)) => (
-            "SELECT f.path, f.package, f.extension, f.size_bytes
-             FROM files_fts fts
-             JOIN files f ON f.rowid = fts.rowid
-             WHERE files_fts MATCH ?1 AND f.extension = ?2
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>, Box::new(ext.to_string())],
-        ),
-        (None, None) => (
-            "SELECT f.path, f.package, f.extension, f.size_bytes
-             FROM files_fts fts
-             JOIN files f ON f.rowid = fts.rowid
-             WHERE files_fts MATCH ?1
-             LIMIT 50".to_string(),
-            vec![Box::new(sanitized) as Box<dyn rusqlite::types::ToSql>],
-        ),
+    raw: bool,
+    page: Page,
+) -> Result<Paged<FileRow>> {
+    let Some(fts) = fts_query(query, raw) else {
+        return Ok(Paged::empty(page));
     };
+    let result = paged_query(
+        conn,
+        "SELECT f.path, f.package, f.extension, f.size_bytes
+         FROM files_fts fts
+         JOIN files f ON f.rowid = fts.rowid
+         WHERE files_fts MATCH ?1
+           AND (?2 IS NULL OR f.package = ?2)
+           AND (?3 IS NULL OR f.extension = ?3)
+         ORDER BY bm25(files_fts), f.path",
+        rusqlite::params![fts, package_filter, extension_filter],
+        page,
+        file_row,
+    );
+    check_raw(result, query, raw)
+}
 
-    let mut stmt = conn.prepare(&sql)?;
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(FileRow {
-            path: row.get(0)?,
-            package: row.get(1)?,
-            extension: row.get(2)?,
-            size_bytes: row.get(3)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+fn file_row(row: &rusqlite::Row) -> rusqlite::Result<FileRow> {
+    Ok(FileRow {
+        path: row.get(0)?,
+        package: row.get(1)?,
+        extension: row.get(2)?,
+        size_bytes: row.get(3)?,
+    })
 }
 
 /// List all files belonging to a package, optionally filtered by extension. Ordered by path.
@@ -338,67 +470,70 @@ pub fn list_package_files(
     conn: &Connection,
     package: &str,
     extension_filter: Option<&str>,
-) -> Result<Vec<FileRow>> {
-    let (sql, params): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = match extension_filter {
-        Some(ext) => (
-            "SELECT path, package, extension, size_bytes
-             FROM files
-             WHERE package = ?1 AND extension = ?2
-             ORDER BY path",
-            vec![Box::new(package.to_string()), Box::new(ext.to_string())],
-        ),
-        None => (
-            "SELECT path, package, extension, size_bytes
-             FROM files
-             WHERE package = ?1
-             ORDER BY path",
-            vec![Box::new(package.to_string())],
-        ),
-    };
-    let mut stmt = conn.prepare(sql)?;
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(FileRow {
-            path: row.get(0)?,
-            package: row.get(1)?,
-            extension: row.get(2)?,
-            size_bytes: row.get(3)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+    page: Page,
+) -> Result<Paged<FileRow>> {
+    paged_query(
+        conn,
+        "SELECT path, package, extension, size_bytes
+         FROM files
+         WHERE package = ?1 AND (?2 IS NULL OR extension = ?2)
+         ORDER BY path",
+        rusqlite::params![package, extension_filter],
+        page,
+        file_row,
+    )
 }
 
-/// FTS5 search across package name, description, and path. Returns up to 20 results.
-pub fn search_packages(conn: &Connection, query: &str) -> Result<Vec<PackageRow>> {
-    if query.trim().is_empty() {
-        return Ok(Vec::new());
-    }
-    // Sanitize for FTS5: wrap in double quotes, escape internal quotes
-    let sanitized = format!("\"{}\"", query.replace('"', "\"\""));
+/// Look up one indexed file by its exact repo-relative path.
+pub fn get_file(conn: &Connection, path: &str) -> Result<Option<FileRow>> {
     let mut stmt = conn.prepare(
+        "SELECT path, package, extension, size_bytes FROM files WHERE path = ?1",
+    )?;
+    let mut rows = stmt.query_map([path], file_row)?;
+    match rows.next() {
+        Some(row) => Ok(Some(row?)),
+        None => Ok(None),
+    }
+}
+
+/// All indexed files, ordered by path.
+pub fn list_files(conn: &Connection, page: Page) -> Result<Paged<FileRow>> {
+    paged_query(
+        conn,
+        "SELECT path, package, extension, size_bytes FROM files ORDER BY path",
+        [],
+        page,
+        file_row,
+    )
+}
+
+/// FTS5 search across package name, description, and path, best matches
+/// first. A match in the name weighs most.
+pub fn search_packages(
+    conn: &Connection,
+    query: &str,
+    tag: Option<&str>,
+    raw: bool,
+    page: Page,
+) -> Result<Paged<PackageRow>> {
+    let Some(fts) = fts_query(query, raw) else {
+        return Ok(Paged::empty(page));
+    };
+    let result = paged_query(
+        conn,
         "SELECT p.name, p.path, p.kind, p.version, p.description, p.metadata
          FROM packages_fts f
          JOIN packages p ON p.name = f.name
          WHERE packages_fts MATCH ?1
-         LIMIT 20",
-    )?;
-    let rows = stmt.query_map([&sanitized], |row| {
-        Ok(PackageRow {
-            name: row.get(0)?,
-            path: row.get(1)?,
-            kind: row.get(2)?,
-            version: row.get(3)?,
-            description: row.get(4)?,
-            metadata: row.get(5)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
+           AND (?2 IS NULL OR EXISTS (
+               SELECT 1 FROM package_tags t WHERE t.package = p.name AND t.kind = 'tag' AND t.name = ?2))
+         ORDER BY bm25(packages_fts, 10.0, 2.0, 1.0), p.name",
+        rusqlite::params![fts, tag],
+        page,
+        package_row,
+    );
+    let mut result = check_raw(result, query, raw)?;
+    attach_tags(conn, &mut result.results)?;
     Ok(result)
 }
 
@@ -409,18 +544,13 @@ pub fn get_package(conn: &Connection, name: &str) -> Result<Option<PackageRow>>
          FROM packages
          WHERE name = ?1",
     )?;
-    let mut rows = stmt.query_map([name], |row| {
-        Ok(PackageRow {
-            name: row.get(0)?,
-            path: row.get(1)?,
-            kind: row.get(2)?,
-            version: row.get(3)?,
-            description: row.get(4)?,
-            metadata: row.get(5)?,
-        })
-    })?;
+    let mut rows = stmt.query_map([name], package_row)?;
     match rows.next() {
-        Some(row) => Ok(Some(row?)),
+        Some(row) => {
+            let mut pkg = row?;
+            attach_tags(conn, std::slice::from_mut(&mut pkg))?;
+            Ok(Some(pkg))
+        }
         None => Ok(None),
     }
 }
@@ -431,54 +561,83 @@ pub fn package_dependencies(
     conn: &Connection,
     name: &str,
     internal_only: bool,
-) -> Result<Vec<DependencyRow>> {
-    let sql = if internal_only {
-        "SELECT package, dependency, dep_kind, version_req, is_internal
-         FROM dependencies
-         WHERE package = ?1 AND is_internal = 1"
-    } else {
-        "SELECT package, dependency, dep_kind, version_req, is_internal
-         FROM dependencies
-         WHERE package = ?1"
-    };
-    let mut stmt = conn.prepare(sql)?;
-    let rows = stmt.query_map([name], |row| {
-        Ok(DependencyRow {
-            package: row.get(0)?,
-            dependency: row.get(1)?,
-            dep_kind: row.get(2)?,
-            version_req: row.get(3)?,
-            is_internal: row.get::<_, i32>(4)? != 0,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+    page: Page,
+) -> Result<Paged<DependencyRow>> {
+    let filter = if internal_only { " AND d.is_internal = 1" } else { "" };
+    let sql = format!(
+        "SELECT d.package, d.dependency, d.dep_kind, d.version_req, d.is_internal, {RESOLVED_VERSION_SQL}
+         FROM dependencies d
+         WHERE d.package = ?1{filter}
+         ORDER BY d.dependency, d.dep_kind"
+    );
+    paged_query(conn, &sql, [name], page, dependency_row)
 }
 
 /// Reverse dependency lookup: find all packages that depend on `name`.
-pub fn package_dependents(conn: &Connection, name: &str) -> Result<Vec<DependencyRow>> {
-    let mut stmt = conn.prepare(
-        "SELECT package, dependency, dep_kind, version_req, is_internal
-         FROM dependencies
-         WHERE dependency = ?1",
-    )?;
-    let rows = stmt.query_map([name], |row| {
-        Ok(DependencyRow {
-            package: row.get(0)?,
-            dependency: row.get(1)?,
-            dep_kind: row.get(2)?,
-            version_req: row.get(3)?,
-            is_internal: row.get::<_, i32>(4)? != 0,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
+pub fn package_dependents(conn: &Connection, name: &str, page: Page) -> Result<Paged<DependencyRow>> {
+    let sql = format!(
+        "SELECT d.package, d.dependency, d.dep_kind, d.version_req, d.is_internal, {RESOLVED_VERSION_SQL}
+         FROM dependencies d
+         WHERE d.dependency = ?1
+         ORDER BY d.package, d.dep_kind"
+    );
+    paged_query(conn, &sql, [name], page, dependency_row)
+}
+
+/// Subquery for the lockfile-pinned versions of a direct dependency row `d`,
+/// as a JSON array.
+const RESOLVED_VERSION_SQL: &str = "(SELECT json_group_array(DISTINCT r.version) FROM resolved_dependencies r
+          WHERE r.package = d.package AND r.dependency = d.dependency AND r.is_direct = 1)";
+
+fn dependency_row(row: &rusqlite::Row) -> rusqlite::Result<DependencyRow> {
+    Ok(DependencyRow {
+        package: row.get(0)?,
+        dependency: row.get(1)?,
+        dep_kind: row.get(2)?,
+        version_req: row.get(3)?,
+        is_internal: row.get::<_, i32>(4)? != 0,
+        resolved_versions: {
+            let json: String = row.get(5)?;
+            let mut versions: Vec<String> = serde_json::from_str(&json).unwrap_or_default();
+            versions.sort_by(|a, b| crate::index::lockfile::compare_versions(a, b));
+            versions
+        },
+    })
+}
+
+/// Lockfile-resolved versions, filtered by the package that pulls them in
+/// and/or the dependency name. When `direct_only` is true, transitive
+/// dependencies are left out. At least one of `package` or `dependency` is required.
+pub fn resolved_dependencies(
+    conn: &Connection,
+    package: Option<&str>,
+    dependency: Option<&str>,
+    direct_only: bool,
+    page: Page,
+) -> Result<Paged<ResolvedDependencyRow>> {
+    if package.is_none() && dependency.is_none() {
+        anyhow::bail!("either a package or a dependency is required");
     }
-    Ok(result)
+    paged_query(
+        conn,
+        "SELECT package, dependency, version, is_direct, lockfile
+         FROM resolved_dependencies
+         WHERE (?1 IS NULL OR package = ?1)
+           AND (?2 IS NULL OR dependency = ?2)
+           AND (?3 = 0 OR is_direct = 1)
+         ORDER BY package, is_direct DESC, dependency, version",
+        rusqlite::params![package, dependency, direct_only],
+        page,
+        |row| {
+            Ok(ResolvedDependencyRow {
+                package: row.get(0)?,
+                dependency: row.get(1)?,
+                version: row.get(2)?,
+                is_direct: row.get::<_, i32>(3)? != 0,
+                lockfile: row.get(4)?,
+            })
+        },
+    )
 }
 
 /// BFS traversal of the dependency graph starting from `root`, up to `max_depth` levels.
@@ -488,7 +647,8 @@ pub fn dependency_graph(
     root: &str,
     max_depth: u32,
     internal_only: bool,
-) -> Result<Vec<GraphEdge>> {
+    page: Page,
+) -> Result<Paged<GraphEdge>> {
     let sql = if internal_only {
         "SELECT dependency, dep_kind FROM dependencies WHERE package = ?1 AND is_internal = 1"
     } else {
@@ -524,41 +684,711 @@ pub fn dependency_graph(
         }
     }
 
-    Ok(edges)
+    Ok(page.apply(edges))
+}
+
+/// Every dependency edge in the index, ordered by package then dependency.
+/// When `internal_only` is true, only returns edges between packages in this repo.
+pub fn all_dependency_edges(conn: &Connection, internal_only: bool, page: Page) -> Result<Paged<GraphEdge>> {
+    let sql = if internal_only {
+        "SELECT package, dependency, dep_kind FROM dependencies
+         WHERE is_internal = 1
+         ORDER BY package, dependency, dep_kind"
+    } else {
+        "SELECT package, dependency, dep_kind FROM dependencies
+         ORDER BY package, dependency, dep_kind"
+    };
+    paged_query(conn, sql, [], page, |row| {
+        Ok(GraphEdge {
+            from: row.get(0)?,
+            to: row.get(1)?,
+            dep_kind: row.get(2)?,
+        })
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyPath {
+    /// Packages along the path, from source to target.
+    pub packages: Vec<String>,
+    /// One edge per hop, with the dep_kind that links each pair.
+    pub edges: Vec<GraphEdge>,
+}
+
+/// When two packages are linked by several edges (e.g. both runtime and dev),
+/// a path is reported through the first kind in this order.
+const DEP_KIND_PRIORITY: &[&str] = &["runtime", "peer", "build", "dev"];
+
+fn dep_kind_rank(kind: &str) -> usize {
+    DEP_KIND_PRIORITY
+        .iter()
+        .position(|k| *k == kind)
+        .unwrap_or(DEP_KIND_PRIORITY.len())
+}
+
+/// Dependency paths found by `dependency_paths`, shortest first.
+#[derive(Debug, Serialize)]
+pub struct DependencyPaths {
+    pub paths: Vec<DependencyPath>,
+    /// The search gave up after `MAX_PATH_EXPANSIONS` steps, so longer (or
+    /// more) paths may exist than the ones returned.
+    pub truncated: bool,
+}
+
+impl Deref for DependencyPaths {
+    type Target = [DependencyPath];
+
+    fn deref(&self) -> &[DependencyPath] {
+        &self.paths
+    }
+}
+
+/// How many packages the path search may step into before giving up. Dense or
+/// cyclic graphs have exponentially many simple paths to try.
+const MAX_PATH_EXPANSIONS: usize = 100_000;
+
+/// Find dependency paths from `from` to `to`, shortest first. Returns up to
+/// `max_paths` simple paths of at most `max_depth` hops: `max_paths = 1` gives
+/// the shortest chain, larger values give the k shortest. When `internal_only`
+/// is true, only internal edges are followed (so `to` must be a repo package).
+pub fn dependency_paths(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+    max_paths: usize,
+    max_depth: u32,
+    internal_only: bool,
+) -> Result<DependencyPaths> {
+    let mut found = DependencyPaths {
+        paths: Vec::new(),
+        truncated: false,
+    };
+    if from == to || max_paths == 0 {
+        return Ok(found);
+    }
+
+    // Collapse parallel edges so each hop appears once, via its strongest kind
+    let edges = all_dependency_edges(conn, internal_only, Page::ALL)?;
+    let mut best: HashMap<(&str, &str), &str> = HashMap::new();
+    for edge in &edges {
+        let kind = best.entry((edge.from.as_str(), edge.to.as_str())).or_insert(edge.dep_kind.as_str());
+        if dep_kind_rank(&edge.dep_kind) < dep_kind_rank(kind) {
+            *kind = edge.dep_kind.as_str();
+        }
+    }
+    let mut adjacency: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+    let mut reverse: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (&(src, dst), &kind) in &best {
+        adjacency.entry(src).or_default().push((dst, kind));
+        reverse.entry(dst).or_default().push(src);
+    }
+    for targets in adjacency.values_mut() {
+        targets.sort();
+    }
+
+    // Hop distance from every node to `to`, used to prune the path search
+    let mut dist_to_target: HashMap<&str, u32> = HashMap::new();
+    let mut queue: VecDeque<&str> = VecDeque::new();
+    dist_to_target.insert(to, 0);
+    queue.push_back(to);
+    while let Some(current) = queue.pop_front() {
+        let d = dist_to_target[current];
+        for &prev in reverse.get(current).into_iter().flatten() {
+            if !dist_to_target.contains_key(prev) {
+                dist_to_target.insert(prev, d + 1);
+                queue.push_back(prev);
+            }
+        }
+    }
+
+    let Some(&shortest) = dist_to_target.get(from) else {
+        return Ok(found);
+    };
+
+    // Iterative deepening: enumerate paths of exactly `len` hops, shortest lengths first
+    let mut search = PathSearch {
+        adjacency: &adjacency,
+        dist_to_target: &dist_to_target,
+        to,
+        limit: max_paths,
+        expansions: 0,
+        out: &mut found.paths,
+    };
+    for len in shortest..=max_depth {
+        let mut nodes = vec![from];
+        let mut kinds = Vec::new();
+        search.collect(len, &mut nodes, &mut kinds);
+        if search.out.len() >= max_paths || search.exhausted() {
+            break;
+        }
+    }
+    found.truncated = search.exhausted() && found.paths.len() < max_paths;
+    Ok(found)
+}
+
+/// State of one `dependency_paths` search.
+struct PathSearch<'a, 'g> {
+    adjacency: &'g HashMap<&'a str, Vec<(&'a str, &'a str)>>,
+    dist_to_target: &'g HashMap<&'a str, u32>,
+    to: &'g str,
+    limit: usize,
+    /// Packages stepped into so far, capped at `MAX_PATH_EXPANSIONS`.
+    expansions: usize,
+    out: &'g mut Vec<DependencyPath>,
+}
+
+impl<'a> PathSearch<'a, '_> {
+    fn exhausted(&self) -> bool {
+        self.expansions >= MAX_PATH_EXPANSIONS
+    }
+
+    /// Depth-first enumeration of simple paths that reach `to` in exactly `remaining` more hops.
+    fn collect(&mut self, remaining: u32, nodes: &mut Vec<&'a str>, kinds: &mut Vec<&'a str>) {
+        if self.out.len() >= self.limit {
+            return;
+        }
+        let current = nodes[nodes.len() - 1];
+        if remaining == 0 {
+            if current == self.to {
+                self.out.push(DependencyPath {
+                    packages: nodes.iter().map(|n| n.to_string()).collect(),
+                    edges: nodes
+                        .windows(2)
+                        .zip(kinds.iter())
+                        .map(|(pair, kind)| GraphEdge {
+                            from: pair[0].to_string(),
+                            to: pair[1].to_string(),
+                            dep_kind: kind.to_string(),
+                        })
+                        .collect(),
+                });
+            }
+            return;
+        }
+        for &(next, kind) in self.adjacency.get(current).into_iter().flatten() {
+            // Skip nodes that can't reach the target in the hops left, and revisits
+            let reachable = self.dist_to_target.get(next).is_some_and(|d| *d < remaining);
+            if !reachable || nodes.contains(&next) {
+                continue;
+            }
+            if self.exhausted() {
+                return;
+            }
+            self.expansions += 1;
+            nodes.push(next);
+            kinds.push(kind);
+            self.collect(remaining - 1, nodes, kinds);
+            nodes.pop();
+            kinds.pop();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyCycle {
+    /// Packages in the cycle (one strongly connected component), sorted by name.
+    pub packages: Vec<String>,
+    /// Every internal edge between members of the cycle.
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Find dependency cycles in the internal graph using Tarjan's strongly connected
+/// components algorithm. Each component with more than one package, or a package
+/// that depends on itself, is reported as a cycle. When `exclude_dev` is true,
+/// dev dependency edges are ignored (they rarely create real build-order cycles).
+/// Largest cycles come first.
+pub fn find_cycles(conn: &Connection, exclude_dev: bool, page: Page) -> Result<Paged<DependencyCycle>> {
+    let mut edges = all_dependency_edges(conn, true, Page::ALL)?.results;
+    if exclude_dev {
+        edges.retain(|e| e.dep_kind != "dev");
+    }
+    Ok(page.apply(cycles_in_edges(&edges)))
+}
+
+/// Group `edges` into strongly connected components and return the cyclic ones.
+fn cycles_in_edges(edges: &[GraphEdge]) -> Vec<DependencyCycle> {
+    let mut names: Vec<&str> = edges
+        .iter()
+        .flat_map(|e| [e.from.as_str(), e.to.as_str()])
+        .collect();
+    names.sort();
+    names.dedup();
+    let index_of: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+    for edge in edges {
+        adjacency[index_of[edge.from.as_str()]].push(index_of[edge.to.as_str()]);
+    }
+
+    let mut cycles: Vec<DependencyCycle> = strongly_connected_components(&adjacency)
+        .into_iter()
+        .filter(|scc| scc.len() > 1 || adjacency[scc[0]].contains(&scc[0]))
+        .map(|scc| {
+            let members: HashSet<&str> = scc.iter().map(|&i| names[i]).collect();
+            let mut packages: Vec<String> = members.iter().map(|n| n.to_string()).collect();
+            packages.sort();
+            let cycle_edges = edges
+                .iter()
+                .filter(|e| members.contains(e.from.as_str()) && members.contains(e.to.as_str()))
+                .cloned()
+                .collect();
+            DependencyCycle {
+                packages,
+                edges: cycle_edges,
+            }
+        })
+        .collect();
+
+    cycles.sort_by(|a, b| {
+        b.packages
+            .len()
+            .cmp(&a.packages.len())
+            .then_with(|| a.packages.cmp(&b.packages))
+    });
+    cycles
+}
+
+#[derive(Debug, Serialize)]
+pub struct BuildOrder {
+    /// Packages in a valid sequential build order, dependencies first.
+    pub order: Vec<String>,
+    /// Groups of packages that can build in parallel; each wave only depends on earlier waves.
+    pub waves: Vec<Vec<String>>,
+}
+
+/// Topologically sort internal packages so every package comes after its internal
+/// dependencies. With `roots`, only the roots and their transitive internal dependencies
+/// are included; otherwise every indexed package is. Fails naming the offending cycle
+/// when the graph isn't a DAG.
+pub fn build_order(conn: &Connection, roots: &[String], exclude_dev: bool) -> Result<BuildOrder> {
+    let mut edges = all_dependency_edges(conn, true, Page::ALL)?.results;
+    if exclude_dev {
+        edges.retain(|e| e.dep_kind != "dev");
+    }
+
+    let mut deps_of: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &edges {
+        deps_of.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+    }
+
+    let all_packages: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT name FROM packages ORDER BY name")?;
+        stmt.query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?
+    };
+
+    let nodes: BTreeSet<&str> = if roots.is_empty() {
+        all_packages.iter().map(String::as_str).collect()
+    } else {
+        let known: HashSet<&str> = all_packages.iter().map(String::as_str).collect();
+        let mut closure: BTreeSet<&str> = BTreeSet::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        for root in roots {
+            let Some(&root) = known.get(root.as_str()) else {
+                anyhow::bail!("Package '{root}' not found");
+            };
+            if closure.insert(root) {
+                queue.push_back(root);
+            }
+        }
+        while let Some(current) = queue.pop_front() {
+            for &dep in deps_of.get(current).into_iter().flatten() {
+                if closure.insert(dep) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+        closure
+    };
+
+    let sub_edges: Vec<GraphEdge> = edges
+        .iter()
+        .filter(|e| nodes.contains(e.from.as_str()) && nodes.contains(e.to.as_str()))
+        .cloned()
+        .collect();
+    if let Some(cycle) = cycles_in_edges(&sub_edges).into_iter().next() {
+        let links: Vec<String> = cycle
+            .edges
+            .iter()
+            .map(|e| format!("{} -> {} ({})", e.from, e.to, e.dep_kind))
+            .collect();
+        anyhow::bail!(
+            "Dependency graph is not a DAG: cycle among {} [{}]",
+            cycle.packages.join(", "),
+            links.join(", ")
+        );
+    }
+
+    // Kahn's algorithm, one wave at a time: a package is ready once all of its
+    // dependencies were built in earlier waves.
+    let mut remaining: HashMap<&str, usize> = nodes.iter().map(|&n| (n, 0)).collect();
+    let mut dependents_of: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut seen_edges: HashSet<(&str, &str)> = HashSet::new();
+    for edge in &sub_edges {
+        if seen_edges.insert((edge.from.as_str(), edge.to.as_str())) {
+            *remaining.get_mut(edge.from.as_str()).unwrap() += 1;
+            dependents_of.entry(edge.to.as_str()).or_default().push(edge.from.as_str());
+        }
+    }
+
+    let mut wave: Vec<&str> = nodes.iter().copied().filter(|n| remaining[n] == 0).collect();
+    let mut waves: Vec<Vec<String>> = Vec::new();
+    while !wave.is_empty() {
+        let mut next: Vec<&str> = Vec::new();
+        for &built in &wave {
+            for &dependent in dependents_of.get(built).into_iter().flatten() {
+                let count = remaining.get_mut(dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    next.push(dependent);
+                }
+            }
+        }
+        next.sort();
+        waves.push(wave.iter().map(|n| n.to_string()).collect());
+        wave = next;
+    }
+
+    Ok(BuildOrder {
+        order: waves.iter().flatten().cloned().collect(),
+        waves,
+    })
+}
+
+/// Iterative Tarjan's SCC over an adjacency list (no recursion, so deep
+/// dependency chains can't overflow the stack).
+fn strongly_connected_components(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = adjacency.len();
+    let mut next_index = 0;
+    let mut index: Vec<Option<usize>> = vec![None; n];
+    let mut lowlink = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut components = Vec::new();
+
+    for start in 0..n {
+        if index[start].is_some() {
+            continue;
+        }
+        // Call stack of (node, position of the next child to visit)
+        let mut call: Vec<(usize, usize)> = vec![(start, 0)];
+        index[start] = Some(next_index);
+        lowlink[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some((v, child)) = call.last_mut() {
+            let v = *v;
+            if *child < adjacency[v].len() {
+                let w = adjacency[v][*child];
+                *child += 1;
+                match index[w] {
+                    None => {
+                        index[w] = Some(next_index);
+                        lowlink[w] = next_index;
+                        next_index += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        call.push((w, 0));
+                    }
+                    Some(w_index) if on_stack[w] => {
+                        lowlink[v] = lowlink[v].min(w_index);
+                    }
+                    Some(_) => {}
+                }
+            } else {
+                call.pop();
+                if let Some((parent, _)) = call.last() {
+                    lowlink[*parent] = lowlink[*parent].min(lowlink[v]);
+                }
+                if Some(lowlink[v]) == index[v] {
+                    let mut component = Vec::new();
+                    while let Some(w) = stack.pop() {
+                        on_stack[w] = false;
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+    }
+
+    components
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionDrift {
+    pub dependency: String,
+    /// Ecosystem kind of the packages declaring the dependency (e.g. "npm").
+    pub kind: String,
+    /// Number of distinct version requirements.
+    pub version_count: usize,
+    /// Number of packages declaring the dependency with a version requirement.
+    pub package_count: usize,
+    /// Each distinct requirement with the packages using it, most common first.
+    pub versions: Vec<DriftVersion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DriftVersion {
+    pub version_req: String,
+    pub packages: Vec<String>,
+}
+
+/// Group external dependencies by name and ecosystem kind and report the distinct
+/// version requirements each is declared with. Only libraries with at least
+/// `min_versions` distinct requirements are returned; dependencies without a
+/// version requirement are ignored. Most fragmented libraries come first.
+pub fn version_drift(
+    conn: &Connection,
+    kind: Option<&str>,
+    min_versions: usize,
+    page: Page,
+) -> Result<Paged<VersionDrift>> {
+    let mut stmt = conn.prepare(
+        "SELECT d.dependency, p.kind, d.version_req, d.package
+         FROM dependencies d
+         JOIN packages p ON p.name = d.package
+         WHERE d.is_internal = 0 AND d.version_req IS NOT NULL
+           AND (?1 IS NULL OR p.kind = ?1)",
+    )?;
+    let rows = stmt.query_map([kind], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+        ))
+    })?;
+
+    let mut groups: BTreeMap<(String, String), BTreeMap<String, BTreeSet<String>>> = BTreeMap::new();
+    for row in rows {
+        let (dependency, kind, version_req, package) = row?;
+        groups
+            .entry((dependency, kind))
+            .or_default()
+            .entry(version_req)
+            .or_default()
+            .insert(package);
+    }
+
+    let mut drift: Vec<VersionDrift> = groups
+        .into_iter()
+        .filter(|(_, versions)| versions.len() >= min_versions.max(1))
+        .map(|((dependency, kind), versions)| {
+            let package_count = versions
+                .values()
+                .flatten()
+                .collect::<BTreeSet<_>>()
+                .len();
+            let mut versions: Vec<DriftVersion> = versions
+                .into_iter()
+                .map(|(version_req, packages)| DriftVersion {
+                    version_req,
+                    packages: packages.into_iter().collect(),
+                })
+                .collect();
+            versions.sort_by_key(|v| std::cmp::Reverse(v.packages.len()));
+            VersionDrift {
+                dependency,
+                kind,
+                version_count: versions.len(),
+                package_count,
+                versions,
+            }
+        })
+        .collect();
+
+    drift.sort_by(|a, b| {
+        b.version_count
+            .cmp(&a.version_count)
+            .then_with(|| b.package_count.cmp(&a.package_count))
+            .then_with(|| a.dependency.cmp(&b.dependency))
+            .then_with(|| a.kind.cmp(&b.kind))
+    });
+    Ok(page.apply(drift))
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleViolation {
+    pub rule: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub from: String,
+    pub to: String,
+    pub dep_kind: String,
+    pub reason: ViolationReason,
+    /// True for edges passed in as a proposed change rather than read from the index.
+    pub proposed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationReason {
+    /// The dependency matches one of the rule's `deny` selectors.
+    Denied,
+    /// The rule has `allow` selectors and the dependency matches none of them.
+    NotAllowed,
+}
+
+/// A [`RuleSelector`] with its globs compiled.
+struct CompiledSelector<'a> {
+    path: Option<glob::Pattern>,
+    name: Option<glob::Pattern>,
+    kind: Option<&'a str>,
+}
+
+impl<'a> CompiledSelector<'a> {
+    fn new(selector: &'a RuleSelector, rule: &str) -> Result<Self> {
+        let compile = |glob: &Option<String>| -> Result<Option<glob::Pattern>> {
+            glob.as_deref()
+                .map(|g| {
+                    glob::Pattern::new(g)
+                        .map_err(|e| anyhow::anyhow!("Rule '{rule}': invalid glob '{g}': {e}"))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            path: compile(&selector.path)?,
+            name: compile(&selector.name)?,
+            kind: selector.kind.as_deref(),
+        })
+    }
+
+    fn matches(&self, name: &str, path: &str, kind: &str) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        // `libs/core/**` also selects a package rooted at `libs/core` itself
+        let path_matches = |p: &glob::Pattern| {
+            p.matches_with(path, options) || p.as_str().strip_suffix("/**") == Some(path)
+        };
+        self.path.as_ref().is_none_or(path_matches)
+            && self.name.as_ref().is_none_or(|p| p.matches(name))
+            && self.kind.is_none_or(|k| k == kind)
+    }
+}
+
+/// Evaluate layering rules against every internal dependency edge in the index,
+/// plus `proposed` edges for a change that hasn't been made yet. Proposed edges
+/// must be between known packages. Violations come in rule order, then by edge.
+pub fn check_rules(
+    conn: &Connection,
+    rules: &[LayeringRule],
+    proposed: &[GraphEdge],
+    page: Page,
+) -> Result<Paged<RuleViolation>> {
+    let mut packages: HashMap<String, (String, String)> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT name, path, kind FROM packages")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+    for row in rows {
+        let (name, location) = row?;
+        packages.insert(name, location);
+    }
+
+    let mut edges: Vec<(GraphEdge, bool)> = all_dependency_edges(conn, true, Page::ALL)?
+        .into_iter()
+        .map(|e| (e, false))
+        .collect();
+    for edge in proposed {
+        for name in [&edge.from, &edge.to] {
+            if !packages.contains_key(name) {
+                anyhow::bail!("Package '{name}' not found");
+            }
+        }
+        let exists = edges
+            .iter()
+            .any(|(e, _)| e.from == edge.from && e.to == edge.to && e.dep_kind == edge.dep_kind);
+        if !exists {
+            edges.push((edge.clone(), true));
+        }
+    }
+
+    let mut violations = Vec::new();
+    for rule in rules {
+        if rule.allow.is_empty() && rule.deny.is_empty() {
+            anyhow::bail!("Rule '{}' has neither allow nor deny selectors", rule.name);
+        }
+        let from = CompiledSelector::new(&rule.from, &rule.name)?;
+        let allow = rule
+            .allow
+            .iter()
+            .map(|s| CompiledSelector::new(s, &rule.name))
+            .collect::<Result<Vec<_>>>()?;
+        let deny = rule
+            .deny
+            .iter()
+            .map(|s| CompiledSelector::new(s, &rule.name))
+            .collect::<Result<Vec<_>>>()?;
+
+        for (edge, is_proposed) in &edges {
+            if rule.exclude_dev && edge.dep_kind == "dev" {
+                continue;
+            }
+            let (Some((from_path, from_kind)), Some((to_path, to_kind))) =
+                (packages.get(&edge.from), packages.get(&edge.to))
+            else {
+                continue;
+            };
+            if !from.matches(&edge.from, from_path, from_kind) {
+                continue;
+            }
+            let reason = if deny.iter().any(|s| s.matches(&edge.to, to_path, to_kind)) {
+                ViolationReason::Denied
+            } else if !allow.is_empty() && !allow.iter().any(|s| s.matches(&edge.to, to_path, to_kind)) {
+                ViolationReason::NotAllowed
+            } else {
+                continue;
+            };
+            violations.push(RuleViolation {
+                rule: rule.name.clone(),
+                description: rule.description.clone(),
+                from: edge.from.clone(),
+                to: edge.to.clone(),
+                dep_kind: edge.dep_kind.clone(),
+                reason,
+                proposed: *is_proposed,
+            });
+        }
+    }
+    Ok(page.apply(violations))
 }
 
-/// List all packages, optionally filtered by kind (e.g. "npm", "go").
-pub fn list_packages(conn: &Connection, kind: Option<&str>) -> Result<Vec<PackageRow>> {
-    let (sql, params): (&str, Vec<Box<dyn rusqlite::types::ToSql>>) = match kind {
-        Some(k) => (
-            "SELECT name, path, kind, version, description, metadata
-             FROM packages
-             WHERE kind = ?1
-             ORDER BY name",
-            vec![Box::new(k.to_string())],
-        ),
-        None => (
-            "SELECT name, path, kind, version, description, metadata
-             FROM packages
-             ORDER BY name",
-            vec![],
-        ),
-    };
-    let mut stmt = conn.prepare(sql)?;
-    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
-        Ok(PackageRow {
-            name: row.get(0)?,
-            path: row.get(1)?,
-            kind: row.get(2)?,
-            version: row.get(3)?,
-            description: row.get(4)?,
-            metadata: row.get(5)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
+/// Layering rules from `shire.toml` as of the last build.
+pub fn stored_rules(conn: &Connection) -> Result<Vec<LayeringRule>> {
+    let mut stmt = conn.prepare("SELECT value FROM shire_meta WHERE key = 'rules'")?;
+    let mut rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    match rows.next() {
+        Some(json) => Ok(serde_json::from_str(&json?)?),
+        None => Ok(Vec::new()),
     }
+}
+
+/// List all packages, optionally filtered by kind (e.g. "npm", "go") and/or tag.
+pub fn list_packages(
+    conn: &Connection,
+    kind: Option<&str>,
+    tag: Option<&str>,
+    page: Page,
+) -> Result<Paged<PackageRow>> {
+    let mut result = paged_query(
+        conn,
+        "SELECT p.name, p.path, p.kind, p.version, p.description, p.metadata
+         FROM packages p
+         WHERE (?1 IS NULL OR p.kind = ?1)
+           AND (?2 IS NULL OR EXISTS (
+               SELECT 1 FROM package_tags t WHERE t.package = p.name AND t.kind = 'tag' AND t.name = ?2))
+         ORDER BY p.name",
+        rusqlite::params![kind, tag],
+        page,
+        package_row,
+    )?;
+    attach_tags(conn, &mut result.results)?;
     Ok(result)
 }
 
@@ -580,16 +1410,41 @@ pub fn index_status(conn: &Connection) -> Result<IndexStatus> {
         symbol_count: get_meta("symbol_count")?,
         file_count: get_meta("file_count")?,
         total_duration_ms: get_meta("total_duration_ms")?,
+        repos: federated_repos(conn)?,
     })
 }
 
+/// Source indexes of a federated index; empty for a single repo's index, or
+/// one built before federation existed.
+pub fn federated_repos(conn: &Connection) -> Result<Vec<FederatedRepo>> {
+    let Ok(mut stmt) = conn.prepare(
+        "SELECT name, repo_root, indexed_at, git_commit FROM federated_repos ORDER BY name",
+    ) else {
+        return Ok(Vec::new());
+    };
+    let rows = stmt.query_map([], |row| {
+        Ok(FederatedRepo {
+            name: row.get(0)?,
+            repo_root: row.get(1)?,
+            indexed_at: row.get(2)?,
+            git_commit: row.get(3)?,
+        })
+    })?;
+    let mut result = Vec::new();
+    for row in rows {
+        result.push(row?);
+    }
+    Ok(result)
+}
+
 /// BFS traversal of the reverse dependency graph starting from `root`, up to `max_depth` levels.
 /// Finds all packages that transitively depend on `root`.
 pub fn reverse_dependency_graph(
     conn: &Connection,
     root: &str,
     max_depth: u32,
-) -> Result<Vec<GraphEdge>> {
+    page: Page,
+) -> Result<Paged<GraphEdge>> {
     let sql = "SELECT package, dep_kind FROM dependencies WHERE dependency = ?1 AND is_internal = 1";
 
     let mut edges = Vec::new();
@@ -621,37 +1476,253 @@ pub fn reverse_dependency_graph(
         }
     }
 
-    Ok(edges)
+    Ok(page.apply(edges))
+}
+
+#[derive(Debug, Serialize)]
+pub struct AffectedPackages {
+    /// Packages that own at least one of the changed files
+    pub changed: Vec<String>,
+    /// Changed packages plus every internal package that transitively depends on them
+    pub affected: Vec<String>,
+    /// Changed files not inside any indexed package
+    pub unowned_files: Vec<String>,
+}
+
+/// Map changed file paths (relative to the repo root) to their owning packages using
+/// longest-prefix matching, then expand through the reverse dependency graph.
+pub fn affected_packages(conn: &Connection, changed_files: &[String]) -> Result<AffectedPackages> {
+    let mut stmt = conn.prepare("SELECT name, path FROM packages")?;
+    let packages: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let sorted_pkgs = crate::index::sort_packages_by_path_len(&packages);
+
+    let mut changed: BTreeSet<String> = BTreeSet::new();
+    let mut unowned_files = Vec::new();
+    for file in changed_files {
+        let file = file.trim().trim_start_matches("./").replace('\\', "/");
+        if file.is_empty() {
+            continue;
+        }
+        match crate::index::owning_package(&file, &sorted_pkgs) {
+            Some(name) => {
+                changed.insert(name.to_string());
+            }
+            None => unowned_files.push(file),
+        }
+    }
+
+    let mut affected = changed.clone();
+    for name in &changed {
+        for edge in reverse_dependency_graph(conn, name, u32::MAX, Page::ALL)? {
+            affected.insert(edge.from);
+        }
+    }
+
+    Ok(AffectedPackages {
+        changed: changed.into_iter().collect(),
+        affected: affected.into_iter().collect(),
+        unowned_files,
+    })
 }
 
 /// Find all packages whose path starts with the given prefix.
-pub fn packages_by_path_prefix(conn: &Connection, prefix: &str) -> Result<Vec<PackageRow>> {
+pub fn packages_by_path_prefix(conn: &Connection, prefix: &str, page: Page) -> Result<Paged<PackageRow>> {
     let escaped = prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
     let pattern = format!("{escaped}%");
-    let mut stmt = conn.prepare(
+    let mut result = paged_query(
+        conn,
         "SELECT name, path, kind, version, description, metadata
          FROM packages
          WHERE path LIKE ?1 ESCAPE '\\'
-         ORDER BY path
-         LIMIT 50",
+         ORDER BY path",
+        [&pattern],
+        page,
+        package_row,
     )?;
-    let rows = stmt.query_map([&pattern], |row| {
-        Ok(PackageRow {
-            name: row.get(0)?,
-            path: row.get(1)?,
-            kind: row.get(2)?,
-            version: row.get(3)?,
-            description: row.get(4)?,
-            metadata: row.get(5)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
+    attach_tags(conn, &mut result.results)?;
+    Ok(result)
+}
+
+#[derive(Debug, Serialize)]
+pub struct Ownership {
+    /// The file, directory or package name that was looked up.
+    pub path: String,
+    /// CODEOWNERS owners of the file, or of every file under a directory.
+    pub owners: Vec<String>,
+    /// The package containing the path, if any.
+    pub package: Option<String>,
+    /// Owners of that package, from CODEOWNERS and `[[packages]]` overrides.
+    pub package_owners: Vec<String>,
+}
+
+/// Look up who owns a file, a directory or a package. A directory reports the
+/// union of its files' owners. Returns `None` when nothing in the index matches.
+pub fn who_owns(conn: &Connection, path: &str) -> Result<Option<Ownership>> {
+    let path = path.trim_start_matches("./").trim_end_matches('/');
+
+    let file_package: Option<Option<String>> = conn
+        .query_row("SELECT package FROM files WHERE path = ?1", [path], |row| row.get(0))
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })?;
+
+    let (owners, package) = if let Some(package) = file_package {
+        let mut stmt = conn.prepare("SELECT owner FROM file_owners WHERE path = ?1 ORDER BY owner")?;
+        let owners = stmt.query_map([path], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?;
+        (owners, package)
+    } else if let Some(pkg) = get_package(conn, path)? {
+        let owners = pkg.owners.clone();
+        (owners, Some(pkg.name))
+    } else {
+        let escaped = path.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let pattern = if path.is_empty() { "%".to_string() } else { format!("{escaped}/%") };
+        let has_files: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM files WHERE path LIKE ?1 ESCAPE '\\')",
+            [&pattern],
+            |row| row.get(0),
+        )?;
+        if !has_files {
+            return Ok(None);
+        }
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT owner FROM file_owners WHERE path LIKE ?1 ESCAPE '\\' ORDER BY owner",
+        )?;
+        let owners = stmt.query_map([&pattern], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?;
+        // The package rooted closest above the directory
+        let package: Option<String> = conn
+            .query_row(
+                "SELECT name FROM packages
+                 WHERE ?1 = path OR substr(?1, 1, length(path) + 1) = path || '/' OR path IN ('', '.')
+                 ORDER BY length(path) DESC
+                 LIMIT 1",
+                [path],
+                |row| row.get(0),
+            )
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e),
+            })?;
+        (owners, package)
+    };
+
+    let package_owners = match &package {
+        Some(name) => get_package(conn, name)?.map(|p| p.owners).unwrap_or_default(),
+        None => Vec::new(),
+    };
+    Ok(Some(Ownership {
+        path: path.to_string(),
+        owners,
+        package,
+        package_owners,
+    }))
+}
+
+/// List packages owned by `owner` (case-insensitive), whether the owner came
+/// from CODEOWNERS or a `[[packages]]` override.
+pub fn packages_by_owner(conn: &Connection, owner: &str, page: Page) -> Result<Paged<PackageRow>> {
+    let mut result = paged_query(
+        conn,
+        "SELECT name, path, kind, version, description, metadata
+         FROM packages
+         WHERE name IN (
+             SELECT package FROM package_tags WHERE kind = 'owner' AND name = ?1 COLLATE NOCASE
+         )
+         ORDER BY name",
+        [owner],
+        page,
+        package_row,
+    )?;
+    attach_tags(conn, &mut result.results)?;
     Ok(result)
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuthorCount {
+    pub name: String,
+    pub commits: usize,
+}
+
+/// Git activity for a package or file, from the `[history]` build phase.
+#[derive(Debug, Serialize)]
+pub struct ChurnRow {
+    /// Package directory, or file path for file-level results.
+    pub path: String,
+    pub package: Option<String>,
+    /// Commits within the configured window.
+    pub commit_count: i64,
+    /// Most recent commit touching it, at any time.
+    pub last_commit_at: Option<String>,
+    /// Authors with the most commits within the window.
+    pub top_authors: Vec<AuthorCount>,
+}
+
+fn churn_row(row: &rusqlite::Row) -> rusqlite::Result<ChurnRow> {
+    let authors: Option<String> = row.get(4)?;
+    Ok(ChurnRow {
+        path: row.get(0)?,
+        package: row.get(1)?,
+        commit_count: row.get(2)?,
+        last_commit_at: row.get(3)?,
+        top_authors: authors
+            .and_then(|a| serde_json::from_str(&a).ok())
+            .unwrap_or_default(),
+    })
+}
+
+/// The history window in days, or an error if the index has no git history.
+pub fn history_window_days(conn: &Connection) -> Result<u32> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM shire_meta WHERE key = 'history_window_days'", [], |row| row.get(0))
+        .ok();
+    match value.and_then(|v| v.parse().ok()) {
+        Some(days) => Ok(days),
+        None => anyhow::bail!("No git history in the index. Set `[history] enabled = true` in shire.toml and rebuild."),
+    }
+}
+
+/// The most churned packages (or files, optionally within one package) by
+/// commit count over the history window.
+pub fn churn_hotspots(conn: &Connection, files: bool, package: Option<&str>, page: Page) -> Result<Paged<ChurnRow>> {
+    history_window_days(conn)?;
+    let sql = if files {
+        "SELECT h.path, f.package, h.commit_count, h.last_commit_at, h.top_authors
+         FROM file_history h
+         JOIN files f ON f.path = h.path
+         WHERE h.commit_count > 0 AND (?1 IS NULL OR f.package = ?1)
+         ORDER BY h.commit_count DESC, h.last_commit_at DESC, h.path"
+    } else {
+        "SELECT p.path, p.name, h.commit_count, h.last_commit_at, h.top_authors
+         FROM package_history h
+         JOIN packages p ON p.name = h.package
+         WHERE h.commit_count > 0 AND (?1 IS NULL OR p.name = ?1)
+         ORDER BY h.commit_count DESC, h.last_commit_at DESC, p.name"
+    };
+    paged_query(conn, sql, [package], page, churn_row)
+}
+
+/// Packages whose last commit is more than `days` days old, least recently
+/// touched first. Packages with no commits yet are left out.
+pub fn stale_packages(conn: &Connection, days: u32, page: Page) -> Result<Paged<ChurnRow>> {
+    history_window_days(conn)?;
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(i64::from(days))).to_rfc3339();
+    paged_query(
+        conn,
+        "SELECT p.path, p.name, h.commit_count, h.last_commit_at, h.top_authors
+         FROM package_history h
+         JOIN packages p ON p.name = h.package
+         WHERE h.last_commit_at < ?1
+         ORDER BY h.last_commit_at, p.name",
+        [cutoff],
+        page,
+        churn_row,
+    )
+}
+
 #[derive(Debug, Serialize)]
 pub struct ExtensionCount {
     pub extension: String,
@@ -659,25 +1730,23 @@ pub struct ExtensionCount {
 }
 
 /// Count files grouped by extension, ordered by count descending.
-pub fn extension_distribution(conn: &Connection) -> Result<Vec<ExtensionCount>> {
-    let mut stmt = conn.prepare(
+pub fn extension_distribution(conn: &Connection, page: Page) -> Result<Paged<ExtensionCount>> {
+    paged_query(
+        conn,
         "SELECT extension, COUNT(*) as cnt
          FROM files
          WHERE extension != ''
          GROUP BY extension
-         ORDER BY cnt DESC",
-    )?;
-    let rows = stmt.query_map([], |row| {
-        Ok(ExtensionCount {
-            extension: row.get(0)?,
-            count: row.get(1)?,
-        })
-    })?;
-    let mut result = Vec::new();
-    for row in rows {
-        result.push(row?);
-    }
-    Ok(result)
+         ORDER BY cnt DESC, extension",
+        [],
+        page,
+        |row| {
+            Ok(ExtensionCount {
+                extension: row.get(0)?,
+                count: row.get(1)?,
+            })
+        },
+    )
 }
 
 #[cfg(test)]
@@ -744,7 +1813,7 @@ mod tests {
     #[test]
     fn test_search_packages_finds_by_name() {
         let conn = test_db();
-        let results = search_packages(&conn, "auth").unwrap();
+        let results = search_packages(&conn, "auth", None, false, Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "auth-service");
     }
@@ -752,7 +1821,7 @@ mod tests {
     #[test]
     fn test_search_packages_finds_by_description() {
         let conn = test_db();
-        let results = search_packages(&conn, "TypeScript").unwrap();
+        let results = search_packages(&conn, "TypeScript", None, false, Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "shared-types");
     }
@@ -760,7 +1829,7 @@ mod tests {
     #[test]
     fn test_search_packages_no_match() {
         let conn = test_db();
-        let results = search_packages(&conn, "nonexistent").unwrap();
+        let results = search_packages(&conn, "nonexistent", None, false, Page::ALL).unwrap();
         assert!(results.is_empty());
     }
 
@@ -786,7 +1855,7 @@ mod tests {
     #[test]
     fn test_package_dependencies_all() {
         let conn = test_db();
-        let deps = package_dependencies(&conn, "auth-service", false).unwrap();
+        let deps = package_dependencies(&conn, "auth-service", false, Page::ALL).unwrap();
         assert_eq!(deps.len(), 2);
         let dep_names: Vec<&str> = deps.iter().map(|d| d.dependency.as_str()).collect();
         assert!(dep_names.contains(&"shared-types"));
@@ -796,7 +1865,7 @@ mod tests {
     #[test]
     fn test_package_dependencies_internal_only() {
         let conn = test_db();
-        let deps = package_dependencies(&conn, "auth-service", true).unwrap();
+        let deps = package_dependencies(&conn, "auth-service", true, Page::ALL).unwrap();
         assert_eq!(deps.len(), 1);
         assert_eq!(deps[0].dependency, "shared-types");
         assert!(deps[0].is_internal);
@@ -805,16 +1874,74 @@ mod tests {
     #[test]
     fn test_package_dependents() {
         let conn = test_db();
-        let dependents = package_dependents(&conn, "auth-service").unwrap();
+        let dependents = package_dependents(&conn, "auth-service", Page::ALL).unwrap();
         assert_eq!(dependents.len(), 1);
         assert_eq!(dependents[0].package, "api-gateway");
     }
 
+    #[test]
+    fn test_resolved_dependencies() {
+        let conn = test_db();
+        for (dep, version, is_direct) in [("express", "4.18.2", 1), ("body-parser", "1.20.1", 0)] {
+            conn.execute(
+                "INSERT INTO resolved_dependencies (package, dependency, version, is_direct, lockfile)
+                 VALUES ('auth-service', ?1, ?2, ?3, 'package-lock.json')",
+                (dep, version, is_direct),
+            )
+            .unwrap();
+        }
+
+        let all = resolved_dependencies(&conn, Some("auth-service"), None, false, Page::ALL).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].dependency, "express");
+        assert!(all[0].is_direct);
+
+        let direct = resolved_dependencies(&conn, Some("auth-service"), None, true, Page::ALL).unwrap();
+        assert_eq!(direct.len(), 1);
+
+        let by_dep = resolved_dependencies(&conn, None, Some("body-parser"), false, Page::ALL).unwrap();
+        assert_eq!(by_dep.len(), 1);
+        assert_eq!(by_dep[0].version, "1.20.1");
+
+        assert!(resolved_dependencies(&conn, None, None, false, Page::ALL).is_err());
+
+        // The pinned version shows up on the declared dependency row
+        let deps = package_dependencies(&conn, "auth-service", false, Page::ALL).unwrap();
+        let express = deps.iter().find(|d| d.dependency == "express").unwrap();
+        assert_eq!(express.resolved_versions, ["4.18.2"]);
+        let shared = deps.iter().find(|d| d.dependency == "shared-types").unwrap();
+        assert!(shared.resolved_versions.is_empty());
+    }
+
+    #[test]
+    fn test_dependency_reports_every_locked_version() {
+        let conn = test_db();
+        conn.execute_batch(
+            "INSERT INTO dependencies (package, dependency, dep_kind, version_req, is_internal)
+                 VALUES ('auth-service', 'zod', 'runtime', '^10.0.0', 0),
+                        ('auth-service', 'zod', 'dev', '^9.0.0', 0);
+             INSERT INTO resolved_dependencies (package, dependency, version, is_direct, lockfile)
+                 VALUES ('auth-service', 'zod', '10.1.0', 1, 'package-lock.json'),
+                        ('auth-service', 'zod', '9.4.0', 1, 'package-lock.json');",
+        )
+        .unwrap();
+
+        // "9.4.0" sorts after "10.1.0" as text; neither version is dropped
+        let deps = package_dependencies(&conn, "auth-service", false, Page::ALL).unwrap();
+        let zod: Vec<_> = deps.iter().filter(|d| d.dependency == "zod").collect();
+        assert_eq!(zod.len(), 2);
+        for dep in zod {
+            assert_eq!(dep.resolved_versions, ["9.4.0", "10.1.0"]);
+        }
+        let dependents = package_dependents(&conn, "zod", Page::ALL).unwrap();
+        assert_eq!(dependents[0].resolved_versions, ["9.4.0", "10.1.0"]);
+    }
+
     #[test]
     fn test_dependency_graph_transitive() {
         let conn = test_db();
         // api-gateway -> auth-service -> shared-types
-        let edges = dependency_graph(&conn, "api-gateway", 10, true).unwrap();
+        let edges = dependency_graph(&conn, "api-gateway", 10, true, Page::ALL).unwrap();
         assert_eq!(edges.len(), 2);
         assert_eq!(edges[0].from, "api-gateway");
         assert_eq!(edges[0].to, "auth-service");
@@ -826,7 +1953,7 @@ mod tests {
     fn test_dependency_graph_depth_limit() {
         let conn = test_db();
         // With max_depth=1, only one level from api-gateway
-        let edges = dependency_graph(&conn, "api-gateway", 1, true).unwrap();
+        let edges = dependency_graph(&conn, "api-gateway", 1, true, Page::ALL).unwrap();
         assert_eq!(edges.len(), 1);
         assert_eq!(edges[0].from, "api-gateway");
         assert_eq!(edges[0].to, "auth-service");
@@ -835,17 +1962,261 @@ mod tests {
     #[test]
     fn test_dependency_graph_includes_external() {
         let conn = test_db();
-        let edges = dependency_graph(&conn, "auth-service", 10, false).unwrap();
+        let edges = dependency_graph(&conn, "auth-service", 10, false, Page::ALL).unwrap();
         assert_eq!(edges.len(), 2);
         let targets: Vec<&str> = edges.iter().map(|e| e.to.as_str()).collect();
         assert!(targets.contains(&"shared-types"));
         assert!(targets.contains(&"express"));
     }
 
+    #[test]
+    fn test_all_dependency_edges() {
+        let conn = test_db();
+        let edges = all_dependency_edges(&conn, false, Page::ALL).unwrap();
+        assert_eq!(edges.len(), 3);
+        assert_eq!(edges[0].from, "api-gateway");
+
+        let internal = all_dependency_edges(&conn, true, Page::ALL).unwrap();
+        assert_eq!(internal.len(), 2);
+        assert!(internal.iter().all(|e| e.to != "express"));
+    }
+
+    #[test]
+    fn test_dependency_paths_shortest_and_k_shortest() {
+        let conn = test_db();
+        // Add a direct shortcut: api-gateway -> shared-types (dev)
+        conn.execute(
+            "INSERT INTO dependencies (package, dependency, dep_kind, version_req, is_internal) VALUES (?1, ?2, ?3, ?4, ?5)",
+            ("api-gateway", "shared-types", "dev", None::<String>, 1),
+        ).unwrap();
+
+        let paths = dependency_paths(&conn, "api-gateway", "shared-types", 1, 10, true).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].packages, vec!["api-gateway", "shared-types"]);
+        assert_eq!(paths[0].edges[0].dep_kind, "dev");
+
+        let paths = dependency_paths(&conn, "api-gateway", "shared-types", 5, 10, true).unwrap();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[1].packages, vec!["api-gateway", "auth-service", "shared-types"]);
+        assert_eq!(paths[1].edges.len(), 2);
+    }
+
+    #[test]
+    fn test_dependency_paths_to_external_and_depth_limit() {
+        let conn = test_db();
+        let paths = dependency_paths(&conn, "api-gateway", "express", 1, 10, false).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].packages, vec!["api-gateway", "auth-service", "express"]);
+
+        // express is external, so it is unreachable over internal edges
+        assert!(dependency_paths(&conn, "api-gateway", "express", 1, 10, true).unwrap().is_empty());
+        // Two hops needed; depth 1 finds nothing
+        assert!(dependency_paths(&conn, "api-gateway", "express", 1, 1, false).unwrap().is_empty());
+        // No reverse path
+        assert!(dependency_paths(&conn, "shared-types", "api-gateway", 1, 10, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dependency_paths_stop_at_the_expansion_cap() {
+        let conn = test_db();
+        // app -> hub -> target, and hub -> a 12-package clique whose members all
+        // lead back to hub: every node looks 2 hops from target, but no longer
+        // simple path exists, so without a cap the search tries every path
+        // through the clique.
+        let clique: Vec<String> = (0..12).map(|i| format!("c{i:02}")).collect();
+        let mut edges = vec![("app", "hub"), ("hub", "target")];
+        for a in &clique {
+            edges.push(("hub", a));
+            edges.push((a, "hub"));
+            for b in clique.iter().filter(|b| *b != a) {
+                edges.push((a, b));
+            }
+        }
+        for name in ["app", "hub", "target"].into_iter().chain(clique.iter().map(String::as_str)) {
+            conn.execute(
+                "INSERT INTO packages (name, path, kind) VALUES (?1, ?1, 'npm')",
+                [name],
+            )
+            .unwrap();
+        }
+        for (from, to) in edges {
+            conn.execute(
+                "INSERT INTO dependencies (package, dependency, dep_kind, is_internal) VALUES (?1, ?2, 'runtime', 1)",
+                (from, to),
+            )
+            .unwrap();
+        }
+
+        let paths = dependency_paths(&conn, "app", "target", 5, 20, true).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].packages, vec!["app", "hub", "target"]);
+        assert!(paths.truncated);
+
+        // A search that finds all it asked for isn't truncated
+        let paths = dependency_paths(&conn, "app", "target", 1, 20, true).unwrap();
+        assert!(!paths.truncated);
+    }
+
+    #[test]
+    fn test_find_cycles_none() {
+        let conn = test_db();
+        assert!(find_cycles(&conn, false, Page::ALL).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_cycles_detects_cycle_and_self_loop() {
+        let conn = test_db();
+        // shared-types -> api-gateway closes api-gateway -> auth-service -> shared-types
+        conn.execute(
+            "INSERT INTO dependencies (package, dependency, dep_kind, version_req, is_internal) VALUES (?1, ?2, ?3, ?4, ?5)",
+            ("shared-types", "api-gateway", "dev", None::<String>, 1),
+        ).unwrap();
+
+        let cycles = find_cycles(&conn, false, Page::ALL).unwrap();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].packages, vec!["api-gateway", "auth-service", "shared-types"]);
+        assert_eq!(cycles[0].edges.len(), 3);
+        assert!(cycles[0].edges.iter().any(|e| e.from == "shared-types" && e.dep_kind == "dev"));
+
+        // The only back edge is dev, so ignoring dev edges breaks the cycle
+        assert!(find_cycles(&conn, true, Page::ALL).unwrap().is_empty());
+
+        conn.execute(
+            "INSERT INTO dependencies (package, dependency, dep_kind, version_req, is_internal) VALUES (?1, ?2, ?3, ?4, ?5)",
+            ("auth-service", "auth-service", "runtime", None::<String>, 1),
+        ).unwrap();
+        let cycles = find_cycles(&conn, true, Page::ALL).unwrap();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].packages, vec!["auth-service"]);
+    }
+
+    #[test]
+    fn test_version_drift() {
+        let conn = test_db();
+        // auth-service already declares express ^4.18.0
+        for (package, dependency, version_req) in [
+            ("shared-types", "express", "^4.17.1"),
+            ("api-gateway", "express", "v1.0.0"),
+            ("shared-types", "lodash", "^4.17.21"),
+            ("auth-service", "lodash", "^4.17.21"),
+        ] {
+            conn.execute(
+                "INSERT INTO dependencies (package, dependency, dep_kind, version_req, is_internal) VALUES (?1, ?2, 'runtime', ?3, 0)",
+                (package, dependency, version_req),
+            ).unwrap();
+        }
+
+        let drift = version_drift(&conn, None, 2, Page::ALL).unwrap();
+        assert_eq!(drift.len(), 1);
+        assert_eq!(drift[0].dependency, "express");
+        assert_eq!(drift[0].kind, "npm");
+        assert_eq!(drift[0].version_count, 2);
+        assert_eq!(drift[0].package_count, 2);
+
+        // The go package's express is a different library
+        let npm = version_drift(&conn, Some("npm"), 1, Page::ALL).unwrap();
+        let summary: Vec<(&str, usize, usize)> = npm
+            .iter()
+            .map(|d| (d.dependency.as_str(), d.version_count, d.package_count))
+            .collect();
+        assert_eq!(summary, vec![("express", 2, 2), ("lodash", 1, 2)]);
+        assert_eq!(npm[1].versions[0].packages, vec!["auth-service", "shared-types"]);
+    }
+
+    fn rule(name: &str, from: RuleSelector, allow: Vec<RuleSelector>, deny: Vec<RuleSelector>) -> LayeringRule {
+        LayeringRule {
+            name: name.into(),
+            description: None,
+            from,
+            allow,
+            deny,
+            exclude_dev: false,
+        }
+    }
+
+    fn path_selector(path: &str) -> RuleSelector {
+        RuleSelector {
+            path: Some(path.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_rules_deny_and_allow() {
+        let conn = test_db();
+        // api-gateway (services/gateway) -> auth-service (services/auth) -> shared-types (packages/shared-types)
+        let deny = rule(
+            "services-no-auth",
+            path_selector("services/gateway"),
+            vec![],
+            vec![RuleSelector {
+                name: Some("auth-*".into()),
+                ..Default::default()
+            }],
+        );
+        let violations = check_rules(&conn, &[deny], &[], Page::ALL).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].from, "api-gateway");
+        assert_eq!(violations[0].to, "auth-service");
+        assert_eq!(violations[0].reason, ViolationReason::Denied);
+        assert!(!violations[0].proposed);
+
+        // npm packages under services/ may only depend on packages/**
+        let allow = rule(
+            "services-use-packages",
+            RuleSelector {
+                path: Some("services/**".into()),
+                kind: Some("npm".into()),
+                ..Default::default()
+            },
+            vec![path_selector("packages/**")],
+            vec![],
+        );
+        assert!(check_rules(&conn, std::slice::from_ref(&allow), &[], Page::ALL).unwrap().is_empty());
+
+        // A proposed edge is checked too
+        let proposed = GraphEdge {
+            from: "auth-service".into(),
+            to: "api-gateway".into(),
+            dep_kind: "runtime".into(),
+        };
+        let violations = check_rules(&conn, &[allow], &[proposed], Page::ALL).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].reason, ViolationReason::NotAllowed);
+        assert!(violations[0].proposed);
+    }
+
+    #[test]
+    fn test_check_rules_errors() {
+        let conn = test_db();
+        let empty = rule("empty", RuleSelector::default(), vec![], vec![]);
+        assert!(check_rules(&conn, &[empty], &[], Page::ALL).is_err());
+
+        let bad_glob = rule("bad", path_selector("[libs"), vec![], vec![path_selector("services/**")]);
+        assert!(check_rules(&conn, &[bad_glob], &[], Page::ALL).is_err());
+
+        let ok = rule("ok", RuleSelector::default(), vec![], vec![path_selector("services/**")]);
+        let unknown = GraphEdge {
+            from: "nope".into(),
+            to: "auth-service".into(),
+            dep_kind: "runtime".into(),
+        };
+        assert!(check_rules(&conn, &[ok], &[unknown], Page::ALL).is_err());
+    }
+
+    #[test]
+    fn test_selector_matches_directory_itself() {
+        let selector = path_selector("libs/core/**");
+        let compiled = CompiledSelector::new(&selector, "r").unwrap();
+        assert!(compiled.matches("core", "libs/core", "npm"));
+        assert!(compiled.matches("core-utils", "libs/core/utils", "npm"));
+        assert!(!compiled.matches("corex", "libs/corex", "npm"));
+    }
+
     #[test]
     fn test_list_packages_all() {
         let conn = test_db();
-        let pkgs = list_packages(&conn, None).unwrap();
+        let pkgs = list_packages(&conn, None, None, Page::ALL).unwrap();
         assert_eq!(pkgs.len(), 3);
         // Ordered by name
         assert_eq!(pkgs[0].name, "api-gateway");
@@ -853,12 +2224,59 @@ mod tests {
         assert_eq!(pkgs[2].name, "shared-types");
     }
 
+    #[test]
+    fn test_list_packages_paged() {
+        let conn = test_db();
+        let page = list_packages(&conn, None, None, Page::new(2, 0)).unwrap();
+        assert_eq!(page.total, 3);
+        assert!(page.has_more);
+        assert_eq!(page.next_offset, Some(2));
+        assert_eq!(page.results.len(), 2);
+
+        let last = list_packages(&conn, None, None, Page::new(2, 2)).unwrap();
+        assert_eq!(last.total, 3);
+        assert!(!last.has_more);
+        assert_eq!(last.next_offset, None);
+        assert_eq!(last[0].name, "shared-types");
+
+        let past_end = list_packages(&conn, None, None, Page::new(2, 10)).unwrap();
+        assert_eq!(past_end.total, 3);
+        assert!(past_end.is_empty());
+        assert!(!past_end.has_more);
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(fts_query("auth", false).as_deref(), Some("\"auth\"*"));
+        assert_eq!(
+            fts_query("parseConfig \"x", false).as_deref(),
+            Some("(\"parseConfig\"* OR (\"parse\"* \"config\"*)) \"x\"*")
+        );
+        assert_eq!(fts_query("  -- ", false), None);
+        assert_eq!(fts_query("a NEAR b", true).as_deref(), Some("a NEAR b"));
+        assert_eq!(fts_query(" ", true), None);
+    }
+
+    #[test]
+    fn test_page_apply() {
+        let paged = Page::new(2, 1).apply(vec!["a", "b", "c", "d"]);
+        assert_eq!(paged.results, ["b", "c"]);
+        assert_eq!(paged.total, 4);
+        assert_eq!(paged.next_offset, Some(3));
+
+        let all = Page::ALL.apply(vec!["a", "b"]);
+        assert_eq!(all.results, ["a", "b"]);
+        assert!(!all.has_more);
+        let json = serde_json::to_value(&all).unwrap();
+        assert!(json.get("limit").is_none() && json.get("next_offset").is_none());
+    }
+
     #[test]
     fn test_list_packages_by_kind() {
         let conn = test_db();
-        let npm = list_packages(&conn, Some("npm")).unwrap();
+        let npm = list_packages(&conn, Some("npm"), None, Page::ALL).unwrap();
         assert_eq!(npm.len(), 2);
-        let go = list_packages(&conn, Some("go")).unwrap();
+        let go = list_packages(&conn, Some("go"), None, Page::ALL).unwrap();
         assert_eq!(go.len(), 1);
         assert_eq!(go[0].name, "api-gateway");
     }
@@ -942,16 +2360,70 @@ mod tests {
     #[test]
     fn test_search_symbols_by_name() {
         let conn = test_db_with_symbols();
-        let results = search_symbols(&conn, "AuthService", None, None).unwrap();
-        assert_eq!(results.len(), 1);
+        let results = search_symbols(&conn, "AuthService", None, None, false, Page::ALL).unwrap();
         assert_eq!(results[0].name, "AuthService");
         assert_eq!(results[0].package, "auth-service");
+        // `validate` only matches by its path, services/auth/..., so it ranks below
+        let names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["AuthService", "validate"]);
+    }
+
+    #[test]
+    fn test_search_symbols_by_identifier_parts() {
+        let conn = test_db();
+        for (name, signature) in [
+            ("parseConfig", "function parseConfig(path: string): Config"),
+            ("parse_config_file", "def parse_config_file(path)"),
+            ("AuthenticationService", "class AuthenticationService"),
+            ("loadConfig", "function loadConfig(): Config"),
+            ("readFile", "function readFile(options: ConfigOptions)"),
+        ] {
+            conn.execute(
+                "INSERT INTO symbols (package, name, kind, signature, file_path, line, name_words)
+                 VALUES ('auth-service', ?1, 'function', ?2, 'src/lib.ts', 1, ?3)",
+                (name, signature, crate::symbols::identifier_words(name).join(" ")),
+            )
+            .unwrap();
+        }
+        let names = |query: &str| -> Vec<String> {
+            search_symbols(&conn, query, None, None, false, Page::ALL)
+                .unwrap()
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+
+        let mut parse_config = names("parse config");
+        parse_config.sort();
+        assert_eq!(parse_config, ["parseConfig", "parse_config_file"]);
+        // The exact identifier ranks first
+        assert_eq!(names("parseConfig"), ["parseConfig", "parse_config_file"]);
+        assert_eq!(names("auth"), ["AuthenticationService"]);
+        assert_eq!(names("authent serv"), ["AuthenticationService"]);
+        // A match in the name outranks one in the signature only
+        let config = names("config");
+        assert_eq!(config.len(), 4);
+        assert_eq!(config.last().unwrap(), "readFile");
+    }
+
+    #[test]
+    fn test_search_symbols_raw() {
+        let conn = test_db_with_symbols();
+        let results = search_symbols(&conn, "name:validate OR name:UserConfig", None, None, true, Page::ALL).unwrap();
+        let mut names: Vec<&str> = results.iter().map(|s| s.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["UserConfig", "validate"]);
+
+        let err = search_symbols(&conn, "validate AND (", None, None, true, Page::ALL).unwrap_err();
+        assert!(format!("{err:#}").contains("Invalid FTS5 query"), "{err:#}");
+        // Without raw, the same text is just words
+        assert!(search_symbols(&conn, "validate AND (", None, None, false, Page::ALL).is_ok());
     }
 
     #[test]
     fn test_search_symbols_by_signature() {
         let conn = test_db_with_symbols();
-        let results = search_symbols(&conn, "token", None, None).unwrap();
+        let results = search_symbols(&conn, "token", None, None, false, Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "validate");
     }
@@ -959,38 +2431,38 @@ mod tests {
     #[test]
     fn test_search_symbols_filter_by_package() {
         let conn = test_db_with_symbols();
-        let results = search_symbols(&conn, "interface", Some("shared-types"), None).unwrap();
+        let results = search_symbols(&conn, "interface", Some("shared-types"), None, false, Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "UserConfig");
 
-        let results = search_symbols(&conn, "interface", Some("auth-service"), None).unwrap();
+        let results = search_symbols(&conn, "interface", Some("auth-service"), None, false, Page::ALL).unwrap();
         assert!(results.is_empty());
     }
 
     #[test]
     fn test_search_symbols_filter_by_kind() {
         let conn = test_db_with_symbols();
-        let results = search_symbols(&conn, "AuthService", None, Some("class")).unwrap();
+        let results = search_symbols(&conn, "AuthService", None, Some("class"), false, Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
 
-        let results = search_symbols(&conn, "AuthService", None, Some("function")).unwrap();
+        let results = search_symbols(&conn, "AuthService", None, Some("function"), false, Page::ALL).unwrap();
         assert!(results.is_empty());
     }
 
     #[test]
     fn test_search_symbols_empty_query() {
         let conn = test_db_with_symbols();
-        let results = search_symbols(&conn, "", None, None).unwrap();
+        let results = search_symbols(&conn, "", None, None, false, Page::ALL).unwrap();
         assert!(results.is_empty());
     }
 
     #[test]
     fn test_get_package_symbols() {
         let conn = test_db_with_symbols();
-        let results = get_package_symbols(&conn, "auth-service", None).unwrap();
+        let results = get_package_symbols(&conn, "auth-service", None, Page::ALL).unwrap();
         assert_eq!(results.len(), 2);
 
-        let results = get_package_symbols(&conn, "auth-service", Some("method")).unwrap();
+        let results = get_package_symbols(&conn, "auth-service", Some("method"), Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "validate");
     }
@@ -998,16 +2470,16 @@ mod tests {
     #[test]
     fn test_get_symbol() {
         let conn = test_db_with_symbols();
-        let results = get_symbol(&conn, "AuthService", None).unwrap();
+        let results = get_symbol(&conn, "AuthService", None, Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
 
-        let results = get_symbol(&conn, "AuthService", Some("auth-service")).unwrap();
+        let results = get_symbol(&conn, "AuthService", Some("auth-service"), Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
 
-        let results = get_symbol(&conn, "AuthService", Some("shared-types")).unwrap();
+        let results = get_symbol(&conn, "AuthService", Some("shared-types"), Page::ALL).unwrap();
         assert!(results.is_empty());
 
-        let results = get_symbol(&conn, "nonexistent", None).unwrap();
+        let results = get_symbol(&conn, "nonexistent", None, Page::ALL).unwrap();
         assert!(results.is_empty());
     }
 
@@ -1039,7 +2511,7 @@ mod tests {
     #[test]
     fn test_search_files_by_filename() {
         let conn = test_db_with_files();
-        let results = search_files(&conn, "middleware", None, None).unwrap();
+        let results = search_files(&conn, "middleware", None, None, false, Page::ALL).unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].path, "services/auth/src/middleware.ts");
     }
@@ -1047,28 +2519,28 @@ mod tests {
     #[test]
     fn test_search_files_by_path_segment() {
         let conn = test_db_with_files();
-        let results = search_files(&conn, "gateway", None, None).unwrap();
+        let results = search_files(&conn, "gateway", None, None, false, Page::ALL).unwrap();
         assert_eq!(results.len(), 2);
     }
 
     #[test]
     fn test_search_files_filter_by_package() {
         let conn = test_db_with_files();
-        let results = search_files(&conn, "ts", Some("auth-service"), None).unwrap();
+        let results = search_files(&conn, "ts", Some("auth-service"), None, false, Page::ALL).unwrap();
         assert!(results.iter().all(|f| f.package.as_deref() == Some("auth-service")));
     }
 
     #[test]
     fn test_search_files_filter_by_extension() {
         let conn = test_db_with_files();
-        let results = search_files(&conn, "auth", None, Some("ts")).unwrap();
+        let results = search_files(&conn, "auth", None, Some("ts"), false, Page::ALL).unwrap();
         assert!(results.iter().all(|f| f.extension == "ts"));
     }
 
     #[test]
     fn test_search_files_combined_filters() {
         let conn = test_db_with_files();
-        let results = search_files(&conn, "auth", Some("auth-service"), Some("ts")).unwrap();
+        let results = search_files(&conn, "auth", Some("auth-service"), Some("ts"), false, Page::ALL).unwrap();
         assert!(results.iter().all(|f| f.package.as_deref() == Some("auth-service") && f.extension == "ts"));
         assert!(!results.is_empty());
     }
@@ -1076,14 +2548,14 @@ mod tests {
     #[test]
     fn test_search_files_empty_query() {
         let conn = test_db_with_files();
-        let results = search_files(&conn, "", None, None).unwrap();
+        let results = search_files(&conn, "", None, None, false, Page::ALL).unwrap();
         assert!(results.is_empty());
     }
 
     #[test]
     fn test_list_package_files_basic() {
         let conn = test_db_with_files();
-        let results = list_package_files(&conn, "auth-service", None).unwrap();
+        let results = list_package_files(&conn, "auth-service", None, Page::ALL).unwrap();
         assert_eq!(results.len(), 3);
         // Should be ordered by path
         assert!(results[0].path < results[1].path);
@@ -1092,7 +2564,7 @@ mod tests {
     #[test]
     fn test_list_package_files_extension_filter() {
         let conn = test_db_with_files();
-        let results = list_package_files(&conn, "auth-service", Some("ts")).unwrap();
+        let results = list_package_files(&conn, "auth-service", Some("ts"), Page::ALL).unwrap();
         assert_eq!(results.len(), 2);
         assert!(results.iter().all(|f| f.extension == "ts"));
     }
@@ -1101,7 +2573,7 @@ mod tests {
     fn test_reverse_dependency_graph() {
         let conn = test_db();
         // shared-types is depended on by auth-service, which is depended on by api-gateway
-        let edges = reverse_dependency_graph(&conn, "shared-types", 10).unwrap();
+        let edges = reverse_dependency_graph(&conn, "shared-types", 10, Page::ALL).unwrap();
         assert_eq!(edges.len(), 2);
         // First level: auth-service depends on shared-types
         assert_eq!(edges[0].from, "auth-service");
@@ -1114,7 +2586,7 @@ mod tests {
     #[test]
     fn test_reverse_dependency_graph_depth_limit() {
         let conn = test_db();
-        let edges = reverse_dependency_graph(&conn, "shared-types", 1).unwrap();
+        let edges = reverse_dependency_graph(&conn, "shared-types", 1, Page::ALL).unwrap();
         assert_eq!(edges.len(), 1);
         assert_eq!(edges[0].from, "auth-service");
         assert_eq!(edges[0].to, "shared-types");
@@ -1123,14 +2595,95 @@ mod tests {
     #[test]
     fn test_reverse_dependency_graph_no_dependents() {
         let conn = test_db();
-        let edges = reverse_dependency_graph(&conn, "api-gateway", 10).unwrap();
+        let edges = reverse_dependency_graph(&conn, "api-gateway", 10, Page::ALL).unwrap();
         assert!(edges.is_empty());
     }
 
+    #[test]
+    fn test_affected_packages_expands_dependents() {
+        let conn = test_db();
+        let files = vec![
+            "packages/shared-types/src/index.ts".to_string(),
+            "./packages/shared-types/package.json".to_string(),
+            "README.md".to_string(),
+        ];
+        let result = affected_packages(&conn, &files).unwrap();
+        assert_eq!(result.changed, vec!["shared-types"]);
+        assert_eq!(result.affected, vec!["api-gateway", "auth-service", "shared-types"]);
+        assert_eq!(result.unowned_files, vec!["README.md"]);
+    }
+
+    #[test]
+    fn test_affected_packages_leaf_and_prefix_boundary() {
+        let conn = test_db();
+        // "services/gateway-v2" must not match the "services/gateway" package
+        let files = vec![
+            "services/gateway/main.go".to_string(),
+            "services/gateway-v2/main.go".to_string(),
+        ];
+        let result = affected_packages(&conn, &files).unwrap();
+        assert_eq!(result.changed, vec!["api-gateway"]);
+        assert_eq!(result.affected, vec!["api-gateway"]);
+        assert_eq!(result.unowned_files, vec!["services/gateway-v2/main.go"]);
+    }
+
+    #[test]
+    fn test_build_order_all_packages() {
+        let conn = test_db();
+        let result = build_order(&conn, &[], false).unwrap();
+        assert_eq!(
+            result.waves,
+            vec![vec!["shared-types"], vec!["auth-service"], vec!["api-gateway"]]
+        );
+        assert_eq!(result.order, vec!["shared-types", "auth-service", "api-gateway"]);
+    }
+
+    #[test]
+    fn test_build_order_closure_and_parallel_wave() {
+        let conn = test_db();
+        conn.execute(
+            "INSERT INTO packages (name, path, kind) VALUES ('billing', 'services/billing', 'npm')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO dependencies (package, dependency, dep_kind, is_internal) VALUES ('billing', 'shared-types', 'runtime', 1)",
+            [],
+        )
+        .unwrap();
+
+        let result = build_order(&conn, &["auth-service".into(), "billing".into()], false).unwrap();
+        assert_eq!(
+            result.waves,
+            vec![vec!["shared-types"], vec!["auth-service", "billing"]]
+        );
+
+        let err = build_order(&conn, &["missing".into()], false).unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_build_order_fails_on_cycle() {
+        let conn = test_db();
+        conn.execute(
+            "INSERT INTO dependencies (package, dependency, dep_kind, is_internal) VALUES ('shared-types', 'api-gateway', 'dev', 1)",
+            [],
+        )
+        .unwrap();
+
+        let err = build_order(&conn, &[], false).unwrap_err().to_string();
+        assert!(err.contains("cycle among api-gateway, auth-service, shared-types"), "{err}");
+        assert!(err.contains("shared-types -> api-gateway (dev)"), "{err}");
+
+        // Dropping dev edges breaks the cycle
+        let result = build_order(&conn, &[], true).unwrap();
+        assert_eq!(result.order.len(), 3);
+    }
+
     #[test]
     fn test_packages_by_path_prefix() {
         let conn = test_db();
-        let results = packages_by_path_prefix(&conn, "services/").unwrap();
+        let results = packages_by_path_prefix(&conn, "services/", Page::ALL).unwrap();
         assert_eq!(results.len(), 2);
         let names: Vec<&str> = results.iter().map(|p| p.name.as_str()).collect();
         assert!(names.contains(&"auth-service"));
@@ -1140,14 +2693,14 @@ mod tests {
     #[test]
     fn test_packages_by_path_prefix_no_match() {
         let conn = test_db();
-        let results = packages_by_path_prefix(&conn, "nonexistent/").unwrap();
+        let results = packages_by_path_prefix(&conn, "nonexistent/", Page::ALL).unwrap();
         assert!(results.is_empty());
     }
 
     #[test]
     fn test_extension_distribution() {
         let conn = test_db_with_files();
-        let dist = extension_distribution(&conn).unwrap();
+        let dist = extension_distribution(&conn, Page::ALL).unwrap();
         assert!(!dist.is_empty());
         // ts files are most common (3 of them)
         assert_eq!(dist[0].extension, "ts");
@@ -0,0 +1,210 @@
+use anyhow::Result;
+use regex::Regex;
+use std::path::Path;
+
+/// Where GitHub and GitLab look for a CODEOWNERS file, in order of precedence.
+pub const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// A parsed CODEOWNERS file. Rules are gitignore-style patterns; the last rule
+/// matching a path decides its owners.
+#[derive(Debug)]
+pub struct CodeOwners {
+    /// Repo-relative path of the CODEOWNERS file these rules came from.
+    pub source: String,
+    /// SHA-256 of the file contents, for change detection.
+    pub content_hash: String,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    /// Matches files: the pattern itself or anything beneath a matched directory.
+    file: Regex,
+    /// Matches directories, where a trailing-slash pattern may match the directory itself.
+    dir: Regex,
+    owners: Vec<String>,
+}
+
+/// Load the first CODEOWNERS file found under `repo_root`, if any.
+pub fn load(repo_root: &Path) -> Result<Option<CodeOwners>> {
+    for rel in CODEOWNERS_PATHS {
+        let path = repo_root.join(rel);
+        if path.is_file() {
+            let content = std::fs::read_to_string(&path)?;
+            let mut owners = parse(&content);
+            owners.source = rel.to_string();
+            owners.content_hash = super::hash::hash_file(&path)?;
+            return Ok(Some(owners));
+        }
+    }
+    Ok(None)
+}
+
+pub fn parse(content: &str) -> CodeOwners {
+    let mut rules = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            // `[Section]` headers are GitLab-only and carry no pattern
+            continue;
+        }
+        let mut tokens = line.split_whitespace();
+        let Some(pattern) = tokens.next() else {
+            continue;
+        };
+        let owners: Vec<String> = tokens
+            .take_while(|t| !t.starts_with('#'))
+            .map(|t| t.to_string())
+            .collect();
+        if let Some((file, dir)) = compile(pattern) {
+            rules.push(Rule { file, dir, owners });
+        }
+    }
+    CodeOwners {
+        source: String::new(),
+        content_hash: String::new(),
+        rules,
+    }
+}
+
+/// Translate a CODEOWNERS pattern into (file, directory) regexes.
+fn compile(pattern: &str) -> Option<(Regex, Regex)> {
+    let dir_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    // A slash at the start or in the middle anchors the pattern to the repo root
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+
+    let mut body = String::new();
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    body.push_str("(?:.*/)?");
+                } else {
+                    body.push_str(".*");
+                }
+            }
+            '*' => body.push_str("[^/]*"),
+            '?' => body.push_str("[^/]"),
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    body.push_str(&regex::escape(&escaped.to_string()));
+                }
+            }
+            c => body.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+
+    let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+    let file_suffix = if dir_only { "/.*$" } else { "(?:/.*)?$" };
+    let file = Regex::new(&format!("{prefix}{body}{file_suffix}")).ok()?;
+    let dir = Regex::new(&format!("{prefix}{body}(?:/.*)?$")).ok()?;
+    Some((file, dir))
+}
+
+impl CodeOwners {
+    /// Owners of a file, by the last matching rule. Empty when no rule matches
+    /// or the matching rule lists no owners.
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| r.file.is_match(path))
+            .map_or(&[], |r| &r.owners)
+    }
+
+    /// Owners of a directory (e.g. a package root), by the last matching rule.
+    pub fn owners_of_dir(&self, dir: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| r.dir.is_match(dir))
+            .map_or(&[], |r| &r.owners)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODEOWNERS: &str = r#"
+# Default owners for everything
+*                       @acme/platform
+
+*.js                    @acme/frontend
+/services/payments/     @acme/payments billing@acme.com # inline comment
+docs/                   @acme/docs
+apps/**/config.yml      @acme/sre
+/services/payments/vendor/
+"#;
+
+    #[test]
+    fn test_last_match_wins() {
+        let owners = parse(CODEOWNERS);
+        assert_eq!(owners.owners_of("README.md"), ["@acme/platform"]);
+        assert_eq!(owners.owners_of("web/src/app.js"), ["@acme/frontend"]);
+        assert_eq!(
+            owners.owners_of("services/payments/src/app.js"),
+            ["@acme/payments", "billing@acme.com"]
+        );
+        // A rule without owners leaves the files unowned
+        assert!(
+            owners
+                .owners_of("services/payments/vendor/lib.go")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_pattern_anchoring() {
+        let owners = parse(CODEOWNERS);
+        // `docs/` is unanchored: any docs directory
+        assert_eq!(owners.owners_of("docs/intro.md"), ["@acme/docs"]);
+        assert_eq!(
+            owners.owners_of("services/auth/docs/api.md"),
+            ["@acme/docs"]
+        );
+        // `/services/payments/` is anchored to the root
+        assert_eq!(
+            owners.owners_of("legacy/services/payments/x.go"),
+            ["@acme/platform"]
+        );
+        // `**` crosses directories
+        assert_eq!(
+            owners.owners_of("apps/web/deploy/config.yml"),
+            ["@acme/sre"]
+        );
+        assert_eq!(owners.owners_of("apps/config.yml"), ["@acme/sre"]);
+    }
+
+    #[test]
+    fn test_directory_owners() {
+        let owners = parse(CODEOWNERS);
+        assert_eq!(
+            owners.owners_of_dir("services/payments"),
+            ["@acme/payments", "billing@acme.com"]
+        );
+        assert_eq!(owners.owners_of_dir("services/auth"), ["@acme/platform"]);
+        // `docs/` is dir-only, so a file named `docs` doesn't match
+        assert_eq!(owners.owners_of("docs"), ["@acme/platform"]);
+    }
+
+    #[test]
+    fn test_load_prefers_github_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("CODEOWNERS"), "* @root\n").unwrap();
+        std::fs::create_dir_all(dir.path().join(".github")).unwrap();
+        std::fs::write(dir.path().join(".github/CODEOWNERS"), "* @github\n").unwrap();
+
+        let owners = load(dir.path()).unwrap().unwrap();
+        assert_eq!(owners.source, ".github/CODEOWNERS");
+        assert_eq!(owners.owners_of("a.txt"), ["@github"]);
+
+        let empty = tempfile::TempDir::new().unwrap();
+        assert!(load(empty.path()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,341 @@
+use super::manifest::{DepInfo, DepKind, ManifestParser, PackageInfo};
+use anyhow::{Result, anyhow};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::path::Path;
+
+/// MSBuild properties read from `<PropertyGroup>` elements.
+#[derive(Debug, Default)]
+struct ProjectProperties {
+    target_framework: Option<String>,
+    target_frameworks: Option<String>,
+    version: Option<String>,
+    version_prefix: Option<String>,
+    description: Option<String>,
+    package_id: Option<String>,
+    assembly_name: Option<String>,
+}
+
+impl ProjectProperties {
+    /// Record a property value. The first occurrence wins, so conditional
+    /// PropertyGroups further down the file don't override the defaults.
+    fn set(&mut self, property: &str, value: String) {
+        let slot = match property {
+            "TargetFramework" => &mut self.target_framework,
+            "TargetFrameworks" => &mut self.target_frameworks,
+            "Version" => &mut self.version,
+            "VersionPrefix" => &mut self.version_prefix,
+            "Description" => &mut self.description,
+            "PackageId" => &mut self.package_id,
+            "AssemblyName" => &mut self.assembly_name,
+            _ => return,
+        };
+        if slot.is_none() && !value.is_empty() {
+            *slot = Some(value);
+        }
+    }
+}
+
+/// Parser for C# project files (`*.csproj`).
+pub struct CsprojParser;
+
+/// Parser for F# project files (`*.fsproj`).
+pub struct FsprojParser;
+
+impl ManifestParser for CsprojParser {
+    fn filename(&self) -> &'static str {
+        "*.csproj"
+    }
+
+    fn parse(&self, manifest_path: &Path, relative_dir: &str) -> Result<PackageInfo> {
+        parse_project(manifest_path, relative_dir)
+    }
+}
+
+impl ManifestParser for FsprojParser {
+    fn filename(&self) -> &'static str {
+        "*.fsproj"
+    }
+
+    fn parse(&self, manifest_path: &Path, relative_dir: &str) -> Result<PackageInfo> {
+        parse_project(manifest_path, relative_dir)
+    }
+}
+
+/// Name of an MSBuild project: its file stem, which is also how
+/// `ProjectReference` and `.sln` entries refer to it.
+pub fn project_name(project_path: &str) -> Option<String> {
+    let file = project_path.rsplit(['/', '\\']).next()?;
+    let (stem, _) = file.rsplit_once('.')?;
+    (!stem.is_empty()).then(|| stem.to_string())
+}
+
+fn parse_project(manifest_path: &Path, relative_dir: &str) -> Result<PackageInfo> {
+    let content = std::fs::read_to_string(manifest_path)?;
+    let file_name = manifest_path
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or_default();
+    let name = project_name(file_name)
+        .ok_or_else(|| anyhow!("Cannot derive project name from {}", file_name))?;
+
+    // csproj files interleave PropertyGroup and ItemGroup elements freely, so
+    // walk the event stream rather than deserializing into fixed structs.
+    let mut reader = Reader::from_str(&content);
+    reader.config_mut().trim_text(true);
+
+    let mut props = ProjectProperties::default();
+    let mut sdk: Option<String> = None;
+    let mut dependencies: Vec<DepInfo> = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    // Index into `dependencies` of an open <PackageReference> awaiting a child <Version>
+    let mut open_package_ref: Option<usize> = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                let tag = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match tag.as_str() {
+                    "Project" if stack.is_empty() => sdk = attribute(&e, "Sdk")?,
+                    "PackageReference" if let Some(dep) = package_reference(&e)? => {
+                        dependencies.push(dep);
+                        open_package_ref = Some(dependencies.len() - 1);
+                    }
+                    "ProjectReference" => dependencies.extend(project_reference(&e)?),
+                    _ => {}
+                }
+                stack.push(tag);
+            }
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"PackageReference" => dependencies.extend(package_reference(&e)?),
+                b"ProjectReference" => dependencies.extend(project_reference(&e)?),
+                _ => {}
+            },
+            Event::End(_) => {
+                let closed = stack.pop();
+                if closed.as_deref() == Some("PackageReference") {
+                    open_package_ref = None;
+                }
+            }
+            Event::Text(t) => {
+                let text = t.unescape()?.trim().to_string();
+                let depth = stack.len();
+                if depth >= 2 && stack[depth - 2] == "PropertyGroup" {
+                    props.set(&stack[depth - 1], text);
+                } else if depth >= 2
+                    && stack[depth - 2] == "PackageReference"
+                    && stack[depth - 1] == "Version"
+                    && let Some(i) = open_package_ref
+                {
+                    dependencies[i].version_req.get_or_insert(text);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut target_frameworks: Vec<String> = Vec::new();
+    if let Some(tf) = &props.target_framework {
+        target_frameworks.push(tf.clone());
+    }
+    if let Some(tfs) = &props.target_frameworks {
+        for tf in tfs.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            if !target_frameworks.iter().any(|t| t == tf) {
+                target_frameworks.push(tf.to_string());
+            }
+        }
+    }
+
+    let mut metadata = serde_json::Map::new();
+    if !target_frameworks.is_empty() {
+        metadata.insert(
+            "target_frameworks".into(),
+            serde_json::json!(target_frameworks),
+        );
+    }
+    if let Some(sdk) = sdk {
+        metadata.insert("sdk".into(), serde_json::json!(sdk));
+    }
+    if let Some(id) = props.package_id {
+        metadata.insert("package_id".into(), serde_json::json!(id));
+    }
+    if let Some(assembly) = props.assembly_name {
+        metadata.insert("assembly_name".into(), serde_json::json!(assembly));
+    }
+
+    Ok(PackageInfo {
+        name,
+        path: relative_dir.to_string(),
+        kind: "dotnet",
+        version: props.version.or(props.version_prefix),
+        description: props.description,
+        metadata: (!metadata.is_empty()).then_some(serde_json::Value::Object(metadata)),
+        dependencies,
+    })
+}
+
+fn attribute(e: &BytesStart, name: &str) -> Result<Option<String>> {
+    Ok(e.try_get_attribute(name)?
+        .map(|a| a.unescape_value().map(|v| v.trim().to_string()))
+        .transpose()?)
+}
+
+/// `<PackageReference Include="Serilog" Version="3.1.1" />` → external NuGet dependency.
+/// Assets marked `PrivateAssets="all"` (analyzers, source generators) are build-only.
+fn package_reference(e: &BytesStart) -> Result<Option<DepInfo>> {
+    let Some(name) = attribute(e, "Include")?.filter(|n| !n.is_empty()) else {
+        return Ok(None);
+    };
+    let private_all = attribute(e, "PrivateAssets")?.is_some_and(|v| v.eq_ignore_ascii_case("all"));
+    Ok(Some(DepInfo {
+        name,
+        version_req: attribute(e, "Version")?,
+        dep_kind: if private_all {
+            DepKind::Build
+        } else {
+            DepKind::Runtime
+        },
+    }))
+}
+
+/// `<ProjectReference Include="..\Shared\Shared.csproj" />` → dependency on the
+/// project named by the file stem, which resolves as internal.
+fn project_reference(e: &BytesStart) -> Result<Option<DepInfo>> {
+    Ok(attribute(e, "Include")?
+        .and_then(|path| project_name(&path))
+        .map(|name| DepInfo {
+            name,
+            version_req: None,
+            dep_kind: DepKind::Runtime,
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_manifest(dir: &Path, filename: &str, content: &str) -> std::path::PathBuf {
+        let path = dir.join(filename);
+        let mut f = std::fs::File::create(&path).unwrap();
+        f.write_all(content.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_sdk_style_csproj() {
+        let dir = TempDir::new().unwrap();
+        let path = write_manifest(
+            dir.path(),
+            "Billing.Api.csproj",
+            r#"<Project Sdk="Microsoft.NET.Sdk.Web">
+  <PropertyGroup>
+    <TargetFramework>net8.0</TargetFramework>
+    <Version>2.3.0</Version>
+    <Description>Billing HTTP API</Description>
+  </PropertyGroup>
+  <ItemGroup>
+    <PackageReference Include="Serilog" Version="3.1.1" />
+    <PackageReference Include="StyleCop.Analyzers" Version="1.1.118" PrivateAssets="all" />
+    <PackageReference Include="Polly">
+      <Version>8.2.0</Version>
+    </PackageReference>
+  </ItemGroup>
+  <PropertyGroup Condition="'$(Configuration)' == 'Release'">
+    <Version>9.9.9</Version>
+  </PropertyGroup>
+  <ItemGroup>
+    <ProjectReference Include="..\Billing.Core\Billing.Core.csproj" />
+  </ItemGroup>
+</Project>"#,
+        );
+
+        let info = CsprojParser.parse(&path, "src/Billing.Api").unwrap();
+        assert_eq!(info.name, "Billing.Api");
+        assert_eq!(info.kind, "dotnet");
+        assert_eq!(info.path, "src/Billing.Api");
+        assert_eq!(info.version.as_deref(), Some("2.3.0"));
+        assert_eq!(info.description.as_deref(), Some("Billing HTTP API"));
+
+        let meta = info.metadata.unwrap();
+        assert_eq!(meta["target_frameworks"], serde_json::json!(["net8.0"]));
+        assert_eq!(meta["sdk"], "Microsoft.NET.Sdk.Web");
+
+        assert_eq!(info.dependencies.len(), 4);
+        let serilog = info
+            .dependencies
+            .iter()
+            .find(|d| d.name == "Serilog")
+            .unwrap();
+        assert_eq!(serilog.version_req.as_deref(), Some("3.1.1"));
+        assert!(matches!(serilog.dep_kind, DepKind::Runtime));
+        let stylecop = info
+            .dependencies
+            .iter()
+            .find(|d| d.name == "StyleCop.Analyzers")
+            .unwrap();
+        assert!(matches!(stylecop.dep_kind, DepKind::Build));
+        let polly = info
+            .dependencies
+            .iter()
+            .find(|d| d.name == "Polly")
+            .unwrap();
+        assert_eq!(polly.version_req.as_deref(), Some("8.2.0"));
+        let core = info
+            .dependencies
+            .iter()
+            .find(|d| d.name == "Billing.Core")
+            .unwrap();
+        assert!(core.version_req.is_none());
+    }
+
+    #[test]
+    fn test_parse_fsproj_multi_target() {
+        let dir = TempDir::new().unwrap();
+        let path = write_manifest(
+            dir.path(),
+            "Pricing.fsproj",
+            r#"<Project Sdk="Microsoft.NET.Sdk">
+  <PropertyGroup>
+    <TargetFrameworks>net6.0;net8.0</TargetFrameworks>
+    <VersionPrefix>0.4.0</VersionPrefix>
+    <PackageId>Acme.Pricing</PackageId>
+  </PropertyGroup>
+</Project>"#,
+        );
+
+        let info = FsprojParser.parse(&path, "lib/pricing").unwrap();
+        assert_eq!(info.name, "Pricing");
+        assert_eq!(info.version.as_deref(), Some("0.4.0"));
+        let meta = info.metadata.unwrap();
+        assert_eq!(
+            meta["target_frameworks"],
+            serde_json::json!(["net6.0", "net8.0"])
+        );
+        assert_eq!(meta["package_id"], "Acme.Pricing");
+        assert!(info.dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_project_name() {
+        assert_eq!(
+            project_name(r"..\Shared\Shared.Kernel.csproj").as_deref(),
+            Some("Shared.Kernel")
+        );
+        assert_eq!(project_name("src/App/App.fsproj").as_deref(), Some("App"));
+        assert_eq!(project_name("noextension"), None);
+    }
+
+    #[test]
+    fn test_malformed_xml_is_error() {
+        let dir = TempDir::new().unwrap();
+        let path = write_manifest(
+            dir.path(),
+            "Broken.csproj",
+            "<Project><PropertyGroup></Project>",
+        );
+        assert!(CsprojParser.parse(&path, "broken").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use anyhow::Result;
+use regex::Regex;
+use std::path::Path;
+
+/// Parsed result from a Visual Studio `.sln` file.
+#[derive(Debug)]
+pub struct Solution {
+    /// Solution name (the `.sln` file stem).
+    pub name: String,
+    /// Directories of member projects, relative to the solution's directory
+    /// (e.g., `src\Api\Api.csproj` → `src/Api`).
+    pub project_dirs: Vec<String>,
+}
+
+/// Parse a `.sln` file.
+pub fn parse_sln(path: &Path) -> Result<Solution> {
+    let content = std::fs::read_to_string(path)?;
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string();
+    Ok(parse_sln_content(&name, &content))
+}
+
+fn parse_sln_content(name: &str, content: &str) -> Solution {
+    // Project("{TYPE-GUID}") = "Api", "src\Api\Api.csproj", "{PROJECT-GUID}"
+    let project_re =
+        Regex::new(r#"(?m)^\s*Project\("\{[^}]+\}"\)\s*=\s*"[^"]*"\s*,\s*"([^"]+)""#).unwrap();
+
+    let mut project_dirs = Vec::new();
+    for cap in project_re.captures_iter(content) {
+        let project_path = cap.get(1).unwrap().as_str().replace('\\', "/");
+        // Solution folders reuse the Project(...) syntax but point at no file
+        if !project_path.ends_with("proj") {
+            continue;
+        }
+        let dir = project_path
+            .rsplit_once('/')
+            .map(|(dir, _)| dir.to_string())
+            .unwrap_or_default();
+        if !project_dirs.contains(&dir) {
+            project_dirs.push(dir);
+        }
+    }
+
+    Solution {
+        name: name.to_string(),
+        project_dirs,
+    }
+}
+
+/// Join a solution-relative directory onto the solution's own directory,
+/// resolving `.` and `..` components. Returns `None` if it escapes the repo root.
+pub fn resolve_project_dir(solution_dir: &str, project_dir: &str) -> Option<String> {
+    let mut parts: Vec<&str> = solution_dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in project_dir.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_projects_and_skip_solution_folders() {
+        let content = r#"
+Microsoft Visual Studio Solution File, Format Version 12.00
+# Visual Studio Version 17
+Project("{2150E333-8FDC-42A3-9474-1A3956D46DE8}") = "src", "src", "{11111111-1111-1111-1111-111111111111}"
+EndProject
+Project("{9A19103F-16F7-4668-BE54-9A1E7A4F7556}") = "Billing.Api", "src\Billing.Api\Billing.Api.csproj", "{22222222-2222-2222-2222-222222222222}"
+EndProject
+Project("{F2A71F9B-5D33-465A-A702-920D77279786}") = "Pricing", "lib\Pricing\Pricing.fsproj", "{33333333-3333-3333-3333-333333333333}"
+EndProject
+Global
+EndGlobal
+"#;
+        let sln = parse_sln_content("Billing", content);
+        assert_eq!(sln.name, "Billing");
+        assert_eq!(sln.project_dirs, vec!["src/Billing.Api", "lib/Pricing"]);
+    }
+
+    #[test]
+    fn test_project_next_to_solution() {
+        let content = r#"Project("{9A19103F-16F7-4668-BE54-9A1E7A4F7556}") = "Tool", "Tool.csproj", "{44444444-4444-4444-4444-444444444444}""#;
+        let sln = parse_sln_content("Tool", content);
+        assert_eq!(sln.project_dirs, vec![""]);
+    }
+
+    #[test]
+    fn test_resolve_project_dir() {
+        assert_eq!(
+            resolve_project_dir("dotnet", "src/Api").as_deref(),
+            Some("dotnet/src/Api")
+        );
+        assert_eq!(
+            resolve_project_dir("dotnet/app", "../lib/Core").as_deref(),
+            Some("dotnet/lib/Core")
+        );
+        assert_eq!(resolve_project_dir("", "./Tool").as_deref(), Some("Tool"));
+        assert_eq!(resolve_project_dir("", "../outside"), None);
+    }
+}
@@ -0,0 +1,503 @@
+use crate::db;
+use crate::db::pool::{file_id, indexed_at};
+use anyhow::{Context, Result, bail};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tempfile::TempDir;
+
+/// One repo's index in a federated server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    /// Results from this index are tagged with this name, and its paths are
+    /// prefixed with it.
+    pub name: String,
+    pub db_path: PathBuf,
+}
+
+impl Source {
+    /// Without a name, the repo is named after its directory: the recorded
+    /// repo root, or the directory holding `.shire/index.db`.
+    pub fn new(name: Option<String>, db_path: PathBuf) -> Self {
+        let name = name.unwrap_or_else(|| default_name(&db_path));
+        Self { name, db_path }
+    }
+
+    /// Parse a `--db` value: `PATH` or `NAME=PATH`.
+    pub fn parse(spec: &str) -> Self {
+        match spec.split_once('=') {
+            Some((name, path)) if !name.is_empty() && !name.contains('/') => {
+                Self::new(Some(name.to_string()), PathBuf::from(path))
+            }
+            _ => Self::new(None, PathBuf::from(spec)),
+        }
+    }
+}
+
+fn default_name(db_path: &Path) -> String {
+    let recorded_root = db::open_readonly(db_path).ok().and_then(|conn| {
+        conn.query_row(
+            "SELECT value FROM shire_meta WHERE key = 'repo_root'",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .ok()
+    });
+    let root = match recorded_root {
+        Some(root) => PathBuf::from(root),
+        None => {
+            let abs = std::fs::canonicalize(db_path).unwrap_or_else(|_| db_path.to_path_buf());
+            let dir = abs.parent().unwrap_or(Path::new("."));
+            match dir.file_name() {
+                Some(name) if name == ".shire" => dir.parent().unwrap_or(dir).to_path_buf(),
+                _ => dir.to_path_buf(),
+            }
+        }
+    };
+    root.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repo".to_string())
+}
+
+/// What a source index looked like when it was merged, to notice rebuilds.
+#[derive(Debug, PartialEq)]
+struct Stamp {
+    file_id: Option<(u64, u64)>,
+    indexed_at: Option<String>,
+}
+
+fn stamp(source: &Source) -> Result<Stamp> {
+    let conn = db::open_readonly(&source.db_path)
+        .with_context(|| format!("failed to open {}", source.db_path.display()))?;
+    Ok(Stamp {
+        file_id: file_id(&source.db_path),
+        indexed_at: indexed_at(&conn),
+    })
+}
+
+/// Several repos' indexes merged into one, so every query sees packages from
+/// all of them and dependencies between repos count as internal. The merged
+/// index is rebuilt whenever a source index is.
+///
+/// The merge lives in a fresh temp directory only this process can write to,
+/// so nothing another user created first is ever opened or replaced. It is
+/// removed by `close`, or when the federation is dropped.
+#[derive(Debug)]
+pub struct Federation {
+    sources: Vec<Source>,
+    dir: TempDir,
+    merged_path: PathBuf,
+    merged_from: Mutex<Vec<Stamp>>,
+}
+
+impl Federation {
+    pub fn open(sources: Vec<Source>) -> Result<Self> {
+        for (i, source) in sources.iter().enumerate() {
+            if source.name.is_empty() || source.name.contains('/') {
+                bail!("invalid repo name '{}' for a federated index", source.name);
+            }
+            if sources[..i].iter().any(|s| s.name == source.name) {
+                bail!(
+                    "two federated indexes are named '{}'; name them with NAME=PATH",
+                    source.name
+                );
+            }
+        }
+
+        for source in &sources {
+            if !source.db_path.is_file() {
+                bail!("index not found at {}", source.db_path.display());
+            }
+        }
+
+        // A random name, and owner-only permissions so no one else can add files to it
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("shire-federation-");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(std::fs::Permissions::from_mode(0o700));
+        }
+        let dir = builder
+            .tempdir()
+            .context("failed to create a directory for the merged index")?;
+        let merged_path = dir.path().join("index.db");
+
+        let federation = Self {
+            sources,
+            dir,
+            merged_path,
+            merged_from: Mutex::new(Vec::new()),
+        };
+        federation.sync()?;
+        Ok(federation)
+    }
+
+    pub fn merged_path(&self) -> &Path {
+        &self.merged_path
+    }
+
+    /// Delete the merged index. Servers call this on shutdown, since sessions
+    /// still holding the federation may never be dropped.
+    pub fn close(&self) {
+        let _ = std::fs::remove_dir_all(self.dir.path());
+    }
+
+    /// Re-merge if any source index was rebuilt since the last merge.
+    /// Returns whether it did.
+    pub fn sync(&self) -> Result<bool> {
+        let mut merged_from = self
+            .merged_from
+            .lock()
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        let current = match self.sources.iter().map(stamp).collect::<Result<Vec<_>>>() {
+            Ok(current) => current,
+            // A source is missing while being replaced; keep the last merge
+            Err(_) if !merged_from.is_empty() => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        if *merged_from == current {
+            return Ok(false);
+        }
+        merge(&self.sources, &self.merged_path)?;
+        *merged_from = current;
+        Ok(true)
+    }
+}
+
+/// Prefix a repo-relative path with the repo name (`""` is the repo root).
+const PREFIXED: &str = "CASE WHEN {col} = '' THEN ?1 ELSE ?1 || '/' || {col} END";
+
+fn prefixed(col: &str) -> String {
+    PREFIXED.replace("{col}", col)
+}
+
+/// Write the merged index next to `out` and move it into place, so readers
+/// see either the old merge or the new one. `out`'s directory is private to
+/// this process, and merges are serialized by `Federation::sync`.
+fn merge(sources: &[Source], out: &Path) -> Result<()> {
+    let tmp = out.with_extension("db.tmp");
+    let _ = std::fs::remove_file(&tmp);
+    {
+        let conn = db::open_or_create(&tmp)?;
+        conn.execute_batch(
+            "PRAGMA journal_mode=DELETE;
+             CREATE TEMP TABLE owned (name TEXT PRIMARY KEY);",
+        )?;
+        for source in sources {
+            merge_source(&conn, source).with_context(|| {
+                format!(
+                    "failed to merge the '{}' index ({}); rebuild it with this version of shire",
+                    source.name,
+                    source.db_path.display()
+                )
+            })?;
+        }
+        super::recompute_is_internal(&conn)?;
+        store_metadata(&conn)?;
+    }
+    std::fs::rename(&tmp, out)
+        .with_context(|| format!("failed to move the merged index to {}", out.display()))?;
+    Ok(())
+}
+
+fn merge_source(conn: &Connection, source: &Source) -> Result<()> {
+    conn.execute(
+        "ATTACH DATABASE ?1 AS src",
+        [source.db_path.to_string_lossy()],
+    )?;
+    let result = copy_source(conn, source);
+    conn.execute_batch("DETACH DATABASE src")?;
+    let shadowed = result?;
+    if !shadowed.is_empty() {
+        eprintln!(
+            "Warning: package(s) {} in '{}' are already indexed from another repo; keeping the first",
+            shadowed.join(", "),
+            source.name
+        );
+    }
+    Ok(())
+}
+
+/// Copy one attached index into the merged one. Packages whose name an earlier
+/// repo already claimed are skipped and returned.
+fn copy_source(conn: &Connection, source: &Source) -> Result<Vec<String>> {
+    let repo = source.name.as_str();
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        "DELETE FROM temp.owned;
+         INSERT INTO temp.owned
+             SELECT name FROM src.packages WHERE name NOT IN (SELECT name FROM main.packages);",
+    )?;
+    let shadowed = {
+        let mut stmt = tx.prepare(
+            "SELECT name FROM src.packages WHERE name NOT IN (SELECT name FROM temp.owned) ORDER BY name",
+        )?;
+        stmt.query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?
+    };
+
+    let statements = [
+        format!(
+            "INSERT INTO packages (name, path, kind, version, description, metadata)
+             SELECT name, {}, kind, version, description, metadata
+             FROM src.packages WHERE name IN (SELECT name FROM temp.owned)",
+            prefixed("path")
+        ),
+        "INSERT INTO package_tags (package, kind, name, value)
+         SELECT package, kind, name, value FROM src.package_tags
+         WHERE package IN (SELECT name FROM temp.owned)"
+            .to_string(),
+        "INSERT INTO dependencies (package, dependency, dep_kind, version_req, is_internal)
+         SELECT package, dependency, dep_kind, version_req, 0 FROM src.dependencies
+         WHERE package IN (SELECT name FROM temp.owned)"
+            .to_string(),
+        format!(
+            "INSERT INTO resolved_dependencies (package, dependency, version, is_direct, lockfile)
+             SELECT package, dependency, version, is_direct, {} FROM src.resolved_dependencies
+             WHERE package IN (SELECT name FROM temp.owned)",
+            prefixed("lockfile")
+        ),
+        "INSERT INTO package_history (package, commit_count, last_commit_at, top_authors)
+         SELECT package, commit_count, last_commit_at, top_authors FROM src.package_history
+         WHERE package IN (SELECT name FROM temp.owned)"
+            .to_string(),
+        format!(
+            "INSERT INTO files (path, package, extension, size_bytes)
+             SELECT {}, CASE WHEN package IN (SELECT name FROM temp.owned) THEN package END,
+                    extension, size_bytes
+             FROM src.files",
+            prefixed("path")
+        ),
+        format!(
+            "INSERT INTO file_owners (path, owner) SELECT {}, owner FROM src.file_owners",
+            prefixed("path")
+        ),
+        format!(
+            "INSERT INTO file_history (path, commit_count, last_commit_at, top_authors)
+             SELECT {}, commit_count, last_commit_at, top_authors FROM src.file_history",
+            prefixed("path")
+        ),
+        format!(
+            "INSERT INTO symbols (package, name, kind, signature, file_path, line,
+                                  visibility, parent_symbol, return_type, parameters, name_words)
+             SELECT package, name, kind, signature, {}, line,
+                    visibility, parent_symbol, return_type, parameters, name_words
+             FROM src.symbols WHERE package IN (SELECT name FROM temp.owned)",
+            prefixed("file_path")
+        ),
+    ];
+    for sql in &statements {
+        let params: &[&dyn rusqlite::ToSql] = if sql.contains("?1") { &[&repo] } else { &[] };
+        tx.execute(sql, params)?;
+    }
+
+    let meta = |key: &str| -> Result<Option<String>> {
+        let mut stmt = tx.prepare("SELECT value FROM src.shire_meta WHERE key = ?1")?;
+        let mut rows = stmt.query_map([key], |row| row.get::<_, String>(0))?;
+        Ok(rows.next().transpose()?)
+    };
+    tx.execute(
+        "INSERT INTO federated_repos (name, db_path, repo_root, indexed_at, git_commit)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            repo,
+            source.db_path.to_string_lossy(),
+            meta("repo_root")?,
+            meta("indexed_at")?,
+            meta("git_commit")?
+        ],
+    )?;
+    tx.execute(
+        "INSERT INTO package_repos (package, repo) SELECT name, ?1 FROM temp.owned",
+        [repo],
+    )?;
+    if let Some(days) = meta("history_window_days")? {
+        tx.execute(
+            "INSERT OR IGNORE INTO shire_meta (key, value) VALUES ('history_window_days', ?1)",
+            [days],
+        )?;
+    }
+    tx.commit()?;
+    Ok(shadowed)
+}
+
+/// Counts for the merged index, and the oldest source's build time as its
+/// `indexed_at`: the merge is only as fresh as its stalest repo.
+fn store_metadata(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "INSERT OR REPLACE INTO shire_meta (key, value)
+             SELECT 'indexed_at', MIN(indexed_at) FROM federated_repos;
+         INSERT OR REPLACE INTO shire_meta (key, value)
+             SELECT 'package_count', COUNT(*) FROM packages;
+         INSERT OR REPLACE INTO shire_meta (key, value)
+             SELECT 'symbol_count', COUNT(*) FROM symbols;
+         INSERT OR REPLACE INTO shire_meta (key, value)
+             SELECT 'file_count', COUNT(*) FROM files;",
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queries::{self, Page};
+
+    /// A minimal index for one repo: packages with their internal flags as a
+    /// single-repo build would have left them.
+    fn repo_index(dir: &Path, repo: &str, sql: &str) -> PathBuf {
+        let db_path = dir.join(repo).join(".shire/index.db");
+        let conn = db::open_or_create(&db_path).unwrap();
+        conn.execute_batch(sql).unwrap();
+        conn.execute(
+            "INSERT INTO shire_meta (key, value) VALUES ('indexed_at', '2026-01-01T00:00:00Z'), ('repo_root', ?1)",
+            [dir.join(repo).to_str().unwrap()],
+        )
+        .unwrap();
+        db_path
+    }
+
+    fn fixture(dir: &Path) -> Vec<Source> {
+        let web = repo_index(
+            dir,
+            "web",
+            "INSERT INTO packages (name, path, kind) VALUES ('web-app', 'apps/web', 'npm');
+             INSERT INTO dependencies (package, dependency, dep_kind, is_internal)
+                 VALUES ('web-app', '@acme/auth', 'runtime', 0), ('web-app', 'react', 'runtime', 0);
+             INSERT INTO files (path, package, extension) VALUES ('apps/web/index.ts', 'web-app', 'ts');
+             INSERT INTO symbols (package, name, kind, file_path, line)
+                 VALUES ('web-app', 'render', 'function', 'apps/web/index.ts', 3);",
+        );
+        let auth = repo_index(
+            dir,
+            "auth",
+            "INSERT INTO packages (name, path, kind) VALUES ('@acme/auth', '', 'npm'), ('@acme/crypto', 'crypto', 'npm');
+             INSERT INTO dependencies (package, dependency, dep_kind, is_internal)
+                 VALUES ('@acme/auth', '@acme/crypto', 'runtime', 1);
+             INSERT INTO files (path, package, extension) VALUES ('index.ts', '@acme/auth', 'ts');",
+        );
+        vec![Source::new(None, web), Source::new(None, auth)]
+    }
+
+    #[test]
+    fn test_merge_tags_repos_and_resolves_cross_repo_deps() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let federation = Federation::open(fixture(dir.path())).unwrap();
+        let conn = db::open_readonly(federation.merged_path()).unwrap();
+
+        let auth = queries::get_package(&conn, "@acme/auth").unwrap().unwrap();
+        assert_eq!(auth.repo.as_deref(), Some("auth"));
+        assert_eq!(
+            auth.path, "auth",
+            "the repo root package gets the repo's name as path"
+        );
+        let web = queries::get_package(&conn, "web-app").unwrap().unwrap();
+        assert_eq!(web.repo.as_deref(), Some("web"));
+        // Repos are kept apart from the `[[packages]]` overrides in package_tags
+        let tag_rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM package_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tag_rows, 0);
+        assert_eq!(web.path, "web/apps/web");
+
+        // web depends on a package published from the auth repo
+        let deps = queries::package_dependencies(&conn, "web-app", true, Page::ALL).unwrap();
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].dependency, "@acme/auth");
+        let graph = queries::dependency_graph(&conn, "web-app", 10, true, Page::ALL).unwrap();
+        assert!(
+            graph
+                .iter()
+                .any(|e| e.from == "@acme/auth" && e.to == "@acme/crypto")
+        );
+
+        let symbols = queries::get_file_symbols(&conn, "web/apps/web/index.ts", None, Page::ALL).unwrap();
+        assert_eq!(symbols[0].name, "render");
+        let status = queries::index_status(&conn).unwrap();
+        assert_eq!(status.package_count.as_deref(), Some("3"));
+        assert_eq!(status.repos.len(), 2);
+    }
+
+    #[test]
+    fn test_merged_index_is_private_and_removed_on_close() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let federation = Federation::open(fixture(dir.path())).unwrap();
+        let merged = federation.merged_path().to_path_buf();
+        assert!(merged.is_file());
+
+        // Never directly in the shared temp dir, where other users can create files
+        let private_dir = merged.parent().unwrap();
+        assert_ne!(private_dir, std::env::temp_dir());
+        assert_ne!(private_dir, std::env::temp_dir().canonicalize().unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(private_dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0, "only the owner may use the merge directory");
+        }
+
+        federation.close();
+        assert!(!merged.exists());
+        assert!(!private_dir.exists());
+    }
+
+    #[test]
+    fn test_sync_remerges_after_a_source_rebuild() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let sources = fixture(dir.path());
+        let federation = Federation::open(sources.clone()).unwrap();
+        assert!(!federation.sync().unwrap(), "nothing changed");
+
+        let conn = db::open_or_create(&sources[1].db_path).unwrap();
+        conn.execute_batch(
+            "INSERT INTO packages (name, path, kind) VALUES ('@acme/tokens', 'tokens', 'npm');
+             UPDATE shire_meta SET value = '2026-01-02T00:00:00Z' WHERE key = 'indexed_at';",
+        )
+        .unwrap();
+        assert!(federation.sync().unwrap());
+        let merged = db::open_readonly(federation.merged_path()).unwrap();
+        assert!(
+            queries::get_package(&merged, "@acme/tokens")
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_shadowed_packages_keep_the_first_repo() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut sources = fixture(dir.path());
+        let fork = repo_index(
+            dir.path(),
+            "fork",
+            "INSERT INTO packages (name, path, kind) VALUES ('@acme/auth', 'vendor/auth', 'npm');
+             INSERT INTO symbols (package, name, kind, file_path, line)
+                 VALUES ('@acme/auth', 'login', 'function', 'vendor/auth/index.ts', 1);",
+        );
+        sources.push(Source::new(None, fork));
+        let federation = Federation::open(sources).unwrap();
+        let conn = db::open_readonly(federation.merged_path()).unwrap();
+        let auth = queries::get_package(&conn, "@acme/auth").unwrap().unwrap();
+        assert_eq!(auth.repo.as_deref(), Some("auth"));
+        assert!(
+            queries::get_symbol(&conn, "login", None, Page::ALL)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_source_names() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let sources = fixture(dir.path());
+        assert_eq!(sources[0].name, "web");
+
+        let named = Source::parse(&format!("billing={}", sources[0].db_path.display()));
+        assert_eq!(named.name, "billing");
+        assert_eq!(named.db_path, sources[0].db_path);
+
+        let duplicate = vec![sources[0].clone(), sources[0].clone()];
+        let err = Federation::open(duplicate).unwrap_err();
+        assert!(err.to_string().contains("named 'web'"));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::db::queries::AuthorCount;
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+
+/// One non-merge commit from `git log`: author time, author name and the
+/// repo-relative paths it touched.
+#[derive(Debug)]
+pub struct Commit {
+    pub timestamp: i64,
+    pub author: String,
+    pub paths: Vec<String>,
+}
+
+/// Commit activity for one file or package.
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// Commits inside the window.
+    pub commit_count: usize,
+    /// Most recent commit at any time, as a Unix timestamp.
+    pub last_commit: Option<i64>,
+    /// Commits per author inside the window.
+    authors: HashMap<String, usize>,
+}
+
+impl Stats {
+    fn record(&mut self, commit: &Commit, in_window: bool) {
+        self.last_commit = Some(
+            self.last_commit
+                .map_or(commit.timestamp, |t| t.max(commit.timestamp)),
+        );
+        if in_window {
+            self.commit_count += 1;
+            *self.authors.entry(commit.author.clone()).or_default() += 1;
+        }
+    }
+
+    /// The `n` authors with the most commits in the window, ties broken by name.
+    pub fn top_authors(&self, n: usize) -> Vec<AuthorCount> {
+        let mut authors: Vec<AuthorCount> = self
+            .authors
+            .iter()
+            .map(|(name, commits)| AuthorCount {
+                name: name.clone(),
+                commits: *commits,
+            })
+            .collect();
+        authors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+        authors.truncate(n);
+        authors
+    }
+}
+
+/// The current HEAD commit, or `None` outside a git repository.
+pub fn head(repo_root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(repo_root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// Run `git log` over the whole history reachable from HEAD, with paths made
+/// relative to `repo_root` (which may be a subdirectory of the git work tree).
+pub fn read_log(repo_root: &Path) -> Result<Vec<Commit>> {
+    let output = Command::new("git")
+        .args([
+            "-c",
+            "core.quotePath=false",
+            "log",
+            "--relative",
+            "--no-renames",
+            "--name-only",
+            "--format=%x1e%ct%x1f%aN",
+        ])
+        .current_dir(repo_root)
+        .output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git log --name-only --format=%x1e%ct%x1f%aN` output.
+pub fn parse_log(output: &str) -> Vec<Commit> {
+    let mut commits = Vec::new();
+    for record in output.split('\x1e') {
+        let mut lines = record.lines();
+        let Some((timestamp, author)) = lines.next().and_then(|header| header.split_once('\x1f'))
+        else {
+            continue;
+        };
+        let Ok(timestamp) = timestamp.trim().parse() else {
+            continue;
+        };
+        let paths: Vec<String> = lines
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect();
+        if paths.is_empty() {
+            // Merge commits list no files and carry no churn of their own
+            continue;
+        }
+        commits.push(Commit {
+            timestamp,
+            author: author.to_string(),
+            paths,
+        });
+    }
+    commits
+}
+
+/// Aggregate commits per indexed file and per package. A commit counts once for
+/// each package it touches, attributing every path (including since-deleted
+/// ones) to the deepest package whose directory contains it. Commits older than
+/// `window_start` only contribute to the last-commit date.
+pub fn aggregate(
+    commits: &[Commit],
+    files: &HashSet<String>,
+    packages: &[(String, String)],
+    window_start: i64,
+) -> (HashMap<String, Stats>, HashMap<String, Stats>) {
+    // Same attribution as the files table; it only compares paths, so
+    // since-deleted files resolve too
+    let sorted_pkgs = super::sort_packages_by_path_len(packages);
+    let package_of = |file: &str| super::owning_package(file, &sorted_pkgs);
+
+    let mut file_stats: HashMap<String, Stats> = HashMap::new();
+    let mut package_stats: HashMap<String, Stats> = HashMap::new();
+    for commit in commits {
+        let in_window = commit.timestamp >= window_start;
+        let mut touched: HashSet<&str> = HashSet::new();
+        for path in &commit.paths {
+            if files.contains(path) {
+                file_stats
+                    .entry(path.clone())
+                    .or_default()
+                    .record(commit, in_window);
+            }
+            if let Some(package) = package_of(path) {
+                touched.insert(package);
+            }
+        }
+        for package in touched {
+            package_stats
+                .entry(package.to_string())
+                .or_default()
+                .record(commit, in_window);
+        }
+    }
+    (file_stats, package_stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\x1e300\x1fAda\n\nservices/auth/src/index.ts\nservices/auth/package.json\n\
+                       \x1e200\x1fGrace\n\nservices/auth/src/index.ts\nlibs/core/lib.rs\n\
+                       \x1e150\x1fAda\n\
+                       \x1e100\x1fAda\n\nlibs/core/old.rs\nREADME.md\n";
+
+    #[test]
+    fn test_parse_log() {
+        let commits = parse_log(LOG);
+        assert_eq!(commits.len(), 3, "merge commit without paths is skipped");
+        assert_eq!(commits[0].timestamp, 300);
+        assert_eq!(commits[0].author, "Ada");
+        assert_eq!(
+            commits[0].paths,
+            vec!["services/auth/src/index.ts", "services/auth/package.json"]
+        );
+        assert_eq!(commits[2].paths, vec!["libs/core/old.rs", "README.md"]);
+    }
+
+    #[test]
+    fn test_aggregate_window_and_packages() {
+        let commits = parse_log(LOG);
+        let files: HashSet<String> = ["services/auth/src/index.ts", "libs/core/lib.rs"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let packages = vec![
+            ("auth".to_string(), "services/auth".to_string()),
+            ("core".to_string(), "libs/core".to_string()),
+        ];
+        let (file_stats, package_stats) = aggregate(&commits, &files, &packages, 150);
+
+        let index = &file_stats["services/auth/src/index.ts"];
+        assert_eq!(index.commit_count, 2);
+        assert_eq!(index.last_commit, Some(300));
+        // Deleted files don't get rows, but still count towards their package
+        assert!(!file_stats.contains_key("libs/core/old.rs"));
+
+        let auth = &package_stats["auth"];
+        assert_eq!(auth.commit_count, 2, "one count per commit, not per file");
+        let top = auth.top_authors(1);
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].name, "Ada");
+
+        // The commit at t=100 falls outside the window and is older than the last commit
+        let core = &package_stats["core"];
+        assert_eq!(core.commit_count, 1);
+        assert_eq!(core.last_commit, Some(200));
+        assert_eq!(core.top_authors(3)[0].name, "Grace");
+    }
+}
@@ -0,0 +1,455 @@
+use super::{
+    BuildSummary, codeowners, hash, owning_package, sort_packages_by_path_len, with_transaction,
+};
+use crate::config::Config;
+use crate::db;
+use crate::symbols;
+use anyhow::Result;
+use ignore::WalkBuilder;
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Files that change what a full build would discover or how it walks, so a
+/// change to any of them needs `build_index`.
+const BUILD_INPUTS: &[&str] = &["shire.toml", ".gitignore", ".ignore", "CODEOWNERS"];
+
+#[derive(Debug, Default)]
+pub struct UpdateSummary {
+    /// Files added to or refreshed in the `files` table.
+    pub files_updated: usize,
+    /// Files that no longer exist (or are no longer indexed) and were removed.
+    pub files_removed: usize,
+    /// Symbols extracted from the changed files.
+    pub symbols: usize,
+    pub duration: Duration,
+}
+
+/// Fast path for a known list of changed paths: update just their `files`
+/// rows and re-extract symbols from just those files, without walking the repo
+/// or rehashing packages.
+///
+/// Returns `Ok(None)` when the change needs a full `build_index` instead: a
+/// manifest, lockfile, config, ignore or CODEOWNERS file changed, a directory
+/// appeared or disappeared, or there is no index yet.
+pub fn update_files(
+    repo_root: &Path,
+    config: &Config,
+    db_override: Option<&Path>,
+    changed: &[PathBuf],
+) -> Result<Option<UpdateSummary>> {
+    let start = Instant::now();
+    let db_path = super::resolve_db_path(repo_root, config, db_override);
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let conn = db::open_or_create(&db_path)?;
+    let indexed: Option<String> = conn
+        .query_row(
+            "SELECT value FROM shire_meta WHERE key = 'indexed_at'",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if indexed.is_none() {
+        return Ok(None);
+    }
+
+    // Repo-relative paths; anything outside the repo is ignored
+    let relative: BTreeSet<String> = changed
+        .iter()
+        .filter_map(|p| {
+            let abs = if p.is_absolute() {
+                p.clone()
+            } else {
+                repo_root.join(p)
+            };
+            let rel = abs
+                .strip_prefix(repo_root)
+                .ok()?
+                .to_string_lossy()
+                .to_string();
+            (!rel.is_empty()).then_some(rel)
+        })
+        .collect();
+    if relative.is_empty() {
+        return Ok(None);
+    }
+    for rel in &relative {
+        if needs_full_build(&conn, repo_root, config, rel)? {
+            return Ok(None);
+        }
+    }
+
+    let mut summary = UpdateSummary::default();
+    with_transaction(&conn, || {
+        let packages: Vec<(String, String)> = conn
+            .prepare("SELECT name, path FROM packages")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        let sorted_pkgs = sort_packages_by_path_len(&packages);
+        let codeowners = codeowners::load(repo_root)?;
+
+        for rel in &relative {
+            let abs = repo_root.join(rel);
+            let known: bool = conn
+                .query_row("SELECT 1 FROM files WHERE path = ?1", [rel], |_| Ok(true))
+                .optional()?
+                .unwrap_or(false);
+            let indexed = abs.is_file() && (known || is_walked(&conn, repo_root, config, rel)?);
+
+            conn.execute("DELETE FROM file_owners WHERE path = ?1", [rel])?;
+            if indexed {
+                let size = abs.metadata().map(|m| m.len()).unwrap_or(0) as i64;
+                let extension = abs
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_lowercase())
+                    .unwrap_or_default();
+                let package = owning_package(rel, &sorted_pkgs);
+                conn.execute(
+                    "INSERT INTO files (path, package, extension, size_bytes) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(path) DO UPDATE SET
+                         package = excluded.package,
+                         extension = excluded.extension,
+                         size_bytes = excluded.size_bytes",
+                    (rel, package, &extension, size),
+                )?;
+                if let Some(owners) = &codeowners {
+                    for owner in owners.owners_of(rel) {
+                        conn.execute(
+                            "INSERT OR IGNORE INTO file_owners (path, owner) VALUES (?1, ?2)",
+                            (rel, owner),
+                        )?;
+                    }
+                }
+                summary.files_updated += 1;
+            } else if known {
+                conn.execute("DELETE FROM files WHERE path = ?1", [rel])?;
+                summary.files_removed += 1;
+            }
+
+            // Package symbol extraction walks the whole package directory, so a
+            // file's symbols belong to every package whose directory contains it
+            for (name, path) in &packages {
+                if !path.is_empty() && !rel.starts_with(&format!("{path}/")) {
+                    continue;
+                }
+                conn.execute(
+                    "DELETE FROM symbols WHERE package = ?1 AND file_path = ?2",
+                    (name, rel),
+                )?;
+                if abs.is_file() {
+                    let syms = symbols::extract_symbols_for_file(
+                        repo_root,
+                        path,
+                        &abs,
+                        &config.symbols.exclude_extensions,
+                    );
+                    summary.symbols += syms.len();
+                    super::insert_symbols(&conn, name, &syms)?;
+                }
+            }
+        }
+
+        // Keep the file-tree hash in step with the table so the next full build
+        // can still skip Phase 9
+        let mut file_tuples: Vec<(String, u64)> = conn
+            .prepare("SELECT path, size_bytes FROM files")?
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+            .collect::<Result<_, _>>()?;
+        if let Some(owners) = &codeowners {
+            file_tuples.push((format!("{}#{}", owners.source, owners.content_hash), 0));
+        }
+        conn.execute(
+            "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('file_tree_hash', ?1)",
+            [hash::compute_file_tree_hash(&file_tuples)],
+        )?;
+
+        let count = |sql: &str| conn.query_row(sql, [], |row| row.get::<_, i64>(0));
+        let build_summary = BuildSummary {
+            num_added: 0,
+            num_changed: 0,
+            num_removed: 0,
+            num_skipped: 0,
+            num_source_reextracted: 0,
+            num_files: count("SELECT COUNT(*) FROM files")? as usize,
+            total_packages: count("SELECT COUNT(*) FROM packages")?,
+            total_symbols: count("SELECT COUNT(*) FROM symbols")?,
+            failures: Vec::new(),
+        };
+        super::store_metadata(&conn, repo_root, &build_summary)
+    })?;
+
+    summary.duration = start.elapsed();
+    Ok(Some(summary))
+}
+
+/// Whether a change to `rel` can't be applied file-by-file.
+fn needs_full_build(
+    conn: &Connection,
+    repo_root: &Path,
+    config: &Config,
+    rel: &str,
+) -> Result<bool> {
+    let abs = repo_root.join(rel);
+    let filename = rel.rsplit('/').next().unwrap_or(rel);
+
+    if BUILD_INPUTS.contains(&filename) || codeowners::CODEOWNERS_PATHS.contains(&rel) {
+        return Ok(true);
+    }
+    if config
+        .discovery
+        .manifests
+        .iter()
+        .any(|p| super::manifest::filename_matches(p, filename))
+    {
+        return Ok(true);
+    }
+    // A new file may complete a custom discovery rule and create a package
+    if config
+        .discovery
+        .custom
+        .iter()
+        .flat_map(|rule| &rule.requires)
+        .any(|p| super::manifest::filename_matches(p, filename))
+    {
+        return Ok(true);
+    }
+    if abs.is_dir() {
+        return Ok(true);
+    }
+    // A deleted directory shows up as a path with indexed files below it
+    if !abs.exists() {
+        let escaped = rel
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let was_dir: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM files WHERE path LIKE ?1 ESCAPE '\\')",
+            [format!("{escaped}/%")],
+            |row| row.get(0),
+        )?;
+        return Ok(was_dir);
+    }
+    Ok(false)
+}
+
+/// Whether the full build's file walk would index a file that isn't in the
+/// `files` table yet. Only files in directories that already hold indexed
+/// files are answered here; anything else is treated as not indexed, since a
+/// brand-new directory arrives as a directory change and triggers a full build.
+fn is_walked(conn: &Connection, repo_root: &Path, config: &Config, rel: &str) -> Result<bool> {
+    let exclude: HashSet<&str> = config
+        .discovery
+        .exclude
+        .iter()
+        .map(|s| s.as_str())
+        .collect();
+    let components: Vec<&str> = rel.split('/').collect();
+    let (filename, dirs) = components
+        .split_last()
+        .expect("split yields at least one item");
+    if filename.starts_with('.')
+        || dirs
+            .iter()
+            .any(|d| d.starts_with('.') || exclude.contains(d))
+    {
+        return Ok(false);
+    }
+
+    let parent = rel.rsplit_once('/').map(|(dir, _)| dir);
+    if let Some(parent) = parent {
+        let escaped = parent
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let has_files: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM files WHERE path LIKE ?1 ESCAPE '\\')",
+            [format!("{escaped}/%")],
+            |row| row.get(0),
+        )?;
+        if !has_files {
+            return Ok(false);
+        }
+    }
+
+    // Let the walker apply .gitignore rules to the file's own directory
+    let dir = parent.map_or_else(|| repo_root.to_path_buf(), |p| repo_root.join(p));
+    let abs = repo_root.join(rel);
+    let walked = WalkBuilder::new(&dir)
+        .hidden(true)
+        .max_depth(Some(1))
+        .build()
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.path() == abs);
+    Ok(walked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::build_index;
+    use std::fs;
+
+    fn build_fixture(dir: &Path) -> PathBuf {
+        // .gitignore rules only apply inside a git repository
+        let init = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(init.status.success());
+        let auth = dir.join("services/auth/src");
+        fs::create_dir_all(&auth).unwrap();
+        fs::write(
+            dir.join("services/auth/package.json"),
+            r#"{"name": "auth-service", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+        fs::write(auth.join("index.ts"), "export function login() {}\n").unwrap();
+        fs::write(dir.join(".gitignore"), "*.log\n").unwrap();
+        build_index(dir, &Config::default(), false, None).unwrap();
+        dir.join(".shire/index.db")
+    }
+
+    fn symbol_names(conn: &Connection, file: &str) -> Vec<String> {
+        conn.prepare("SELECT name FROM symbols WHERE file_path = ?1 ORDER BY name")
+            .unwrap()
+            .query_map([file], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_update_changed_source_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = build_fixture(dir.path());
+
+        let index = dir.path().join("services/auth/src/index.ts");
+        fs::write(
+            &index,
+            "export function login() {}\nexport function logout() {}\n",
+        )
+        .unwrap();
+        let added = dir.path().join("services/auth/src/token.ts");
+        fs::write(&added, "export class Token {}\n").unwrap();
+
+        let summary = update_files(dir.path(), &Config::default(), None, &[index, added])
+            .unwrap()
+            .expect("source-only change takes the fast path");
+        assert_eq!(summary.files_updated, 2);
+        assert_eq!(summary.symbols, 3);
+
+        let conn = db::open_readonly(&db_path).unwrap();
+        assert_eq!(
+            symbol_names(&conn, "services/auth/src/index.ts"),
+            vec!["login", "logout"]
+        );
+        assert_eq!(
+            symbol_names(&conn, "services/auth/src/token.ts"),
+            vec!["Token"]
+        );
+        let package: Option<String> = conn
+            .query_row(
+                "SELECT package FROM files WHERE path = 'services/auth/src/token.ts'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(package.as_deref(), Some("auth-service"));
+        let symbol_count: String = conn
+            .query_row(
+                "SELECT value FROM shire_meta WHERE key = 'symbol_count'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(symbol_count, "3");
+    }
+
+    #[test]
+    fn test_update_matches_full_build() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = build_fixture(dir.path());
+
+        let index = dir.path().join("services/auth/src/index.ts");
+        fs::remove_file(&index).unwrap();
+        let ignored = dir.path().join("services/auth/src/debug.log");
+        fs::write(&ignored, "noise").unwrap();
+        let summary = update_files(dir.path(), &Config::default(), None, &[index, ignored])
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.files_removed, 1);
+        assert_eq!(summary.files_updated, 0, "gitignored file is not indexed");
+
+        let conn = db::open_readonly(&db_path).unwrap();
+        assert!(symbol_names(&conn, "services/auth/src/index.ts").is_empty());
+        let tree_hash = |conn: &Connection| -> String {
+            conn.query_row(
+                "SELECT value FROM shire_meta WHERE key = 'file_tree_hash'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        let fast = tree_hash(&conn);
+        drop(conn);
+
+        // A full build agrees with the fast path's files table
+        build_index(dir.path(), &Config::default(), false, None).unwrap();
+        let conn = db::open_readonly(&db_path).unwrap();
+        assert_eq!(tree_hash(&conn), fast);
+    }
+
+    #[test]
+    fn test_manifest_or_directory_change_needs_full_build() {
+        let dir = tempfile::TempDir::new().unwrap();
+        build_fixture(dir.path());
+        let config = Config::default();
+
+        let manifest = dir.path().join("services/auth/package.json");
+        assert!(
+            update_files(dir.path(), &config, None, &[manifest])
+                .unwrap()
+                .is_none()
+        );
+
+        let gitignore = dir.path().join(".gitignore");
+        assert!(
+            update_files(dir.path(), &config, None, &[gitignore])
+                .unwrap()
+                .is_none()
+        );
+
+        let new_dir = dir.path().join("services/billing");
+        fs::create_dir_all(&new_dir).unwrap();
+        assert!(
+            update_files(dir.path(), &config, None, &[new_dir])
+                .unwrap()
+                .is_none()
+        );
+
+        fs::remove_dir_all(dir.path().join("services/auth/src")).unwrap();
+        let removed_dir = dir.path().join("services/auth/src");
+        assert!(
+            update_files(dir.path(), &config, None, &[removed_dir])
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_no_index_needs_full_build() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("a.ts");
+        fs::write(&file, "export const a = 1;\n").unwrap();
+        assert!(
+            update_files(dir.path(), &Config::default(), None, &[file])
+                .unwrap()
+                .is_none()
+        );
+    }
+}
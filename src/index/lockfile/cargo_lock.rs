@@ -0,0 +1,169 @@
+use super::{Importer, LockGraph, LockNode, Lockfile, ResolvedDep};
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct CargoLockFile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+/// A parsed `Cargo.lock`. Crates without a `source` are workspace members or
+/// path dependencies.
+#[derive(Debug)]
+pub struct CargoLock {
+    graph: LockGraph,
+}
+
+pub fn parse(content: &str) -> Result<CargoLock> {
+    let lock: CargoLockFile = toml::from_str(content)?;
+
+    let mut ids_by_name: HashMap<&str, Vec<String>> = HashMap::new();
+    for pkg in &lock.package {
+        ids_by_name
+            .entry(pkg.name.as_str())
+            .or_default()
+            .push(node_id(&pkg.name, &pkg.version));
+    }
+
+    let mut graph = LockGraph::default();
+    for pkg in &lock.package {
+        let deps = pkg
+            .dependencies
+            .iter()
+            .filter_map(|d| dependency_id(d, &ids_by_name))
+            .collect();
+        graph.nodes.insert(
+            node_id(&pkg.name, &pkg.version),
+            LockNode {
+                name: pkg.name.clone(),
+                version: pkg.version.clone(),
+                deps,
+                local: pkg.source.is_none(),
+            },
+        );
+    }
+
+    Ok(CargoLock { graph })
+}
+
+fn node_id(name: &str, version: &str) -> String {
+    format!("{} {}", name, version)
+}
+
+/// Dependency entries are `name` when only one version is locked, otherwise
+/// `name version` or `name version (source)`.
+fn dependency_id(entry: &str, ids_by_name: &HashMap<&str, Vec<String>>) -> Option<String> {
+    let mut parts = entry.split_whitespace();
+    let name = parts.next()?;
+    match parts.next() {
+        Some(version) => Some(node_id(name, version)),
+        None => ids_by_name.get(name)?.first().cloned(),
+    }
+}
+
+impl Lockfile for CargoLock {
+    fn resolve(&self, importer: &Importer) -> Vec<ResolvedDep> {
+        let Some(member) = self
+            .graph
+            .nodes
+            .values()
+            .find(|n| n.local && n.name == importer.name)
+        else {
+            return Vec::new();
+        };
+        self.graph.closure(&member.deps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "core-lib",
+ "serde",
+ "syn 2.0.48",
+]
+
+[[package]]
+name = "core-lib"
+version = "0.1.0"
+dependencies = [
+ "anyhow",
+]
+
+[[package]]
+name = "anyhow"
+version = "1.0.79"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.196"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "syn 1.0.109",
+]
+
+[[package]]
+name = "syn"
+version = "1.0.109"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "2.0.48"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    #[test]
+    fn test_resolve_workspace_member() {
+        let lock = parse(LOCK).unwrap();
+        let deps = lock.resolve(&Importer {
+            name: "app",
+            rel_dir: "app",
+            declared: &[],
+        });
+        let summary: Vec<(&str, &str, bool)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("anyhow", "1.0.79", false),
+                ("serde", "1.0.196", true),
+                ("syn", "1.0.109", false),
+                ("syn", "2.0.48", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_member_resolves_nothing() {
+        let lock = parse(LOCK).unwrap();
+        let deps = lock.resolve(&Importer {
+            name: "serde",
+            rel_dir: "",
+            declared: &[],
+        });
+        assert!(deps.is_empty());
+    }
+}
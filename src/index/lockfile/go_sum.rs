@@ -0,0 +1,95 @@
+use super::{Importer, Lockfile, ResolvedDep, compare_versions};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+/// A parsed `go.sum`. It records checksums rather than a dependency graph, so
+/// every module whose source is checksummed counts as part of the build, at
+/// the highest version listed (what minimal version selection picks).
+#[derive(Debug)]
+pub struct GoSum {
+    modules: BTreeMap<String, String>,
+}
+
+pub fn parse(content: &str) -> GoSum {
+    let mut modules: BTreeMap<String, String> = BTreeMap::new();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(module), Some(version)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        // `v1.2.3/go.mod` lines only pin the module's go.mod for graph pruning
+        if version.ends_with("/go.mod") {
+            continue;
+        }
+        match modules.get(module) {
+            Some(current) if compare_versions(current, version) != Ordering::Less => {}
+            _ => {
+                modules.insert(module.to_string(), version.to_string());
+            }
+        }
+    }
+
+    GoSum { modules }
+}
+
+impl Lockfile for GoSum {
+    fn resolve(&self, importer: &Importer) -> Vec<ResolvedDep> {
+        let declared: HashSet<&str> = importer.declared.iter().map(|(n, _)| n.as_str()).collect();
+        self.modules
+            .iter()
+            .map(|(module, version)| ResolvedDep {
+                name: module.clone(),
+                version: version.clone(),
+                is_direct: declared.contains(module.as_str()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_go_sum_picks_highest_version() {
+        let sum = parse(
+            "github.com/google/uuid v1.3.0 h1:abc=\n\
+             github.com/google/uuid v1.3.0/go.mod h1:def=\n\
+             github.com/google/uuid v1.6.0 h1:ghi=\n\
+             github.com/google/uuid v1.6.0/go.mod h1:jkl=\n\
+             golang.org/x/sys v0.0.0-20220715151400-c0bba94af5f8/go.mod h1:mno=\n\
+             golang.org/x/net v0.10.0 h1:pqr=\n",
+        );
+        let declared = vec![(
+            "github.com/google/uuid".to_string(),
+            Some("v1.6.0".to_string()),
+        )];
+        let deps = sum.resolve(&Importer {
+            name: "github.com/acme/api",
+            rel_dir: "",
+            declared: &declared,
+        });
+        let summary: Vec<(&str, &str, bool)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("github.com/google/uuid", "v1.6.0", true),
+                ("golang.org/x/net", "v0.10.0", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("v1.10.0", "v1.9.0"), Ordering::Greater);
+        assert_eq!(compare_versions("v0.0.0-2023", "v0.1.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("v2.0.0+incompatible", "v1.9.9"),
+            Ordering::Greater
+        );
+    }
+}
@@ -0,0 +1,229 @@
+pub mod cargo_lock;
+pub mod go_sum;
+pub mod npm_lock;
+pub mod pnpm_lock;
+pub mod poetry_lock;
+pub mod yarn_lock;
+
+use anyhow::{Result, bail};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// Lockfile names and the package kind they resolve, in order of preference
+/// when a directory holds more than one lockfile for the same kind.
+pub const LOCKFILES: &[(&str, &str)] = &[
+    ("Cargo.lock", "cargo"),
+    ("pnpm-lock.yaml", "npm"),
+    ("package-lock.json", "npm"),
+    ("yarn.lock", "npm"),
+    ("go.sum", "go"),
+    ("poetry.lock", "python"),
+];
+
+/// The package kind a lockfile filename resolves, if it is a known lockfile.
+pub fn kind_for(filename: &str) -> Option<&'static str> {
+    LOCKFILES
+        .iter()
+        .find(|(name, _)| *name == filename)
+        .map(|(_, kind)| *kind)
+}
+
+/// Compare locked versions (`1.2.3`, `v0.0.0-20240101-abcdef`, `2.0.0+incompatible`,
+/// `1.0.0-beta.1`) by their numeric release parts, then a pre-release before
+/// its release, then lexically.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn release(v: &str) -> &str {
+        v.trim_start_matches('v').split('+').next().unwrap_or("")
+    }
+    fn numeric(v: &str) -> Vec<u64> {
+        release(v)
+            .split('-')
+            .next()
+            .unwrap_or("")
+            .split('.')
+            .map(|p| {
+                let digits = p.find(|c: char| !c.is_ascii_digit()).unwrap_or(p.len());
+                p[..digits].parse().unwrap_or(0)
+            })
+            .collect()
+    }
+    let is_release = |v: &str| !release(v).contains('-');
+    numeric(a)
+        .cmp(&numeric(b))
+        .then_with(|| is_release(a).cmp(&is_release(b)))
+        .then_with(|| a.cmp(b))
+}
+
+/// A dependency version pinned by a lockfile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedDep {
+    pub name: String,
+    pub version: String,
+    /// Declared by the package itself, rather than pulled in transitively.
+    pub is_direct: bool,
+}
+
+/// A package whose dependencies are looked up in a lockfile.
+pub struct Importer<'a> {
+    pub name: &'a str,
+    /// Package directory relative to the lockfile's directory (`""` when co-located).
+    pub rel_dir: &'a str,
+    /// Declared external dependencies as `(name, version_req)`.
+    pub declared: &'a [(String, Option<String>)],
+}
+
+/// A parsed lockfile that can resolve the locked dependency closure of a package.
+pub trait Lockfile {
+    fn resolve(&self, importer: &Importer) -> Vec<ResolvedDep>;
+}
+
+/// Parse a lockfile, dispatching on its filename.
+pub fn load(path: &Path) -> Result<Box<dyn Lockfile>> {
+    let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("");
+    let content = std::fs::read_to_string(path)?;
+    Ok(match filename {
+        "Cargo.lock" => Box::new(cargo_lock::parse(&content)?),
+        "pnpm-lock.yaml" => Box::new(pnpm_lock::parse(&content)?),
+        "package-lock.json" => Box::new(npm_lock::parse(&content)?),
+        "yarn.lock" => Box::new(yarn_lock::parse(&content)),
+        "go.sum" => Box::new(go_sum::parse(&content)),
+        "poetry.lock" => Box::new(poetry_lock::parse(&content)?),
+        _ => bail!("Unsupported lockfile: {}", filename),
+    })
+}
+
+/// A locked package in a dependency graph. `local` nodes (workspace members,
+/// path dependencies) are traversed but never reported as resolved deps.
+#[derive(Debug)]
+struct LockNode {
+    name: String,
+    version: String,
+    deps: Vec<String>,
+    local: bool,
+}
+
+/// Lock graph keyed by a format-specific node id.
+#[derive(Debug, Default)]
+struct LockGraph {
+    nodes: HashMap<String, LockNode>,
+}
+
+impl LockGraph {
+    /// Breadth-first closure from the direct dependency ids, deduplicated by
+    /// `(name, version)` and sorted by name.
+    fn closure(&self, direct: &[String]) -> Vec<ResolvedDep> {
+        let mut resolved = ResolvedSet::default();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<(&str, bool)> = VecDeque::new();
+        for id in direct {
+            queue.push_back((id.as_str(), true));
+        }
+
+        while let Some((id, is_direct)) = queue.pop_front() {
+            if !seen.insert(id) {
+                continue;
+            }
+            let Some(node) = self.nodes.get(id) else {
+                continue;
+            };
+            if !node.local {
+                resolved.insert(&node.name, &node.version, is_direct);
+            }
+            for dep in &node.deps {
+                queue.push_back((dep.as_str(), false));
+            }
+        }
+
+        resolved.into_vec()
+    }
+}
+
+/// Accumulates resolved deps; a dep reached both directly and transitively is direct.
+#[derive(Debug, Default)]
+struct ResolvedSet {
+    deps: BTreeMap<(String, String), bool>,
+}
+
+impl ResolvedSet {
+    fn insert(&mut self, name: &str, version: &str, is_direct: bool) {
+        let entry = self
+            .deps
+            .entry((name.to_string(), version.to_string()))
+            .or_insert(false);
+        *entry |= is_direct;
+    }
+
+    fn into_vec(self) -> Vec<ResolvedDep> {
+        self.deps
+            .into_iter()
+            .map(|((name, version), is_direct)| ResolvedDep {
+                name,
+                version,
+                is_direct,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, version: &str, deps: &[&str], local: bool) -> LockNode {
+        LockNode {
+            name: name.to_string(),
+            version: version.to_string(),
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            local,
+        }
+    }
+
+    #[test]
+    fn test_closure_marks_direct_and_skips_local_nodes() {
+        let mut graph = LockGraph::default();
+        graph
+            .nodes
+            .insert("a".into(), node("a", "1.0.0", &["b"], false));
+        graph
+            .nodes
+            .insert("b".into(), node("b", "2.0.0", &["a"], false));
+        graph
+            .nodes
+            .insert("ws".into(), node("ws", "0.0.0", &["c"], true));
+        graph
+            .nodes
+            .insert("c".into(), node("c", "3.0.0", &["b"], false));
+
+        let deps = graph.closure(&["a".to_string(), "ws".to_string()]);
+        let summary: Vec<(&str, &str, bool)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a", "1.0.0", true),
+                ("b", "2.0.0", false),
+                // Reached through a local workspace dependency
+                ("c", "3.0.0", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kind_for() {
+        assert_eq!(kind_for("Cargo.lock"), Some("cargo"));
+        assert_eq!(kind_for("yarn.lock"), Some("npm"));
+        assert_eq!(kind_for("poetry.lock"), Some("python"));
+        assert_eq!(kind_for("package.json"), None);
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("10.1.0", "9.4.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-beta.1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("2.31.0rc1", "2.4.0"), Ordering::Greater);
+        assert_eq!(compare_versions("v2.0.0+incompatible", "v2.0.0"), Ordering::Greater);
+    }
+}
@@ -0,0 +1,211 @@
+use super::{Importer, Lockfile, ResolvedDep, ResolvedSet};
+use anyhow::{Result, bail};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Deserialize)]
+struct PackageLockFile {
+    #[serde(rename = "lockfileVersion", default)]
+    lockfile_version: u32,
+    #[serde(default)]
+    packages: HashMap<String, LockEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LockEntry {
+    name: Option<String>,
+    version: Option<String>,
+    #[serde(default)]
+    link: bool,
+    resolved: Option<String>,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+    #[serde(rename = "devDependencies", default)]
+    dev_dependencies: HashMap<String, String>,
+    #[serde(rename = "optionalDependencies", default)]
+    optional_dependencies: HashMap<String, String>,
+    #[serde(rename = "peerDependencies", default)]
+    peer_dependencies: HashMap<String, String>,
+}
+
+impl LockEntry {
+    fn dependency_names(&self) -> impl Iterator<Item = &String> {
+        self.dependencies
+            .keys()
+            .chain(self.dev_dependencies.keys())
+            .chain(self.optional_dependencies.keys())
+            .chain(self.peer_dependencies.keys())
+    }
+}
+
+/// A parsed `package-lock.json` (lockfile v2/v3). Entries are keyed by install
+/// location, e.g. `node_modules/lodash` or `packages/app/node_modules/lodash`.
+#[derive(Debug)]
+pub struct NpmLock {
+    packages: HashMap<String, LockEntry>,
+}
+
+pub fn parse(content: &str) -> Result<NpmLock> {
+    let lock: PackageLockFile = serde_json::from_str(content)?;
+    if lock.lockfile_version < 2 {
+        bail!(
+            "package-lock.json lockfileVersion {} is not supported (v2+ required)",
+            lock.lockfile_version
+        );
+    }
+    Ok(NpmLock {
+        packages: lock.packages,
+    })
+}
+
+impl NpmLock {
+    /// Resolve `name` as required from `location` the way Node does: look in
+    /// `location/node_modules`, then in each ancestor's `node_modules`.
+    fn find(&self, location: &str, name: &str) -> Option<String> {
+        let mut dir = location.to_string();
+        loop {
+            let candidate = if dir.is_empty() {
+                format!("node_modules/{}", name)
+            } else {
+                format!("{}/node_modules/{}", dir, name)
+            };
+            if self.packages.contains_key(&candidate) {
+                return Some(candidate);
+            }
+            if dir.is_empty() {
+                return None;
+            }
+            dir = dir
+                .rsplit_once('/')
+                .map(|(p, _)| p.to_string())
+                .unwrap_or_default();
+        }
+    }
+}
+
+/// Package name for an install location: the part after the last `node_modules/`.
+fn name_from_location(location: &str) -> &str {
+    location
+        .rsplit_once("node_modules/")
+        .map(|(_, name)| name)
+        .unwrap_or(location)
+}
+
+impl Lockfile for NpmLock {
+    fn resolve(&self, importer: &Importer) -> Vec<ResolvedDep> {
+        let Some(root) = self.packages.get(importer.rel_dir) else {
+            return Vec::new();
+        };
+
+        let mut resolved = ResolvedSet::default();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut queue: VecDeque<(String, bool)> = root
+            .dependency_names()
+            .filter_map(|dep| self.find(importer.rel_dir, dep))
+            .map(|location| (location, true))
+            .collect();
+
+        while let Some((location, is_direct)) = queue.pop_front() {
+            if !seen.insert(location.clone()) {
+                continue;
+            }
+            let Some(entry) = self.packages.get(&location) else {
+                continue;
+            };
+
+            // Workspace symlinks: follow to the member's own entry, but don't report it
+            if entry.link {
+                if let Some(target) = &entry.resolved
+                    && let Some(member) = self.packages.get(target)
+                {
+                    for dep in member.dependency_names() {
+                        if let Some(found) = self.find(target, dep) {
+                            queue.push_back((found, false));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            if let Some(version) = &entry.version {
+                let name = entry
+                    .name
+                    .as_deref()
+                    .unwrap_or_else(|| name_from_location(&location));
+                resolved.insert(name, version, is_direct);
+            }
+            for dep in entry
+                .dependencies
+                .keys()
+                .chain(entry.optional_dependencies.keys())
+            {
+                if let Some(found) = self.find(&location, dep) {
+                    queue.push_back((found, false));
+                }
+            }
+        }
+
+        resolved.into_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"{
+  "name": "monorepo",
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "monorepo", "workspaces": ["packages/*"] },
+    "node_modules/app": { "resolved": "packages/app", "link": true },
+    "node_modules/shared": { "resolved": "packages/shared", "link": true },
+    "node_modules/lodash": { "version": "4.17.21" },
+    "node_modules/debug": { "version": "4.3.4", "dependencies": { "ms": "2.1.2" } },
+    "node_modules/ms": { "version": "2.1.2" },
+    "node_modules/jest": { "version": "29.7.0", "dev": true },
+    "packages/app": {
+      "name": "app",
+      "version": "1.0.0",
+      "dependencies": { "shared": "*", "debug": "^4.3.0", "lodash": "^4.17.0" },
+      "devDependencies": { "jest": "^29.0.0" }
+    },
+    "packages/app/node_modules/lodash": { "version": "3.10.1" },
+    "packages/shared": {
+      "name": "shared",
+      "version": "2.0.0",
+      "dependencies": { "lodash": "^4.17.0" }
+    }
+  }
+}"#;
+
+    #[test]
+    fn test_resolve_workspace_member_with_nested_node_modules() {
+        let lock = parse(LOCK).unwrap();
+        let deps = lock.resolve(&Importer {
+            name: "app",
+            rel_dir: "packages/app",
+            declared: &[],
+        });
+        let summary: Vec<(&str, &str, bool)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("debug", "4.3.4", true),
+                ("jest", "29.7.0", true),
+                // The nested copy wins for app; shared gets the hoisted one
+                ("lodash", "3.10.1", true),
+                ("lodash", "4.17.21", false),
+                ("ms", "2.1.2", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_v1_lockfile_is_error() {
+        assert!(parse(r#"{"lockfileVersion": 1, "dependencies": {}}"#).is_err());
+    }
+}
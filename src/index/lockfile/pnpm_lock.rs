@@ -0,0 +1,373 @@
+use super::{Importer, LockGraph, LockNode, Lockfile, ResolvedDep};
+use anyhow::Result;
+use serde_yaml::{Mapping, Value};
+
+/// A parsed `pnpm-lock.yaml`. Supports the v5 (`/name/1.0.0`), v6 (`/name@1.0.0`)
+/// and v9 (`name@1.0.0`, with a separate `snapshots` section) key styles.
+#[derive(Debug)]
+pub struct PnpmLock {
+    importers: Mapping,
+    graph: LockGraph,
+    key_style: KeyStyle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KeyStyle {
+    /// `/name/1.0.0` (lockfile v5)
+    SlashVersion,
+    /// `/name@1.0.0` (lockfile v6)
+    SlashAt,
+    /// `name@1.0.0` (lockfile v9)
+    At,
+}
+
+const DEP_SECTIONS: &[&str] = &["dependencies", "devDependencies", "optionalDependencies"];
+
+pub fn parse(content: &str) -> Result<PnpmLock> {
+    let doc: Value = serde_yaml::from_str(content)?;
+
+    let version = match &doc["lockfileVersion"] {
+        Value::String(s) => s.parse::<f64>().unwrap_or(0.0),
+        Value::Number(n) => n.as_f64().unwrap_or(0.0),
+        _ => 0.0,
+    };
+    let key_style = if version >= 9.0 {
+        KeyStyle::At
+    } else if version >= 6.0 {
+        KeyStyle::SlashAt
+    } else {
+        KeyStyle::SlashVersion
+    };
+
+    // v9 moved the dependency graph from `packages` to `snapshots`
+    let graph_section = if key_style == KeyStyle::At {
+        &doc["snapshots"]
+    } else {
+        &doc["packages"]
+    };
+
+    let mut graph = LockGraph::default();
+    if let Value::Mapping(entries) = graph_section {
+        for (key, entry) in entries {
+            let Some(key) = key.as_str() else { continue };
+            let Some((name, version)) = split_key(key, key_style) else {
+                continue;
+            };
+            let mut deps = Vec::new();
+            for section in ["dependencies", "optionalDependencies"] {
+                if let Value::Mapping(map) = &entry[section] {
+                    for (dep, version) in map {
+                        if let (Some(dep), Some(version)) = (dep.as_str(), version_str(version)) {
+                            deps.push(child_key(dep, &version, key_style));
+                        }
+                    }
+                }
+            }
+            graph.nodes.insert(
+                key.to_string(),
+                LockNode {
+                    name,
+                    version,
+                    deps,
+                    local: false,
+                },
+            );
+        }
+    }
+
+    let importers = match &doc["importers"] {
+        Value::Mapping(importers) => importers.clone(),
+        // Lockfiles for a single project keep the importer's deps at the top level
+        _ => {
+            let mut root = Mapping::new();
+            for section in DEP_SECTIONS {
+                if let Some(deps) = doc.get(*section) {
+                    root.insert((*section).into(), deps.clone());
+                }
+            }
+            let mut importers = Mapping::new();
+            importers.insert(".".into(), Value::Mapping(root));
+            importers
+        }
+    };
+
+    Ok(PnpmLock {
+        importers,
+        graph,
+        key_style,
+    })
+}
+
+/// Version of an importer or snapshot dependency: a plain string (v5, snapshots)
+/// or a `{specifier, version}` map (v6+ importers).
+fn version_str(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Mapping(_) => version_str(&value["version"]),
+        _ => None,
+    }
+}
+
+/// Key of the graph node a dependency points at. Aliased dependencies carry the
+/// full key (`/string-width@4.2.3` or `string-width@4.2.3`) as their version.
+fn child_key(dep: &str, version: &str, style: KeyStyle) -> String {
+    if version.starts_with('/') || (style == KeyStyle::At && is_alias(version)) {
+        return version.to_string();
+    }
+    match style {
+        KeyStyle::SlashVersion => format!("/{}/{}", dep, version),
+        KeyStyle::SlashAt => format!("/{}@{}", dep, version),
+        KeyStyle::At => format!("{}@{}", dep, version),
+    }
+}
+
+fn is_alias(version: &str) -> bool {
+    !version.starts_with(|c: char| c.is_ascii_digit()) && !version.starts_with("link:")
+}
+
+/// Split a package key into name and version, dropping peer-dependency suffixes
+/// like `(react@18.2.0)` (v6+) or `_react@18.2.0` (v5).
+fn split_key(key: &str, style: KeyStyle) -> Option<(String, String)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+    let key = key.split('(').next().unwrap_or(key);
+    let (name, version) = match style {
+        KeyStyle::SlashVersion => {
+            let (name, version) = key.rsplit_once('/')?;
+            (name, version.split('_').next().unwrap_or(version))
+        }
+        KeyStyle::SlashAt | KeyStyle::At => {
+            // Skip the leading `@` of scoped names
+            let at = key[1..].rfind('@')? + 1;
+            (&key[..at], &key[at + 1..])
+        }
+    };
+    (!name.is_empty() && !version.is_empty()).then(|| (name.to_string(), version.to_string()))
+}
+
+/// Normalize an importer path for lookup: `.` is the lockfile's own directory.
+fn importer_key(rel_dir: &str) -> &str {
+    if rel_dir.is_empty() { "." } else { rel_dir }
+}
+
+/// Join a `link:` target onto the importer that declares it.
+fn join_link(importer: &str, link: &str) -> String {
+    let mut parts: Vec<&str> = importer
+        .split('/')
+        .filter(|p| !p.is_empty() && *p != ".")
+        .collect();
+    for part in link.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+impl PnpmLock {
+    /// Graph keys for an importer's dependencies, following `link:` deps into
+    /// the linked importer. Returns `(direct keys, keys reached through links)`.
+    fn importer_deps(&self, key: &str) -> (Vec<String>, Vec<String>) {
+        let mut direct = Vec::new();
+        let mut linked = Vec::new();
+        let mut visited = vec![key.to_string()];
+        let mut pending = vec![(key.to_string(), true)];
+
+        while let Some((importer_key, is_root)) = pending.pop() {
+            let Some(importer) = self.importers.get(importer_key.as_str()) else {
+                continue;
+            };
+            for section in DEP_SECTIONS {
+                let Value::Mapping(map) = &importer[*section] else {
+                    continue;
+                };
+                for (dep, value) in map {
+                    let (Some(dep), Some(version)) = (dep.as_str(), version_str(value)) else {
+                        continue;
+                    };
+                    if let Some(link) = version.strip_prefix("link:") {
+                        let target = join_link(&importer_key, link);
+                        if !visited.contains(&target) {
+                            visited.push(target.clone());
+                            pending.push((target, false));
+                        }
+                        continue;
+                    }
+                    let child = child_key(dep, &version, self.key_style);
+                    if is_root {
+                        direct.push(child);
+                    } else {
+                        linked.push(child);
+                    }
+                }
+            }
+        }
+
+        (direct, linked)
+    }
+}
+
+impl Lockfile for PnpmLock {
+    fn resolve(&self, importer: &Importer) -> Vec<ResolvedDep> {
+        let (direct, linked) = self.importer_deps(importer_key(importer.rel_dir));
+        let mut deps = self.graph.closure(&direct);
+
+        // Deps of linked workspace members are transitive for this importer
+        let transitive = self.graph.closure(&linked);
+        for dep in transitive {
+            if !deps
+                .iter()
+                .any(|d| d.name == dep.name && d.version == dep.version)
+            {
+                deps.push(ResolvedDep {
+                    is_direct: false,
+                    ..dep
+                });
+            }
+        }
+        deps.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+        deps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK_V9: &str = r#"
+lockfileVersion: '9.0'
+
+importers:
+  .:
+    devDependencies:
+      typescript:
+        specifier: ^5.3.0
+        version: 5.3.3
+  packages/app:
+    dependencies:
+      '@acme/shared':
+        specifier: workspace:*
+        version: link:../shared
+      react-dom:
+        specifier: ^18.2.0
+        version: 18.2.0(react@18.2.0)
+  packages/shared:
+    dependencies:
+      lodash:
+        specifier: ^4.17.21
+        version: 4.17.21
+
+packages:
+  lodash@4.17.21:
+    resolution: {integrity: sha512-x}
+  react@18.2.0:
+    resolution: {integrity: sha512-y}
+  react-dom@18.2.0:
+    resolution: {integrity: sha512-z}
+  typescript@5.3.3:
+    resolution: {integrity: sha512-w}
+
+snapshots:
+  lodash@4.17.21: {}
+  react@18.2.0: {}
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      react: 18.2.0
+  typescript@5.3.3: {}
+"#;
+
+    #[test]
+    fn test_resolve_v9_importer_with_link_and_peers() {
+        let lock = parse(LOCK_V9).unwrap();
+        let deps = lock.resolve(&Importer {
+            name: "app",
+            rel_dir: "packages/app",
+            declared: &[],
+        });
+        let summary: Vec<(&str, &str, bool)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                // Reached through the linked @acme/shared workspace member
+                ("lodash", "4.17.21", false),
+                ("react", "18.2.0", false),
+                ("react-dom", "18.2.0", true),
+            ]
+        );
+
+        let root = lock.resolve(&Importer {
+            name: "monorepo",
+            rel_dir: "",
+            declared: &[],
+        });
+        assert_eq!(root.len(), 1);
+        assert_eq!(root[0].name, "typescript");
+        assert_eq!(root[0].version, "5.3.3");
+    }
+
+    #[test]
+    fn test_resolve_v6_single_project() {
+        let lock = parse(
+            r#"
+lockfileVersion: '6.0'
+
+dependencies:
+  '@babel/core':
+    specifier: ^7.23.0
+    version: 7.23.7
+
+packages:
+  /@babel/core@7.23.7:
+    resolution: {integrity: sha512-a}
+    dependencies:
+      '@babel/types': 7.23.6
+  /@babel/types@7.23.6:
+    resolution: {integrity: sha512-b}
+"#,
+        )
+        .unwrap();
+        let deps = lock.resolve(&Importer {
+            name: "web",
+            rel_dir: "",
+            declared: &[],
+        });
+        let summary: Vec<(&str, &str, bool)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("@babel/core", "7.23.7", true),
+                ("@babel/types", "7.23.6", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_key_styles() {
+        assert_eq!(
+            split_key("/@scope/pkg/1.0.0_react@18.2.0", KeyStyle::SlashVersion),
+            Some(("@scope/pkg".into(), "1.0.0".into()))
+        );
+        assert_eq!(
+            split_key("/@scope/pkg@1.0.0(react@18.2.0)", KeyStyle::SlashAt),
+            Some(("@scope/pkg".into(), "1.0.0".into()))
+        );
+        assert_eq!(
+            split_key("pkg@2.0.0", KeyStyle::At),
+            Some(("pkg".into(), "2.0.0".into()))
+        );
+    }
+}
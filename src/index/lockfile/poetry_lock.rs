@@ -0,0 +1,159 @@
+use super::{Importer, LockGraph, LockNode, Lockfile, ResolvedDep};
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize)]
+struct PoetryLockFile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    dependencies: HashMap<String, toml::Value>,
+}
+
+/// A parsed `poetry.lock`. The project itself isn't listed, so direct
+/// dependencies come from the importer's declared dependencies.
+#[derive(Debug)]
+pub struct PoetryLock {
+    graph: LockGraph,
+}
+
+pub fn parse(content: &str) -> Result<PoetryLock> {
+    let lock: PoetryLockFile = toml::from_str(content)?;
+
+    let mut graph = LockGraph::default();
+    for pkg in lock.package {
+        let mut deps: Vec<String> = pkg.dependencies.keys().map(|d| normalize_name(d)).collect();
+        deps.sort();
+        // Keyed by normalized name: poetry locks a single version per package
+        graph
+            .nodes
+            .entry(normalize_name(&pkg.name))
+            .or_insert(LockNode {
+                name: pkg.name,
+                version: pkg.version,
+                deps,
+                local: false,
+            });
+    }
+
+    Ok(PoetryLock { graph })
+}
+
+/// PEP 503 name normalization: case-insensitive, with runs of `-`, `_` and `.` equivalent.
+fn normalize_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut prev_sep = false;
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !prev_sep {
+                out.push('-');
+            }
+            prev_sep = true;
+        } else {
+            out.extend(c.to_lowercase());
+            prev_sep = false;
+        }
+    }
+    out
+}
+
+impl Lockfile for PoetryLock {
+    fn resolve(&self, importer: &Importer) -> Vec<ResolvedDep> {
+        let direct: Vec<String> = importer
+            .declared
+            .iter()
+            .map(|(name, _)| normalize_name(name))
+            .collect();
+        let mut deps = self.graph.closure(&direct);
+
+        // Report direct deps under their declared spelling so they line up
+        // with rows in the dependencies table
+        let declared: HashMap<String, &str> = importer
+            .declared
+            .iter()
+            .map(|(name, _)| (normalize_name(name), name.as_str()))
+            .collect();
+        for dep in deps.iter_mut().filter(|d| d.is_direct) {
+            if let Some(name) = declared.get(&normalize_name(&dep.name)) {
+                dep.name = name.to_string();
+            }
+        }
+        deps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_declared_and_transitive() {
+        let lock = parse(
+            r#"
+[[package]]
+name = "django"
+version = "4.2.9"
+description = "A high-level Python web framework"
+optional = false
+python-versions = ">=3.8"
+
+[package.dependencies]
+asgiref = ">=3.6.0,<4"
+sqlparse = ">=0.3.1"
+
+[[package]]
+name = "asgiref"
+version = "3.7.2"
+
+[[package]]
+name = "sqlparse"
+version = "0.4.4"
+
+[[package]]
+name = "typing-extensions"
+version = "4.9.0"
+
+[metadata]
+lock-version = "2.0"
+python-versions = "^3.11"
+"#,
+        )
+        .unwrap();
+
+        let declared = vec![
+            ("Django".to_string(), Some("^4.2".to_string())),
+            ("python".to_string(), Some("^3.11".to_string())),
+        ];
+        let deps = lock.resolve(&Importer {
+            name: "api",
+            rel_dir: "",
+            declared: &declared,
+        });
+        let summary: Vec<(&str, &str, bool)> = deps
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("asgiref", "3.7.2", false),
+                ("Django", "4.2.9", true),
+                ("sqlparse", "0.4.4", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Typing_Extensions"), "typing-extensions");
+        assert_eq!(normalize_name("zope.interface"), "zope-interface");
+        assert_eq!(normalize_name("a__b"), "a-b");
+    }
+}
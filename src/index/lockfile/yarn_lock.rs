@@ -0,0 +1,277 @@
+use super::{Importer, LockGraph, LockNode, Lockfile, ResolvedDep};
+
+/// A parsed `yarn.lock`, classic (v1) or Berry (v2+). Entries are keyed by the
+/// descriptors (`lodash@^4.17.0`, `lodash@npm:^4.17.0`) that resolve to them, so
+/// an importer's direct deps are found from its declared version ranges.
+#[derive(Debug)]
+pub struct YarnLock {
+    graph: LockGraph,
+}
+
+#[derive(Default)]
+struct Entry {
+    descriptors: Vec<String>,
+    version: Option<String>,
+    deps: Vec<String>,
+}
+
+pub fn parse(content: &str) -> YarnLock {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut current: Option<Entry> = None;
+    let mut in_deps = false;
+
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+
+        if indent == 0 {
+            entries.extend(current.take());
+            in_deps = false;
+            // `__metadata:` (Berry) isn't a package
+            if let Some(header) = trimmed.strip_suffix(':')
+                && !header.starts_with("__metadata")
+            {
+                current = Some(Entry {
+                    descriptors: header
+                        .split(", ")
+                        .map(|d| unquote(d.trim()).to_string())
+                        .collect(),
+                    ..Entry::default()
+                });
+            }
+            continue;
+        }
+
+        let Some(entry) = current.as_mut() else {
+            continue;
+        };
+        if indent <= 2 {
+            in_deps = trimmed == "dependencies:" || trimmed == "optionalDependencies:";
+            if let Some(version) = field_value(trimmed, "version") {
+                entry.version = Some(version.to_string());
+            }
+        } else if in_deps && let Some((name, range)) = dependency_line(trimmed) {
+            entry.deps.push(format!("{}@{}", name, range));
+        }
+    }
+    entries.extend(current);
+
+    let mut graph = LockGraph::default();
+    for (i, entry) in entries.iter().enumerate() {
+        let Some(version) = &entry.version else {
+            continue;
+        };
+        let Some(name) = entry.descriptors.first().and_then(|d| descriptor_name(d)) else {
+            continue;
+        };
+        // Workspace and linked entries are repo-local packages
+        let local = entry.descriptors.iter().any(|d| {
+            let range = descriptor_name(d).map_or("", |n| &d[n.len() + 1..]);
+            range.starts_with("workspace:")
+                || range.starts_with("link:")
+                || range.starts_with("portal:")
+        });
+        let id = format!("#{}", i);
+        for descriptor in &entry.descriptors {
+            graph.nodes.insert(
+                descriptor.clone(),
+                LockNode {
+                    name: name.to_string(),
+                    version: version.clone(),
+                    deps: vec![id.clone()],
+                    local: true,
+                },
+            );
+        }
+        graph.nodes.insert(
+            id,
+            LockNode {
+                name: name.to_string(),
+                version: version.clone(),
+                deps: entry.deps.clone(),
+                local,
+            },
+        );
+    }
+
+    YarnLock { graph }
+}
+
+/// `version "1.2.3"` (v1) or `version: 1.2.3` (Berry).
+fn field_value<'a>(line: &'a str, field: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(field)?;
+    let rest = rest.strip_prefix(':').unwrap_or(rest);
+    if !rest.starts_with(' ') {
+        return None;
+    }
+    Some(unquote(rest.trim()))
+}
+
+/// `"@babel/highlight" "^7.10.4"` (v1) or `"@babel/highlight": "npm:^7.10.4"` (Berry).
+fn dependency_line(line: &str) -> Option<(&str, &str)> {
+    let (name, rest) = if let Some(quoted) = line.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        let end = line.find([' ', ':'])?;
+        (&line[..end], &line[end..])
+    };
+    let range = unquote(rest.trim_start_matches(':').trim());
+    (!name.is_empty() && !range.is_empty()).then_some((name, range))
+}
+
+/// Package name of a descriptor: everything before the `@` that starts the range.
+fn descriptor_name(descriptor: &str) -> Option<&str> {
+    let at = descriptor.get(1..)?.find('@')? + 1;
+    Some(&descriptor[..at])
+}
+
+fn unquote(s: &str) -> &str {
+    s.trim_matches('"')
+}
+
+impl Lockfile for YarnLock {
+    fn resolve(&self, importer: &Importer) -> Vec<ResolvedDep> {
+        let direct: Vec<String> = importer
+            .declared
+            .iter()
+            .filter_map(|(name, range)| {
+                let range = range.as_deref()?;
+                // Berry prefixes registry ranges with `npm:`
+                [
+                    format!("{}@{}", name, range),
+                    format!("{}@npm:{}", name, range),
+                ]
+                .into_iter()
+                .find(|d| self.graph.nodes.contains_key(d))
+            })
+            .collect();
+
+        // Descriptor nodes are local aliases for their entry, so start from the
+        // entries themselves to have them reported as direct
+        let direct_ids: Vec<String> = direct
+            .iter()
+            .filter_map(|d| self.graph.nodes.get(d))
+            .flat_map(|n| n.deps.clone())
+            .collect();
+        self.graph.closure(&direct_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(deps: &[ResolvedDep]) -> Vec<(&str, &str, bool)> {
+        deps.iter()
+            .map(|d| (d.name.as_str(), d.version.as_str(), d.is_direct))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_classic_yarn_lock() {
+        let lock = parse(
+            r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@babel/code-frame@^7.0.0", "@babel/code-frame@^7.10.4":
+  version "7.12.13"
+  resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.12.13.tgz"
+  integrity sha512-abc
+  dependencies:
+    "@babel/highlight" "^7.10.4"
+
+"@babel/highlight@^7.10.4":
+  version "7.13.10"
+  dependencies:
+    js-tokens "^4.0.0"
+
+js-tokens@^4.0.0:
+  version "4.0.0"
+
+lodash@^4.17.21:
+  version "4.17.21"
+"#,
+        );
+
+        let declared = vec![
+            ("@babel/code-frame".to_string(), Some("^7.10.4".to_string())),
+            ("unlocked".to_string(), Some("^1.0.0".to_string())),
+        ];
+        let deps = lock.resolve(&Importer {
+            name: "web",
+            rel_dir: "",
+            declared: &declared,
+        });
+        assert_eq!(
+            summary(&deps),
+            vec![
+                ("@babel/code-frame", "7.12.13", true),
+                ("@babel/highlight", "7.13.10", false),
+                ("js-tokens", "4.0.0", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_berry_yarn_lock() {
+        let lock = parse(
+            r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 8
+  cacheKey: 10c0
+
+"app@workspace:packages/app":
+  version: 0.0.0-use.local
+  resolution: "app@workspace:packages/app"
+  dependencies:
+    debug: "npm:^4.3.0"
+  languageName: unknown
+  linkType: soft
+
+"debug@npm:^4.3.0":
+  version: 4.3.4
+  resolution: "debug@npm:4.3.4"
+  dependencies:
+    ms: "npm:2.1.2"
+  languageName: node
+  linkType: hard
+
+"ms@npm:2.1.2":
+  version: 2.1.2
+  resolution: "ms@npm:2.1.2"
+  languageName: node
+  linkType: hard
+"#,
+        );
+
+        let declared = vec![("debug".to_string(), Some("^4.3.0".to_string()))];
+        let deps = lock.resolve(&Importer {
+            name: "app",
+            rel_dir: "packages/app",
+            declared: &declared,
+        });
+        assert_eq!(
+            summary(&deps),
+            vec![("debug", "4.3.4", true), ("ms", "2.1.2", false)]
+        );
+    }
+
+    #[test]
+    fn test_dependency_line_forms() {
+        assert_eq!(
+            dependency_line(r#""@babel/highlight" "^7.10.4""#),
+            Some(("@babel/highlight", "^7.10.4"))
+        );
+        assert_eq!(
+            dependency_line(r#"js-tokens: "npm:^4.0.0""#),
+            Some(("js-tokens", "npm:^4.0.0"))
+        );
+        assert_eq!(dependency_line("ms 2.1.2"), Some(("ms", "2.1.2")));
+    }
+}
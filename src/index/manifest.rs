@@ -39,6 +39,21 @@ impl DepKind {
 }
 
 pub trait ManifestParser {
+    /// Manifest filename, or a glob such as `*.csproj` for per-project file names.
     fn filename(&self) -> &'static str;
     fn parse(&self, manifest_path: &Path, relative_dir: &str) -> Result<PackageInfo>;
+
+    fn matches(&self, filename: &str) -> bool {
+        filename_matches(self.filename(), filename)
+    }
+}
+
+/// Match a filename against a manifest pattern: an exact name (`package.json`)
+/// or a glob (`*.csproj`).
+pub fn filename_matches(pattern: &str, filename: &str) -> bool {
+    if pattern.contains(['*', '?', '[']) {
+        glob::Pattern::new(pattern).is_ok_and(|p| p.matches(filename))
+    } else {
+        pattern == filename
+    }
 }
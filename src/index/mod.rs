@@ -1,18 +1,27 @@
 pub mod cargo;
+pub mod codeowners;
 pub mod custom_discovery;
+pub mod dotnet;
+pub mod dotnet_sln;
+pub mod federation;
 pub mod go;
 pub mod go_work;
 pub mod gradle;
 pub mod gradle_settings;
 pub mod hash;
+pub mod history;
+pub mod incremental;
+pub mod lockfile;
 pub mod manifest;
 pub mod maven;
 pub mod npm;
+pub mod npm_workspace;
 pub mod perl;
 pub mod python;
 pub mod ruby;
+pub mod swift;
 
-use crate::config::Config;
+use crate::config::{Config, HistoryConfig};
 use crate::db;
 use crate::symbols;
 use anyhow::Result;
@@ -58,17 +67,25 @@ fn walk_manifests(
     config: &Config,
     parsers: &[Box<dyn ManifestParser>],
 ) -> Result<Vec<WalkedManifest>> {
-    let mut manifest_filenames: HashSet<&str> = parsers.iter().map(|p| p.filename()).collect();
+    let mut manifest_patterns: HashSet<&str> = parsers.iter().map(|p| p.filename()).collect();
     // go.work provides workspace context, not packages — but must be walked
-    manifest_filenames.insert("go.work");
+    manifest_patterns.insert("go.work");
     // settings.gradle provides workspace context, not packages — but must be walked
-    manifest_filenames.insert("settings.gradle");
-    manifest_filenames.insert("settings.gradle.kts");
-    let enabled: HashSet<&str> = config
+    manifest_patterns.insert("settings.gradle");
+    manifest_patterns.insert("settings.gradle.kts");
+    // .sln files provide solution membership for .NET projects — but must be walked
+    manifest_patterns.insert("*.sln");
+    // pnpm-workspace.yaml declares npm workspace members — but must be walked
+    manifest_patterns.insert("pnpm-workspace.yaml");
+    // Lockfiles pin resolved versions for packages next to or below them — but must be walked
+    manifest_patterns.extend(lockfile::LOCKFILES.iter().map(|(name, _)| *name));
+    // Only patterns that are also enabled in config; entries may be exact names or globs
+    let patterns: Vec<&str> = config
         .discovery
         .manifests
         .iter()
         .map(|s| s.as_str())
+        .filter(|p| manifest_patterns.contains(p))
         .collect();
     let exclude_set: HashSet<String> = config.discovery.exclude.iter().cloned().collect();
 
@@ -97,8 +114,9 @@ fn walk_manifests(
             None => continue,
         };
 
-        if !manifest_filenames.contains(filename.as_str())
-            || !enabled.contains(filename.as_str())
+        if !patterns
+            .iter()
+            .any(|p| manifest::filename_matches(p, &filename))
         {
             continue;
         }
@@ -237,7 +255,7 @@ fn upsert_package(conn: &Connection, pkg: &PackageInfo) -> Result<String> {
 
 /// Recompute is_internal for all dependencies using a single SQL UPDATE.
 /// Handles both direct package name matches and Go module path aliases.
-fn recompute_is_internal(conn: &Connection) -> Result<()> {
+pub(crate) fn recompute_is_internal(conn: &Connection) -> Result<()> {
     conn.execute(
         "UPDATE dependencies SET is_internal = (
             dependency IN (SELECT name FROM packages)
@@ -248,6 +266,119 @@ fn recompute_is_internal(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Phase 5.5: Resolve locked dependency versions from lockfiles. Each package uses
+/// the nearest lockfile for its kind in its own directory or an ancestor (`go.sum`
+/// only applies to the module next to it). Returns the number of rows stored.
+fn phase_lockfiles(conn: &Connection, walked: &[WalkedManifest]) -> Result<usize> {
+    conn.execute("DELETE FROM resolved_dependencies", [])?;
+
+    let lockfile_name = |m: &WalkedManifest| -> &'static str {
+        let filename = m.abs_path.file_name().and_then(|f| f.to_str()).unwrap_or("");
+        lockfile::LOCKFILES
+            .iter()
+            .find(|(name, _)| *name == filename)
+            .map_or("", |(name, _)| *name)
+    };
+    let rank = |m: &WalkedManifest| {
+        lockfile::LOCKFILES
+            .iter()
+            .position(|(name, _)| *name == lockfile_name(m))
+    };
+
+    // (dir, kind) → lockfile, preferring earlier LOCKFILES entries within a directory
+    let mut lockfiles: HashMap<(&str, &str), &WalkedManifest> = HashMap::new();
+    for manifest in walked {
+        let Some(kind) = lockfile::kind_for(lockfile_name(manifest)) else {
+            continue;
+        };
+        let key = (manifest.relative_dir.as_str(), kind);
+        match lockfiles.get(&key) {
+            Some(existing) if rank(existing) <= rank(manifest) => {}
+            _ => {
+                lockfiles.insert(key, manifest);
+            }
+        }
+    }
+    if lockfiles.is_empty() {
+        return Ok(0);
+    }
+
+    let packages: Vec<(String, String, String)> = conn
+        .prepare("SELECT name, path, kind FROM packages")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+    let internal: HashSet<&str> = packages.iter().map(|(name, _, _)| name.as_str()).collect();
+
+    // Group packages by the lockfile that governs them
+    let mut importers: HashMap<(&str, &str), Vec<(&str, &str)>> = HashMap::new();
+    for (name, path, kind) in &packages {
+        let mut dir = path.as_str();
+        loop {
+            if lockfiles.contains_key(&(dir, kind.as_str())) {
+                importers.entry((dir, kind.as_str())).or_default().push((name, path));
+                break;
+            }
+            if dir.is_empty() || kind == "go" {
+                break;
+            }
+            dir = dir.rsplit_once('/').map_or("", |(parent, _)| parent);
+        }
+    }
+
+    let mut declared_stmt = conn.prepare(
+        "SELECT dependency, version_req FROM dependencies WHERE package = ?1 AND is_internal = 0",
+    )?;
+    let mut insert_stmt = conn.prepare(
+        "INSERT OR IGNORE INTO resolved_dependencies (package, dependency, version, is_direct, lockfile)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    let mut num_rows = 0;
+
+    for (key, members) in &importers {
+        let manifest = lockfiles[key];
+        let lock = match lockfile::load(&manifest.abs_path) {
+            Ok(lock) => lock,
+            Err(e) => {
+                eprintln!("Warning: skipping lockfile {}: {}", manifest.manifest_key, e);
+                continue;
+            }
+        };
+
+        for (name, path) in members {
+            let rel_dir = if manifest.relative_dir.is_empty() {
+                path
+            } else {
+                path.strip_prefix(manifest.relative_dir.as_str())
+                    .map(|p| p.trim_start_matches('/'))
+                    .unwrap_or(path)
+            };
+            let declared: Vec<(String, Option<String>)> = declared_stmt
+                .query_map([name], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<_, _>>()?;
+
+            let resolved = lock.resolve(&lockfile::Importer {
+                name,
+                rel_dir,
+                declared: &declared,
+            });
+            for dep in resolved {
+                if internal.contains(dep.name.as_str()) {
+                    continue;
+                }
+                num_rows += insert_stmt.execute((
+                    name,
+                    &dep.name,
+                    &dep.version,
+                    dep.is_direct,
+                    &manifest.manifest_key,
+                ))?;
+            }
+        }
+    }
+
+    Ok(num_rows)
+}
+
 /// Post-build safety net: clean up any orphaned child rows that reference
 /// non-existent packages. This handles edge cases that slip through the
 /// per-phase FK management.
@@ -287,24 +418,28 @@ fn validate_referential_integrity(conn: &Connection) -> Result<()> {
 /// Clear and re-insert symbols for a package using batched multi-row INSERTs.
 fn upsert_symbols(conn: &Connection, package: &str, syms: &[symbols::SymbolInfo]) -> Result<()> {
     conn.execute("DELETE FROM symbols WHERE package = ?1", [package])?;
+    insert_symbols(conn, package, syms)
+}
 
+/// Insert symbols for a package using batched multi-row INSERTs.
+fn insert_symbols(conn: &Connection, package: &str, syms: &[symbols::SymbolInfo]) -> Result<()> {
     const BATCH_SIZE: usize = 100;
-    const COLS: usize = 10;
+    const COLS: usize = 11;
 
     for chunk in syms.chunks(BATCH_SIZE) {
         let placeholders: Vec<String> = (0..chunk.len())
             .map(|i| {
                 let base = i * COLS + 1;
                 format!(
-                    "(?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{})",
+                    "(?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{}, ?{})",
                     base, base + 1, base + 2, base + 3, base + 4,
-                    base + 5, base + 6, base + 7, base + 8, base + 9
+                    base + 5, base + 6, base + 7, base + 8, base + 9, base + 10
                 )
             })
             .collect();
 
         let sql = format!(
-            "INSERT INTO symbols (package, name, kind, signature, file_path, line, visibility, parent_symbol, return_type, parameters) VALUES {}",
+            "INSERT INTO symbols (package, name, kind, signature, file_path, line, visibility, parent_symbol, return_type, parameters, name_words) VALUES {}",
             placeholders.join(", ")
         );
 
@@ -325,6 +460,7 @@ fn upsert_symbols(conn: &Connection, package: &str, syms: &[symbols::SymbolInfo]
             params.push(Box::new(sym.parent_symbol.clone()));
             params.push(Box::new(sym.return_type.clone()));
             params.push(Box::new(params_json));
+            params.push(Box::new(symbols::identifier_words(&sym.name).join(" ")));
         }
 
         conn.execute(&sql, rusqlite::params_from_iter(params.iter()))?;
@@ -433,29 +569,12 @@ fn associate_files_with_packages(
     files: &[WalkedFile],
     packages: &[(String, String)], // (name, path)
 ) -> Vec<(String, Option<String>, String, u64)> {
-    // Sort package paths by length descending so longest prefix matches first
-    let mut sorted_pkgs: Vec<&(String, String)> = packages.iter().collect();
-    sorted_pkgs.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+    let sorted_pkgs = sort_packages_by_path_len(packages);
 
     files
         .iter()
         .map(|file| {
-            let file_dir = file
-                .relative_path
-                .rsplit_once('/')
-                .map(|(dir, _)| dir)
-                .unwrap_or("");
-
-            let package = sorted_pkgs.iter().find_map(|(name, path)| {
-                if path.is_empty() {
-                    // Root-level package matches everything
-                    Some(name.clone())
-                } else if file_dir == path.as_str() || file_dir.starts_with(&format!("{}/", path)) {
-                    Some(name.clone())
-                } else {
-                    None
-                }
-            });
+            let package = owning_package(&file.relative_path, &sorted_pkgs).map(str::to_string);
 
             (
                 file.relative_path.clone(),
@@ -467,6 +586,30 @@ fn associate_files_with_packages(
         .collect()
 }
 
+/// Sort (name, path) pairs by path length descending so longest prefix matches first.
+pub fn sort_packages_by_path_len(packages: &[(String, String)]) -> Vec<&(String, String)> {
+    let mut sorted_pkgs: Vec<&(String, String)> = packages.iter().collect();
+    sorted_pkgs.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+    sorted_pkgs
+}
+
+/// Find the package owning a repo-relative file path. `sorted_pkgs` must come from
+/// `sort_packages_by_path_len` so the deepest package directory wins.
+pub fn owning_package<'a>(file_path: &str, sorted_pkgs: &[&'a (String, String)]) -> Option<&'a str> {
+    let file_dir = file_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+    sorted_pkgs.iter().find_map(|(name, path)| {
+        if path.is_empty() || path == "." {
+            // Root-level package matches everything
+            Some(name.as_str())
+        } else if file_dir == path.as_str() || file_dir.starts_with(&format!("{}/", path)) {
+            Some(name.as_str())
+        } else {
+            None
+        }
+    })
+}
+
 /// Clear and re-insert all files using batched multi-row INSERTs.
 fn upsert_files(
     conn: &Connection,
@@ -591,12 +734,107 @@ fn collect_gradle_settings_context(
     (dirs, root_names)
 }
 
+/// Scan walked .sln files and map each member project directory to the solutions that list it.
+fn collect_dotnet_solution_context(walked: &[WalkedManifest]) -> HashMap<String, Vec<String>> {
+    let mut solutions: HashMap<String, Vec<String>> = HashMap::new();
+
+    for manifest in walked {
+        if manifest.abs_path.extension().and_then(|e| e.to_str()) != Some("sln") {
+            continue;
+        }
+
+        if let Ok(sln) = dotnet_sln::parse_sln(&manifest.abs_path) {
+            for d in &sln.project_dirs {
+                // .sln project paths are relative to the .sln location
+                if let Some(full_dir) = dotnet_sln::resolve_project_dir(&manifest.relative_dir, d) {
+                    let names = solutions.entry(full_dir).or_default();
+                    if !names.contains(&sln.name) {
+                        names.push(sln.name.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    solutions
+}
+
+/// Scan walked package.json and pnpm-workspace.yaml files for npm/yarn/pnpm workspace
+/// roots, then record the version of every member so `workspace:` ranges can be resolved.
+fn collect_npm_workspace_context(walked: &[WalkedManifest]) -> npm_workspace::NpmWorkspaceContext {
+    let mut roots: HashMap<String, npm_workspace::NpmWorkspace> = HashMap::new();
+    let mut packages: Vec<(&str, String, String)> = Vec::new(); // (dir, name, version)
+
+    for manifest in walked {
+        let filename = manifest
+            .abs_path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .unwrap_or("");
+
+        if filename == "pnpm-workspace.yaml" {
+            if let Ok(patterns) = npm_workspace::parse_pnpm_workspace(&manifest.abs_path) {
+                // pnpm ignores package.json `workspaces`, so this definition wins
+                roots.insert(
+                    manifest.relative_dir.clone(),
+                    npm_workspace::NpmWorkspace {
+                        root_dir: manifest.relative_dir.clone(),
+                        manager: "pnpm".to_string(),
+                        patterns,
+                    },
+                );
+            }
+            continue;
+        }
+
+        if filename != "package.json" {
+            continue;
+        }
+        let json: serde_json::Value = match std::fs::read_to_string(&manifest.abs_path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+        {
+            Some(j) => j,
+            None => continue,
+        };
+
+        if let Some(patterns) = npm_workspace::parse_package_json_workspaces(&json) {
+            let root_abs_dir = manifest.abs_path.parent().unwrap_or(Path::new("."));
+            roots
+                .entry(manifest.relative_dir.clone())
+                .or_insert_with(|| npm_workspace::NpmWorkspace {
+                    root_dir: manifest.relative_dir.clone(),
+                    manager: npm_workspace::detect_manager(&json, root_abs_dir),
+                    patterns,
+                });
+        }
+        if let (Some(name), Some(version)) = (json["name"].as_str(), json["version"].as_str()) {
+            packages.push((&manifest.relative_dir, name.to_string(), version.to_string()));
+        }
+    }
+
+    let mut ctx = npm_workspace::NpmWorkspaceContext {
+        workspaces: roots.into_values().collect(),
+        member_versions: HashMap::new(),
+    };
+    for (dir, name, version) in packages {
+        if let Some(ws) = ctx.workspace_for(dir) {
+            let root = ws.root_dir.clone();
+            ctx.member_versions.entry(root).or_default().insert(name, version);
+        }
+    }
+
+    ctx
+}
+
 /// Workspace context collected in Phase 1.5 for use during manifest parsing.
 struct WorkspaceContext {
     cargo_deps: HashMap<String, String>,
     go_dirs: HashSet<String>,
     maven_parents: HashMap<String, maven::MavenParentContext>,
     gradle_settings: (HashSet<String>, HashMap<String, Option<String>>),
+    dotnet_solutions: HashMap<String, Vec<String>>,
+    npm: npm_workspace::NpmWorkspaceContext,
 }
 
 /// Summary of a completed build, used for output and metadata storage.
@@ -631,10 +869,38 @@ fn phase_parse(
             .unwrap_or("");
 
         // Skip context-only files — they provide workspace context, not packages
-        if filename == "go.work"
-            || filename == "settings.gradle"
-            || filename == "settings.gradle.kts"
-        {
+        if is_context_file(filename) {
+            continue;
+        }
+
+        // npm: record workspace membership and resolve `workspace:` ranges
+        if filename == "package.json" && !ws.npm.workspaces.is_empty() {
+            let npm_parser = npm::NpmParser;
+            let workspace = ws.npm.workspace_for(&manifest.relative_dir);
+            let no_members = HashMap::new();
+            let member_versions = workspace
+                .and_then(|w| ws.npm.member_versions.get(&w.root_dir))
+                .unwrap_or(&no_members);
+            match npm_parser.parse_with_workspace_versions(
+                &manifest.abs_path,
+                &manifest.relative_dir,
+                member_versions,
+            ) {
+                Ok(mut pkg) => {
+                    pkg.metadata = Some(match workspace {
+                        Some(w) => serde_json::json!({
+                            "npm_workspace": {"root": w.root_dir, "manager": w.manager}
+                        }),
+                        // Not matched by any workspace glob — likely a stray or forgotten package
+                        None => serde_json::json!({"npm_workspace_orphan": true}),
+                    });
+                    let winner = upsert_package(conn, &pkg)?;
+                    parsed_packages.push((winner, pkg.path.clone(), pkg.kind.to_string()));
+                }
+                Err(e) => {
+                    failures.push((manifest.abs_path.display().to_string(), e.to_string()));
+                }
+            }
             continue;
         }
 
@@ -711,12 +977,21 @@ fn phase_parse(
         }
 
         for parser in parsers {
-            if parser.filename() == filename {
+            if parser.matches(filename) {
                 match parser.parse(&manifest.abs_path, &manifest.relative_dir) {
                     Ok(mut pkg) => {
                         if pkg.kind == "go" && ws.go_dirs.contains(&manifest.relative_dir) {
                             pkg.metadata = Some(serde_json::json!({"go_workspace": true}));
                         }
+                        if pkg.kind == "dotnet"
+                            && let Some(solutions) = ws.dotnet_solutions.get(&manifest.relative_dir)
+                            && let Some(meta) = pkg
+                                .metadata
+                                .get_or_insert_with(|| serde_json::json!({}))
+                                .as_object_mut()
+                        {
+                            meta.insert("dotnet_solutions".into(), serde_json::json!(solutions));
+                        }
                         let winner = upsert_package(conn, &pkg)?;
                         parsed_packages.push((winner, pkg.path.clone(), pkg.kind.to_string()));
                     }
@@ -743,13 +1018,30 @@ fn phase_parse(
     Ok((parsed_packages, failures))
 }
 
+/// Walked files that provide workspace context or lockfile data rather than packages.
+fn is_context_file(filename: &str) -> bool {
+    filename == "go.work"
+        || filename == "settings.gradle"
+        || filename == "settings.gradle.kts"
+        || filename.ends_with(".sln")
+        || filename == "pnpm-workspace.yaml"
+        || lockfile::kind_for(filename).is_some()
+}
+
 /// Phase 4: Remove packages whose manifests were deleted.
 fn phase_remove_deleted(conn: &Connection, removed: &[String]) -> Result<()> {
     for manifest_key in removed {
-        let relative_dir = manifest_key
+        let (relative_dir, filename) = manifest_key
             .rsplit_once('/')
-            .map(|(dir, _)| dir)
-            .unwrap_or("");
+            .unwrap_or(("", manifest_key.as_str()));
+        // A deleted context file leaves the package in its directory in place
+        if is_context_file(filename) {
+            conn.execute(
+                "DELETE FROM manifest_hashes WHERE path = ?1",
+                [manifest_key.as_str()],
+            )?;
+            continue;
+        }
         conn.execute(
             "DELETE FROM source_hashes WHERE package IN (SELECT name FROM packages WHERE path = ?1)",
             [relative_dir],
@@ -943,14 +1235,19 @@ fn phase_index_files(
     conn: &Connection,
     repo_root: &Path,
     config: &Config,
+    codeowners: Option<&codeowners::CodeOwners>,
 ) -> Result<usize> {
     let walked_files = walk_files(repo_root, config)?;
 
-    // Compute file-tree hash from (path, size) tuples
-    let file_tuples: Vec<(String, u64)> = walked_files
+    // Compute file-tree hash from (path, size) tuples, plus the CODEOWNERS
+    // contents so an ownership-only edit still refreshes file owners
+    let mut file_tuples: Vec<(String, u64)> = walked_files
         .iter()
         .map(|f| (f.relative_path.clone(), f.size_bytes))
         .collect();
+    if let Some(owners) = codeowners {
+        file_tuples.push((format!("{}#{}", owners.source, owners.content_hash), 0));
+    }
     let current_hash = hash::compute_file_tree_hash(&file_tuples);
 
     // Check stored hash
@@ -997,6 +1294,16 @@ fn phase_index_files(
     let num_files = validated_files.len();
     upsert_files(conn, &validated_files)?;
 
+    conn.execute("DELETE FROM file_owners", [])?;
+    if let Some(owners) = codeowners {
+        let mut stmt = conn.prepare("INSERT OR IGNORE INTO file_owners (path, owner) VALUES (?1, ?2)")?;
+        for (path, _, _, _) in &validated_files {
+            for owner in owners.owners_of(path) {
+                stmt.execute((path, owner))?;
+            }
+        }
+    }
+
     // Store the new file-tree hash
     conn.execute(
         "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('file_tree_hash', ?1)",
@@ -1008,23 +1315,201 @@ fn phase_index_files(
 
 /// Apply config overrides (custom package descriptions).
 fn apply_config_overrides(conn: &Connection, config: &Config) -> Result<()> {
+    conn.execute("DELETE FROM package_tags", [])?;
+    if config.packages.is_empty() {
+        return Ok(());
+    }
+
+    let packages: Vec<(String, String)> = conn
+        .prepare("SELECT name, path FROM packages")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut insert_tag = conn.prepare(
+        "INSERT OR REPLACE INTO package_tags (package, kind, name, value) VALUES (?1, ?2, ?3, ?4)",
+    )?;
     for override_pkg in &config.packages {
-        if let Some(desc) = &override_pkg.description {
-            let rows = conn.execute(
-                "UPDATE packages SET description = ?1 WHERE name = ?2",
-                (desc, &override_pkg.name),
-            )?;
-            if rows == 0 {
-                eprintln!(
-                    "Warning: config override for '{}' matched no packages",
-                    override_pkg.name
-                );
+        let (label, matched): (&str, Vec<&str>) = match (&override_pkg.name, &override_pkg.pattern) {
+            (Some(name), None) => (
+                name,
+                packages
+                    .iter()
+                    .filter(|(n, _)| n == name)
+                    .map(|(n, _)| n.as_str())
+                    .collect(),
+            ),
+            (None, Some(pattern)) => {
+                let glob = glob::Pattern::new(pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid [[packages]] match glob '{}': {}", pattern, e))?;
+                let options = glob::MatchOptions {
+                    require_literal_separator: true,
+                    ..Default::default()
+                };
+                (
+                    pattern,
+                    packages
+                        .iter()
+                        .filter(|(n, p)| glob.matches_with(p, options) || glob.matches(n))
+                        .map(|(n, _)| n.as_str())
+                        .collect(),
+                )
+            }
+            _ => anyhow::bail!("Each [[packages]] override needs exactly one of `name` or `match`"),
+        };
+        if matched.is_empty() {
+            eprintln!("Warning: config override for '{}' matched no packages", label);
+            continue;
+        }
+
+        for name in matched {
+            if let Some(desc) = &override_pkg.description {
+                conn.execute("UPDATE packages SET description = ?1 WHERE name = ?2", (desc, name))?;
+            }
+            for tag in &override_pkg.tags {
+                insert_tag.execute((name, "tag", tag, None::<&str>))?;
+            }
+            for owner in &override_pkg.owners {
+                insert_tag.execute((name, "owner", owner, None::<&str>))?;
+            }
+            // A later override's lifecycle replaces an earlier one
+            if let Some(lifecycle) = &override_pkg.lifecycle {
+                conn.execute(
+                    "DELETE FROM package_tags WHERE package = ?1 AND kind = 'lifecycle'",
+                    [name],
+                )?;
+                insert_tag.execute((name, "lifecycle", lifecycle, None::<&str>))?;
+            }
+            for (key, value) in &override_pkg.labels {
+                insert_tag.execute((name, "label", key, Some(value)))?;
             }
         }
     }
     Ok(())
 }
 
+/// Phase 10: Record commit counts, last-commit dates and top authors per file
+/// and package from `git log`. Skipped when HEAD, the indexed files, the
+/// packages and the window are all unchanged since the last run.
+fn phase_history(conn: &Connection, repo_root: &Path, config: &HistoryConfig) -> Result<()> {
+    let Some(head) = history::head(repo_root) else {
+        eprintln!("Note: skipping git history (not a git repo?)");
+        return clear_history(conn);
+    };
+
+    let packages: Vec<(String, String)> = conn
+        .prepare("SELECT name, path FROM packages")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    let files: HashSet<String> = conn
+        .prepare("SELECT path FROM files")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    let now = chrono::Utc::now();
+    let file_tree_hash: Option<String> = conn
+        .query_row("SELECT value FROM shire_meta WHERE key = 'file_tree_hash'", [], |row| row.get(0))
+        .ok();
+    let package_tuples: Vec<(String, u64)> = packages
+        .iter()
+        .map(|(name, path)| (format!("{name}\0{path}"), 0))
+        .collect();
+    let key = format!(
+        "{head}:{}:{}:{}:{}:{}",
+        config.window_days,
+        config.top_authors,
+        now.format("%Y-%m-%d"),
+        file_tree_hash.unwrap_or_default(),
+        hash::compute_file_tree_hash(&package_tuples),
+    );
+    let stored_key: Option<String> = conn
+        .query_row("SELECT value FROM shire_meta WHERE key = 'history_key'", [], |row| row.get(0))
+        .ok();
+    if stored_key.as_deref() == Some(key.as_str()) {
+        return Ok(());
+    }
+
+    let commits = history::read_log(repo_root)?;
+    let window_start = (now - chrono::Duration::days(i64::from(config.window_days))).timestamp();
+    let (file_stats, package_stats) = history::aggregate(&commits, &files, &packages, window_start);
+
+    clear_history(conn)?;
+    let to_rfc3339 = |ts: Option<i64>| {
+        ts.and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+            .map(|d| d.to_rfc3339())
+    };
+    let mut stmt = conn.prepare(
+        "INSERT INTO file_history (path, commit_count, last_commit_at, top_authors) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (path, stats) in &file_stats {
+        let authors = serde_json::to_string(&stats.top_authors(config.top_authors))?;
+        stmt.execute((path, stats.commit_count as i64, to_rfc3339(stats.last_commit), authors))?;
+    }
+    let mut stmt = conn.prepare(
+        "INSERT INTO package_history (package, commit_count, last_commit_at, top_authors) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (package, stats) in &package_stats {
+        let authors = serde_json::to_string(&stats.top_authors(config.top_authors))?;
+        stmt.execute((package, stats.commit_count as i64, to_rfc3339(stats.last_commit), authors))?;
+    }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('history_key', ?1)",
+        [key],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('history_window_days', ?1)",
+        [config.window_days.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Drop recorded git history, e.g. when `[history]` is disabled.
+fn clear_history(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM file_history", [])?;
+    conn.execute("DELETE FROM package_history", [])?;
+    conn.execute(
+        "DELETE FROM shire_meta WHERE key IN ('history_key', 'history_window_days')",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Record CODEOWNERS owners of each package root as `owner` tags. Runs after
+/// `apply_config_overrides`, which clears `package_tags`; CODEOWNERS owners are
+/// added next to any `owners` set in config.
+fn apply_codeowners(conn: &Connection, codeowners: Option<&codeowners::CodeOwners>) -> Result<()> {
+    let Some(owners) = codeowners else {
+        return Ok(());
+    };
+    let packages: Vec<(String, String)> = conn
+        .prepare("SELECT name, path FROM packages")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO package_tags (package, kind, name, value) VALUES (?1, 'owner', ?2, ?3)",
+    )?;
+    for (name, path) in &packages {
+        for owner in owners.owners_of_dir(path) {
+            stmt.execute((name, owner, &owners.source))?;
+        }
+    }
+    Ok(())
+}
+
+/// Store the configured layering rules so `check_rules` can evaluate them
+/// from the index alone.
+fn store_rules(conn: &Connection, config: &Config) -> Result<()> {
+    if config.rules.is_empty() {
+        conn.execute("DELETE FROM shire_meta WHERE key = 'rules'", [])?;
+    } else {
+        conn.execute(
+            "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('rules', ?1)",
+            [serde_json::to_string(&config.rules)?],
+        )?;
+    }
+    Ok(())
+}
+
 /// Store build metadata in shire_meta.
 fn store_metadata(conn: &Connection, repo_root: &Path, summary: &BuildSummary) -> Result<()> {
     let git_commit = match std::process::Command::new("git")
@@ -1052,6 +1537,12 @@ fn store_metadata(conn: &Connection, repo_root: &Path, summary: &BuildSummary) -
         "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('indexed_at', ?1)",
         [chrono::Utc::now().to_rfc3339()],
     )?;
+    // Lets readers (e.g. the MCP server's freshness check) find the repo
+    // even when the index lives outside it
+    conn.execute(
+        "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('repo_root', ?1)",
+        [repo_root.to_string_lossy()],
+    )?;
     conn.execute(
         "INSERT OR REPLACE INTO shire_meta (key, value) VALUES ('package_count', ?1)",
         [summary.total_packages.to_string()],
@@ -1114,17 +1605,22 @@ fn print_timings(timings: &[(&str, Duration)], total: Duration) {
     eprintln!("  {:<20} {}ms", "total", total.as_millis());
 }
 
-pub fn build_index(repo_root: &Path, config: &Config, force: bool, db_override: Option<&Path>) -> Result<()> {
-    let build_start = Instant::now();
-    let mut timings: Vec<(&str, Duration)> = Vec::new();
-
-    let db_path = if let Some(p) = db_override {
+/// Where the index lives: `--db`, then `db_path` in config, then `.shire/index.db`.
+fn resolve_db_path(repo_root: &Path, config: &Config, db_override: Option<&Path>) -> PathBuf {
+    if let Some(p) = db_override {
         p.to_path_buf()
     } else if let Some(ref p) = config.db_path {
         PathBuf::from(p)
     } else {
         repo_root.join(".shire").join("index.db")
-    };
+    }
+}
+
+pub fn build_index(repo_root: &Path, config: &Config, force: bool, db_override: Option<&Path>) -> Result<()> {
+    let build_start = Instant::now();
+    let mut timings: Vec<(&str, Duration)> = Vec::new();
+
+    let db_path = resolve_db_path(repo_root, config, db_override);
     let conn = db::open_or_create(&db_path)?;
 
     if force {
@@ -1152,6 +1648,9 @@ pub fn build_index(repo_root: &Path, config: &Config, force: bool, db_override:
         Box::new(gradle::GradleKtsParser),
         Box::new(perl::CpanfileParser),
         Box::new(ruby::RubyParser),
+        Box::new(dotnet::CsprojParser),
+        Box::new(dotnet::FsprojParser),
+        Box::new(swift::SwiftPackageParser),
     ];
 
     // Phase 1: Walk manifests
@@ -1166,6 +1665,8 @@ pub fn build_index(repo_root: &Path, config: &Config, force: bool, db_override:
         go_dirs: collect_go_workspace_context(&walked),
         maven_parents: maven::collect_maven_parent_context(&walked),
         gradle_settings: collect_gradle_settings_context(&walked),
+        dotnet_solutions: collect_dotnet_solution_context(&walked),
+        npm: collect_npm_workspace_context(&walked),
     };
     timings.push(("workspace-context", t.elapsed()));
 
@@ -1254,6 +1755,16 @@ pub fn build_index(repo_root: &Path, config: &Config, force: bool, db_override:
     })?;
     timings.push(("recompute-internals", t.elapsed()));
 
+    // Phase 5.5: Resolve lockfile versions (transaction-wrapped)
+    let t = Instant::now();
+    with_transaction(&conn, || {
+        if num_added > 0 || num_changed > 0 || num_removed > 0 {
+            phase_lockfiles(&conn, &walked)?;
+        }
+        Ok(())
+    })?;
+    timings.push(("lockfiles", t.elapsed()));
+
     // Phase 6: Store manifest hashes (transaction-wrapped)
     let t = Instant::now();
     with_transaction(&conn, || {
@@ -1269,16 +1780,28 @@ pub fn build_index(repo_root: &Path, config: &Config, force: bool, db_override:
     })?;
     timings.push(("extract-symbols", t.elapsed()));
 
-    // Phase 9: Index files (transaction-wrapped)
+    // Phase 9: Index files and their CODEOWNERS owners (transaction-wrapped)
     let t = Instant::now();
+    let codeowners = codeowners::load(repo_root)?;
     let num_files = with_transaction(&conn, || {
-        phase_index_files(&conn, repo_root, config)
+        phase_index_files(&conn, repo_root, config, codeowners.as_ref())
     })?;
     timings.push(("index-files", t.elapsed()));
 
-    // Post-build: config overrides, metadata, summary (transaction-wrapped)
+    // Phase 10: Git history (opt-in, transaction-wrapped)
+    if config.history.enabled {
+        let t = Instant::now();
+        with_transaction(&conn, || phase_history(&conn, repo_root, &config.history))?;
+        timings.push(("git-history", t.elapsed()));
+    } else {
+        with_transaction(&conn, || clear_history(&conn))?;
+    }
+
+    // Post-build: config overrides, layering rules, metadata, summary (transaction-wrapped)
     with_transaction(&conn, || {
-        apply_config_overrides(&conn, config)
+        apply_config_overrides(&conn, config)?;
+        apply_codeowners(&conn, codeowners.as_ref())?;
+        store_rules(&conn, config)
     })?;
 
     let total_packages: i64 = conn.query_row("SELECT COUNT(*) FROM packages", [], |row| row.get(0))?;
@@ -1674,6 +2197,77 @@ anyhow = "1"
         assert_eq!(version_req.as_deref(), Some("*"));
     }
 
+    #[test]
+    fn test_npm_workspace_membership_and_version_resolution() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            br#"{"name": "root", "private": true, "workspaces": ["packages/*"]}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("yarn.lock"), "").unwrap();
+
+        for (rel, json) in [
+            (
+                "packages/app",
+                r#"{"name": "app", "version": "1.0.0", "dependencies": {"shared": "workspace:^"}}"#,
+            ),
+            ("packages/shared", r#"{"name": "shared", "version": "2.3.0"}"#),
+            ("tools/stray", r#"{"name": "stray", "version": "0.1.0"}"#),
+        ] {
+            let pkg_dir = dir.path().join(rel);
+            fs::create_dir_all(&pkg_dir).unwrap();
+            fs::write(pkg_dir.join("package.json"), json).unwrap();
+        }
+
+        // A separate pnpm workspace nested under web/
+        let web = dir.path().join("web");
+        fs::create_dir_all(web.join("apps/site")).unwrap();
+        fs::write(web.join("pnpm-workspace.yaml"), "packages:\n  - 'apps/*'\n").unwrap();
+        fs::write(
+            web.join("apps/site/package.json"),
+            r#"{"name": "site", "version": "0.0.1", "dependencies": {"shared": "workspace:*"}}"#,
+        )
+        .unwrap();
+
+        let config = Config::default();
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        let conn = db::open_readonly(&db_path).unwrap();
+
+        let metadata = |name: &str| -> serde_json::Value {
+            let raw: String = conn
+                .query_row("SELECT metadata FROM packages WHERE name = ?1", [name], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            serde_json::from_str(&raw).unwrap()
+        };
+        assert_eq!(
+            metadata("app")["npm_workspace"],
+            serde_json::json!({"root": "", "manager": "yarn"})
+        );
+        assert_eq!(metadata("root")["npm_workspace"]["root"], "");
+        assert_eq!(metadata("stray")["npm_workspace_orphan"], true);
+        assert_eq!(
+            metadata("site")["npm_workspace"],
+            serde_json::json!({"root": "web", "manager": "pnpm"})
+        );
+
+        let version_req = |package: &str| -> Option<String> {
+            conn.query_row(
+                "SELECT version_req FROM dependencies WHERE package = ?1 AND dependency = 'shared'",
+                [package],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(version_req("app").as_deref(), Some("^2.3.0"));
+        // `shared` isn't a member of the web/ workspace, so the range stays unresolved
+        assert_eq!(version_req("site").as_deref(), Some("*"));
+    }
+
     #[test]
     fn test_go_work_metadata() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -1770,6 +2364,407 @@ anyhow = "1"
         assert!(total > 0);
     }
 
+    #[test]
+    fn test_dotnet_projects_and_solution_metadata() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        // Solution at dotnet/ listing only the API project
+        let sln_dir = dir.path().join("dotnet");
+        fs::create_dir_all(sln_dir.join("src/Api")).unwrap();
+        fs::create_dir_all(sln_dir.join("src/Core")).unwrap();
+        fs::write(
+            sln_dir.join("Shop.sln"),
+            "Project(\"{9A19103F-16F7-4668-BE54-9A1E7A4F7556}\") = \"Api\", \"src\\Api\\Api.csproj\", \"{1}\"\nEndProject\n",
+        )
+        .unwrap();
+        fs::write(
+            sln_dir.join("src/Api/Api.csproj"),
+            r#"<Project Sdk="Microsoft.NET.Sdk"><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup><ItemGroup><ProjectReference Include="..\Core\Core.csproj" /><PackageReference Include="Serilog" Version="3.1.1" /></ItemGroup></Project>"#,
+        )
+        .unwrap();
+        fs::write(
+            sln_dir.join("src/Core/Core.csproj"),
+            r#"<Project Sdk="Microsoft.NET.Sdk"><PropertyGroup><TargetFramework>net8.0</TargetFramework></PropertyGroup></Project>"#,
+        )
+        .unwrap();
+
+        let config = Config::default();
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        let conn = db::open_readonly(&db_path).unwrap();
+
+        let kinds: Vec<(String, String)> = conn
+            .prepare("SELECT name, kind FROM packages ORDER BY name")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![("Api".into(), "dotnet".into()), ("Core".into(), "dotnet".into())]
+        );
+
+        let core_internal: bool = conn
+            .query_row(
+                "SELECT is_internal FROM dependencies WHERE package = 'Api' AND dependency = 'Core'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(core_internal);
+
+        let api_meta: String = conn
+            .query_row("SELECT metadata FROM packages WHERE name = 'Api'", [], |row| row.get(0))
+            .unwrap();
+        let meta: serde_json::Value = serde_json::from_str(&api_meta).unwrap();
+        assert_eq!(meta["dotnet_solutions"], serde_json::json!(["Shop"]));
+        assert_eq!(meta["target_frameworks"], serde_json::json!(["net8.0"]));
+
+        let core_meta: String = conn
+            .query_row("SELECT metadata FROM packages WHERE name = 'Core'", [], |row| row.get(0))
+            .unwrap();
+        let meta: serde_json::Value = serde_json::from_str(&core_meta).unwrap();
+        assert!(meta.get("dotnet_solutions").is_none());
+    }
+
+    #[test]
+    fn test_swift_packages_and_symbols() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let app = dir.path().join("ios/App");
+        let kit = dir.path().join("ios/CoreKit");
+        fs::create_dir_all(app.join("Sources/App")).unwrap();
+        fs::create_dir_all(kit.join("Sources/CoreKit")).unwrap();
+        fs::write(
+            app.join("Package.swift"),
+            r#"// swift-tools-version:5.9
+import PackageDescription
+let package = Package(
+    name: "App",
+    dependencies: [
+        .package(path: "../CoreKit"),
+        .package(url: "https://github.com/apple/swift-log.git", from: "1.5.0"),
+    ],
+    targets: [.executableTarget(name: "App", dependencies: ["CoreKit"])]
+)"#,
+        )
+        .unwrap();
+        fs::write(
+            kit.join("Package.swift"),
+            r#"let package = Package(name: "CoreKit", targets: [.target(name: "CoreKit")])"#,
+        )
+        .unwrap();
+        fs::write(
+            kit.join("Sources/CoreKit/Clock.swift"),
+            "public protocol Clock {\n    func now() -> Date\n}\n",
+        )
+        .unwrap();
+
+        let config = Config::default();
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        let conn = db::open_readonly(&db_path).unwrap();
+
+        let deps: Vec<(String, bool)> = conn
+            .prepare("SELECT dependency, is_internal FROM dependencies WHERE package = 'App' ORDER BY dependency")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            deps,
+            vec![("CoreKit".into(), true), ("swift-log".into(), false)]
+        );
+
+        let symbols: Vec<(String, String)> = conn
+            .prepare("SELECT name, kind FROM symbols WHERE package = 'CoreKit' ORDER BY line")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            symbols,
+            vec![
+                ("Clock".into(), "interface".into()),
+                ("now".into(), "method".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lockfile_resolved_dependencies() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            br#"{"name": "root", "private": true, "workspaces": ["packages/*"]}"#,
+        )
+        .unwrap();
+        for (rel, json) in [
+            (
+                "packages/app",
+                r#"{"name": "app", "version": "1.0.0", "dependencies": {"shared": "*", "debug": "^4.3.0"}}"#,
+            ),
+            ("packages/shared", r#"{"name": "shared", "version": "2.0.0"}"#),
+        ] {
+            let pkg_dir = dir.path().join(rel);
+            fs::create_dir_all(&pkg_dir).unwrap();
+            fs::write(pkg_dir.join("package.json"), json).unwrap();
+        }
+        let lock = |debug_version: &str| {
+            format!(
+                r#"{{
+  "lockfileVersion": 3,
+  "packages": {{
+    "": {{ "name": "root", "workspaces": ["packages/*"] }},
+    "node_modules/shared": {{ "resolved": "packages/shared", "link": true }},
+    "node_modules/debug": {{ "version": "{debug_version}", "dependencies": {{ "ms": "2.1.2" }} }},
+    "node_modules/ms": {{ "version": "2.1.2" }},
+    "packages/app": {{ "name": "app", "version": "1.0.0", "dependencies": {{ "shared": "*", "debug": "^4.3.0" }} }},
+    "packages/shared": {{ "name": "shared", "version": "2.0.0" }}
+  }}
+}}"#
+            )
+        };
+        fs::write(dir.path().join("package-lock.json"), lock("4.3.4")).unwrap();
+
+        let config = Config::default();
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        let resolved = || -> Vec<(String, String, bool, String)> {
+            let conn = db::open_readonly(&db_path).unwrap();
+            conn.prepare(
+                "SELECT dependency, version, is_direct, lockfile FROM resolved_dependencies
+                 WHERE package = 'app' ORDER BY dependency",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+        };
+        // The internal `shared` link isn't reported
+        assert_eq!(
+            resolved(),
+            vec![
+                ("debug".into(), "4.3.4".into(), true, "package-lock.json".into()),
+                ("ms".into(), "2.1.2".into(), false, "package-lock.json".into()),
+            ]
+        );
+
+        // A lockfile-only change is picked up incrementally
+        fs::write(dir.path().join("package-lock.json"), lock("4.3.5")).unwrap();
+        build_index(dir.path(), &config, false, None).unwrap();
+        assert_eq!(resolved()[0].1, "4.3.5");
+
+        let conn = db::open_readonly(&db_path).unwrap();
+        let deps = crate::db::queries::package_dependencies(&conn, "app", false, crate::db::queries::Page::ALL).unwrap();
+        let debug = deps.iter().find(|d| d.dependency == "debug").unwrap();
+        assert_eq!(debug.resolved_versions, ["4.3.5"]);
+    }
+
+    #[test]
+    fn test_package_overrides_tags_and_match() {
+        let dir = tempfile::TempDir::new().unwrap();
+        create_test_monorepo(dir.path());
+        let config: Config = toml::from_str(
+            r#"
+[[packages]]
+match = "services/*"
+tags = ["service"]
+lifecycle = "active"
+
+[[packages]]
+name = "auth-service"
+description = "Legacy auth"
+tags = ["deprecated"]
+owners = ["@acme/identity"]
+lifecycle = "deprecated"
+labels = { tier = "1" }
+"#,
+        )
+        .unwrap();
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        let conn = db::open_readonly(&db_path).unwrap();
+
+        let auth = crate::db::queries::get_package(&conn, "auth-service").unwrap().unwrap();
+        assert_eq!(auth.description.as_deref(), Some("Legacy auth"));
+        assert_eq!(auth.tags, vec!["deprecated", "service"]);
+        assert_eq!(auth.owners, vec!["@acme/identity"]);
+        // The later override wins
+        assert_eq!(auth.lifecycle.as_deref(), Some("deprecated"));
+        assert_eq!(auth.labels.get("tier").map(String::as_str), Some("1"));
+
+        let services: Vec<String> = crate::db::queries::list_packages(&conn, None, Some("service"), crate::db::queries::Page::ALL)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(services.len(), 2);
+        assert!(!services.contains(&"shared-types".to_string()));
+
+        let deprecated = crate::db::queries::search_packages(&conn, "auth", Some("deprecated"), false, crate::db::queries::Page::ALL).unwrap();
+        assert_eq!(deprecated.len(), 1);
+        assert!(crate::db::queries::search_packages(&conn, "shared", Some("deprecated"), false, crate::db::queries::Page::ALL).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_package_override_needs_name_or_match() {
+        let dir = tempfile::TempDir::new().unwrap();
+        create_test_monorepo(dir.path());
+        let config: Config = toml::from_str("[[packages]]\ntags = [\"orphan\"]\n").unwrap();
+        assert!(build_index(dir.path(), &config, false, None).is_err());
+    }
+
+    #[test]
+    fn test_codeowners_file_and_package_owners() {
+        let dir = tempfile::TempDir::new().unwrap();
+        create_test_monorepo(dir.path());
+        fs::write(dir.path().join("services/auth/index.ts"), "export {}\n").unwrap();
+        fs::create_dir_all(dir.path().join(".github")).unwrap();
+        fs::write(
+            dir.path().join(".github/CODEOWNERS"),
+            "* @acme/platform\n/services/auth/ @acme/identity\n*.ts @acme/frontend\n",
+        )
+        .unwrap();
+        let config: Config = toml::from_str(
+            "[[packages]]\nname = \"shared-types\"\nowners = [\"@acme/types\"]\n",
+        )
+        .unwrap();
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        {
+            let conn = db::open_readonly(&db_path).unwrap();
+            let file = crate::db::queries::who_owns(&conn, "services/auth/index.ts").unwrap().unwrap();
+            assert_eq!(file.owners, vec!["@acme/frontend"]);
+            assert_eq!(file.package.as_deref(), Some("auth-service"));
+            assert_eq!(file.package_owners, vec!["@acme/identity"]);
+
+            let auth_dir = crate::db::queries::who_owns(&conn, "services/auth/").unwrap().unwrap();
+            assert_eq!(auth_dir.owners, vec!["@acme/frontend", "@acme/identity"]);
+
+            // Config owners and CODEOWNERS owners both apply
+            let shared = crate::db::queries::get_package(&conn, "shared-types").unwrap().unwrap();
+            assert_eq!(shared.owners, vec!["@acme/platform", "@acme/types"]);
+
+            let identity: Vec<String> = crate::db::queries::packages_by_owner(&conn, "@ACME/identity", crate::db::queries::Page::ALL)
+                .unwrap()
+                .into_iter()
+                .map(|p| p.name)
+                .collect();
+            assert_eq!(identity, vec!["auth-service"]);
+            assert!(crate::db::queries::who_owns(&conn, "nowhere").unwrap().is_none());
+        }
+
+        // Editing only CODEOWNERS refreshes file owners on the next build
+        fs::write(dir.path().join(".github/CODEOWNERS"), "* @acme/everyone\n").unwrap();
+        build_index(dir.path(), &config, false, None).unwrap();
+        let conn = db::open_readonly(&db_path).unwrap();
+        let file = crate::db::queries::who_owns(&conn, "services/auth/index.ts").unwrap().unwrap();
+        assert_eq!(file.owners, vec!["@acme/everyone"]);
+    }
+
+    fn git_commit_at(dir: &Path, author: &str, date: &str) {
+        let run = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .env("GIT_AUTHOR_NAME", author)
+                .env("GIT_AUTHOR_EMAIL", "dev@example.com")
+                .env("GIT_COMMITTER_NAME", author)
+                .env("GIT_COMMITTER_EMAIL", "dev@example.com")
+                .env("GIT_AUTHOR_DATE", date)
+                .env("GIT_COMMITTER_DATE", date)
+                .output()
+                .unwrap();
+            assert!(status.status.success(), "git {args:?} failed");
+        };
+        run(&["add", "-A"]);
+        run(&["commit", "-q", "-m", "change"]);
+    }
+
+    #[test]
+    fn test_git_history_phase() {
+        let dir = tempfile::TempDir::new().unwrap();
+        create_test_monorepo(dir.path());
+        let init = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        assert!(init.status.success());
+        git_commit_at(dir.path(), "Ada", "2015-01-01T00:00:00Z");
+        let recent = chrono::Utc::now() - chrono::Duration::days(3);
+        for i in 0..2 {
+            fs::write(dir.path().join("services/auth/index.ts"), format!("export const v = {i};\n")).unwrap();
+            git_commit_at(dir.path(), "Grace", &recent.to_rfc3339());
+        }
+
+        let mut config = Config::default();
+        config.history.enabled = true;
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        let conn = db::open_readonly(&db_path).unwrap();
+        let hotspots = crate::db::queries::churn_hotspots(&conn, false, None, crate::db::queries::Page::new(10, 0)).unwrap();
+        assert_eq!(hotspots.len(), 1, "only auth-service changed within the window");
+        assert_eq!(hotspots[0].package.as_deref(), Some("auth-service"));
+        assert_eq!(hotspots[0].commit_count, 2);
+        assert_eq!(hotspots[0].top_authors[0].name, "Grace");
+
+        let files = crate::db::queries::churn_hotspots(&conn, true, Some("auth-service"), crate::db::queries::Page::new(10, 0)).unwrap();
+        assert_eq!(files[0].path, "services/auth/index.ts");
+
+        let stale: Vec<String> = crate::db::queries::stale_packages(&conn, 365, crate::db::queries::Page::ALL)
+            .unwrap()
+            .into_iter()
+            .filter_map(|r| r.package)
+            .collect();
+        assert_eq!(stale, vec!["gateway", "shared-types"]);
+        drop(conn);
+
+        // Disabling the phase drops the recorded history
+        build_index(dir.path(), &Config::default(), false, None).unwrap();
+        let conn = db::open_readonly(&db_path).unwrap();
+        assert!(crate::db::queries::churn_hotspots(&conn, false, None, crate::db::queries::Page::new(10, 0)).is_err());
+    }
+
+    #[test]
+    fn test_layering_rules_stored_in_index() {
+        let dir = tempfile::TempDir::new().unwrap();
+        create_test_monorepo(dir.path());
+        let mut config: Config = toml::from_str(
+            r#"
+[[rules]]
+name = "no-services"
+from = { path = "packages/**" }
+deny = [{ path = "services/**" }]
+"#,
+        )
+        .unwrap();
+        build_index(dir.path(), &config, false, None).unwrap();
+
+        let db_path = dir.path().join(".shire/index.db");
+        let conn = db::open_readonly(&db_path).unwrap();
+        let rules = crate::db::queries::stored_rules(&conn).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].name, "no-services");
+        drop(conn);
+
+        // Removing the rules from config clears them on the next build
+        config.rules.clear();
+        build_index(dir.path(), &config, false, None).unwrap();
+        let conn = db::open_readonly(&db_path).unwrap();
+        assert!(crate::db::queries::stored_rules(&conn).unwrap().is_empty());
+    }
+
     #[test]
     fn test_mtime_precheck_skips_unchanged() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -1,5 +1,7 @@
 use super::manifest::{DepInfo, DepKind, ManifestParser, PackageInfo};
+use super::npm_workspace;
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::Path;
 
 pub struct NpmParser;
@@ -10,44 +12,73 @@ impl ManifestParser for NpmParser {
     }
 
     fn parse(&self, manifest_path: &Path, relative_dir: &str) -> Result<PackageInfo> {
-        let content = std::fs::read_to_string(manifest_path)?;
-        let json: serde_json::Value = serde_json::from_str(&content)?;
-
-        let name = json["name"]
-            .as_str()
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| relative_dir.replace('/', "-"));
-
-        let version = json["version"].as_str().map(|s| s.to_string());
-        let description = json["description"].as_str().map(|s| s.to_string());
-
-        let mut dependencies = Vec::new();
-
-        extract_deps(&json, "dependencies", DepKind::Runtime, &mut dependencies);
-        extract_deps(&json, "devDependencies", DepKind::Dev, &mut dependencies);
-        extract_deps(&json, "peerDependencies", DepKind::Peer, &mut dependencies);
-
-        Ok(PackageInfo {
-            name,
-            path: relative_dir.to_string(),
-            kind: "npm",
-            version,
-            description,
-            metadata: None,
-            dependencies,
-        })
+        parse_package_json(manifest_path, relative_dir, None)
     }
 }
 
+impl NpmParser {
+    /// Parse a workspace member, resolving `workspace:` ranges against the
+    /// versions of the other members of its workspace.
+    pub fn parse_with_workspace_versions(
+        &self,
+        manifest_path: &Path,
+        relative_dir: &str,
+        member_versions: &HashMap<String, String>,
+    ) -> Result<PackageInfo> {
+        parse_package_json(manifest_path, relative_dir, Some(member_versions))
+    }
+}
+
+fn parse_package_json(
+    manifest_path: &Path,
+    relative_dir: &str,
+    member_versions: Option<&HashMap<String, String>>,
+) -> Result<PackageInfo> {
+    let content = std::fs::read_to_string(manifest_path)?;
+    let json: serde_json::Value = serde_json::from_str(&content)?;
+
+    let name = json["name"]
+        .as_str()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| relative_dir.replace('/', "-"));
+
+    let version = json["version"].as_str().map(|s| s.to_string());
+    let description = json["description"].as_str().map(|s| s.to_string());
+
+    let mut dependencies = Vec::new();
+
+    extract_deps(&json, "dependencies", DepKind::Runtime, member_versions, &mut dependencies);
+    extract_deps(&json, "devDependencies", DepKind::Dev, member_versions, &mut dependencies);
+    extract_deps(&json, "peerDependencies", DepKind::Peer, member_versions, &mut dependencies);
+
+    Ok(PackageInfo {
+        name,
+        path: relative_dir.to_string(),
+        kind: "npm",
+        version,
+        description,
+        metadata: None,
+        dependencies,
+    })
+}
+
 fn extract_deps(
     json: &serde_json::Value,
     section: &str,
     kind: DepKind,
+    member_versions: Option<&HashMap<String, String>>,
     out: &mut Vec<DepInfo>,
 ) {
     if let Some(deps) = json[section].as_object() {
         for (name, ver) in deps {
-            let version_req = ver.as_str().map(|s| strip_workspace_protocol(s).to_string());
+            let version_req = ver.as_str().map(|s| match member_versions {
+                Some(versions) => npm_workspace::resolve_workspace_range(
+                    s,
+                    versions.get(name).map(String::as_str),
+                )
+                .unwrap_or_else(|| s.to_string()),
+                None => strip_workspace_protocol(s).to_string(),
+            });
             out.push(DepInfo {
                 name: name.clone(),
                 version_req,
@@ -174,6 +205,47 @@ mod tests {
         assert_eq!(find_dep("test-helpers"), "^2.0.0");
     }
 
+    #[test]
+    fn test_parse_with_workspace_versions_resolves_members() {
+        let dir = TempDir::new().unwrap();
+        let path = write_manifest(
+            dir.path(),
+            r#"{
+                "name": "app",
+                "dependencies": {
+                    "shared-utils": "workspace:*",
+                    "shared-types": "workspace:^",
+                    "missing": "workspace:~",
+                    "express": "^4.18.0"
+                }
+            }"#,
+        );
+
+        let versions: HashMap<String, String> = [
+            ("shared-utils".to_string(), "1.4.0".to_string()),
+            ("shared-types".to_string(), "0.2.1".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let info = NpmParser
+            .parse_with_workspace_versions(&path, "packages/app", &versions)
+            .unwrap();
+
+        let find_dep = |name: &str| -> String {
+            info.dependencies
+                .iter()
+                .find(|d| d.name == name)
+                .unwrap()
+                .version_req
+                .clone()
+                .unwrap()
+        };
+        assert_eq!(find_dep("shared-utils"), "1.4.0");
+        assert_eq!(find_dep("shared-types"), "^0.2.1");
+        assert_eq!(find_dep("missing"), "~");
+        assert_eq!(find_dep("express"), "^4.18.0");
+    }
+
     #[test]
     fn test_parse_no_name_falls_back_to_dir() {
         let dir = TempDir::new().unwrap();
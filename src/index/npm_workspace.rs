@@ -0,0 +1,263 @@
+use anyhow::Result;
+use glob::{MatchOptions, Pattern};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// An npm, yarn or pnpm workspace declared by a root `package.json` `workspaces`
+/// field or a `pnpm-workspace.yaml` file.
+#[derive(Debug, Clone)]
+pub struct NpmWorkspace {
+    /// Directory of the workspace root, relative to the repo root.
+    pub root_dir: String,
+    /// Package manager that owns the workspace: `npm`, `yarn` or `pnpm`.
+    pub manager: String,
+    /// Member globs relative to the workspace root; `!`-prefixed globs exclude.
+    pub patterns: Vec<String>,
+}
+
+impl NpmWorkspace {
+    /// Whether the package at `dir` (relative to the repo root) is a member of this
+    /// workspace. The workspace root itself always counts as a member.
+    pub fn contains(&self, dir: &str) -> bool {
+        let relative = if self.root_dir.is_empty() {
+            dir
+        } else if dir == self.root_dir {
+            return true;
+        } else {
+            match dir
+                .strip_prefix(self.root_dir.as_str())
+                .and_then(|d| d.strip_prefix('/'))
+            {
+                Some(d) => d,
+                None => return false,
+            }
+        };
+        if relative.is_empty() {
+            return true;
+        }
+
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::default()
+        };
+        let mut included = false;
+        for pattern in &self.patterns {
+            let (negated, glob) = match pattern.strip_prefix('!') {
+                Some(p) => (true, p),
+                None => (false, pattern.as_str()),
+            };
+            let glob = normalize_pattern(glob);
+            let matched = Pattern::new(&glob)
+                .map(|p| p.matches_with(relative, options))
+                .unwrap_or(false);
+            if matched {
+                included = !negated;
+            }
+        }
+        included
+    }
+}
+
+/// Strip `./` prefixes and trailing slashes so globs compare against bare relative dirs.
+fn normalize_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim();
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    pattern.trim_end_matches('/').to_string()
+}
+
+/// Read the `workspaces` field of a `package.json`: either an array of globs or
+/// yarn's `{ "packages": [...] }` object form. Returns `None` when absent.
+pub fn parse_package_json_workspaces(json: &serde_json::Value) -> Option<Vec<String>> {
+    let workspaces = match &json["workspaces"] {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(obj) => obj.get("packages")?.as_array()?,
+        _ => return None,
+    };
+    Some(
+        workspaces
+            .iter()
+            .filter_map(|w| w.as_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+/// Parse the `packages:` list of a `pnpm-workspace.yaml` file.
+pub fn parse_pnpm_workspace(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let doc: serde_yaml::Value = serde_yaml::from_str(&content)?;
+    let patterns = doc
+        .get("packages")
+        .and_then(|p| p.as_sequence())
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.as_str().map(str::to_string))
+        .collect();
+    Ok(patterns)
+}
+
+/// Infer the package manager from the root `package.json` `packageManager` field
+/// (`"yarn@4.1.0"`), falling back to the lockfile next to it.
+pub fn detect_manager(json: &serde_json::Value, root_abs_dir: &Path) -> String {
+    if let Some(pm) = json["packageManager"].as_str() {
+        let name = pm.split('@').next().unwrap_or(pm);
+        if !name.is_empty() {
+            return name.to_string();
+        }
+    }
+    if root_abs_dir.join("yarn.lock").exists() {
+        "yarn".to_string()
+    } else if root_abs_dir.join("pnpm-lock.yaml").exists() {
+        "pnpm".to_string()
+    } else {
+        "npm".to_string()
+    }
+}
+
+/// Resolve a `workspace:` protocol range against the member's actual version:
+/// `workspace:*` → `1.2.3`, `workspace:^` → `^1.2.3`, `workspace:~` → `~1.2.3`.
+/// Explicit ranges (`workspace:^1.0.0`) keep their range. Returns `None` for
+/// non-workspace specifiers.
+pub fn resolve_workspace_range(spec: &str, member_version: Option<&str>) -> Option<String> {
+    let range = spec.strip_prefix("workspace:")?;
+    let resolved = match (range, member_version) {
+        ("*", Some(v)) => v.to_string(),
+        ("^" | "~", Some(v)) => format!("{}{}", range, v),
+        _ => range.to_string(),
+    };
+    Some(resolved)
+}
+
+/// Workspace context for npm packages, collected in Phase 1.5.
+#[derive(Debug, Default)]
+pub struct NpmWorkspaceContext {
+    pub workspaces: Vec<NpmWorkspace>,
+    /// Member versions keyed by workspace root dir, then package name.
+    pub member_versions: HashMap<String, HashMap<String, String>>,
+}
+
+impl NpmWorkspaceContext {
+    /// The innermost workspace containing the package at `dir`, if any.
+    pub fn workspace_for(&self, dir: &str) -> Option<&NpmWorkspace> {
+        self.workspaces
+            .iter()
+            .filter(|ws| ws.contains(dir))
+            .max_by_key(|ws| ws.root_dir.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace(root: &str, patterns: &[&str]) -> NpmWorkspace {
+        NpmWorkspace {
+            root_dir: root.to_string(),
+            manager: "npm".to_string(),
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_contains_single_star_and_double_star() {
+        let ws = workspace("", &["packages/*", "./apps/**"]);
+        assert!(ws.contains(""));
+        assert!(ws.contains("packages/app"));
+        assert!(!ws.contains("packages/app/nested"));
+        assert!(ws.contains("apps/web"));
+        assert!(ws.contains("apps/mobile/ios"));
+        assert!(!ws.contains("tools/script"));
+    }
+
+    #[test]
+    fn test_contains_negation_and_nested_root() {
+        let ws = workspace("frontend", &["packages/*", "!packages/legacy"]);
+        assert!(ws.contains("frontend"));
+        assert!(ws.contains("frontend/packages/ui"));
+        assert!(!ws.contains("frontend/packages/legacy"));
+        assert!(!ws.contains("packages/ui"));
+        assert!(!ws.contains("frontend-other/packages/ui"));
+    }
+
+    #[test]
+    fn test_parse_package_json_workspaces_forms() {
+        let array = serde_json::json!({"workspaces": ["packages/*"]});
+        assert_eq!(
+            parse_package_json_workspaces(&array),
+            Some(vec!["packages/*".to_string()])
+        );
+        let yarn =
+            serde_json::json!({"workspaces": {"packages": ["libs/*"], "nohoist": ["**/react"]}});
+        assert_eq!(
+            parse_package_json_workspaces(&yarn),
+            Some(vec!["libs/*".to_string()])
+        );
+        assert_eq!(
+            parse_package_json_workspaces(&serde_json::json!({"name": "x"})),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_pnpm_workspace() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("pnpm-workspace.yaml");
+        std::fs::write(
+            &path,
+            "# monorepo\npackages:\n  - 'packages/*'\n  - \"apps/**\" # apps\n  - '!**/test/**'\ncatalog:\n  react: ^18.2.0\n",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_pnpm_workspace(&path).unwrap(),
+            vec!["packages/*", "apps/**", "!**/test/**"]
+        );
+
+        // Flow sequences, and `#` inside a quoted pattern
+        std::fs::write(&path, "packages: ['packages/*', \"apps/#legacy/*\"] # all\n").unwrap();
+        assert_eq!(
+            parse_pnpm_workspace(&path).unwrap(),
+            vec!["packages/*", "apps/#legacy/*"]
+        );
+
+        std::fs::write(&path, "# no packages yet\n").unwrap();
+        assert!(parse_pnpm_workspace(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_workspace_range() {
+        assert_eq!(
+            resolve_workspace_range("workspace:*", Some("1.2.3")).as_deref(),
+            Some("1.2.3")
+        );
+        assert_eq!(
+            resolve_workspace_range("workspace:^", Some("1.2.3")).as_deref(),
+            Some("^1.2.3")
+        );
+        assert_eq!(
+            resolve_workspace_range("workspace:~", Some("1.2.3")).as_deref(),
+            Some("~1.2.3")
+        );
+        assert_eq!(
+            resolve_workspace_range("workspace:^2.0.0", Some("2.1.0")).as_deref(),
+            Some("^2.0.0")
+        );
+        // Unknown member keeps the bare range
+        assert_eq!(
+            resolve_workspace_range("workspace:*", None).as_deref(),
+            Some("*")
+        );
+        assert_eq!(resolve_workspace_range("^4.18.0", Some("1.0.0")), None);
+    }
+
+    #[test]
+    fn test_detect_manager() {
+        let dir = TempDir::new().unwrap();
+        let json = serde_json::json!({"packageManager": "pnpm@8.15.0"});
+        assert_eq!(detect_manager(&json, dir.path()), "pnpm");
+
+        let json = serde_json::json!({});
+        assert_eq!(detect_manager(&json, dir.path()), "npm");
+        std::fs::write(dir.path().join("yarn.lock"), "").unwrap();
+        assert_eq!(detect_manager(&json, dir.path()), "yarn");
+    }
+}
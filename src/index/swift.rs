@@ -0,0 +1,368 @@
+use super::manifest::{DepInfo, DepKind, ManifestParser, PackageInfo};
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::path::Path;
+
+pub struct SwiftPackageParser;
+
+impl ManifestParser for SwiftPackageParser {
+    fn filename(&self) -> &'static str {
+        "Package.swift"
+    }
+
+    fn parse(&self, manifest_path: &Path, relative_dir: &str) -> Result<PackageInfo> {
+        let content = std::fs::read_to_string(manifest_path)?;
+        let content = strip_comments(&content);
+
+        let name = extract_package_name(&content)
+            .ok_or_else(|| anyhow!("No Package(name:) in Package.swift"))?;
+
+        let manifest_dir = manifest_path.parent().unwrap_or(Path::new("."));
+        let dependencies = call_arguments(&content, ".package")
+            .iter()
+            .filter_map(|args| parse_package_dependency(args, manifest_dir))
+            .collect();
+
+        let mut targets = Vec::new();
+        let mut test_targets = Vec::new();
+        for call in [
+            ".target",
+            ".executableTarget",
+            ".macro",
+            ".plugin",
+            ".testTarget",
+        ] {
+            for args in call_arguments(&content, call) {
+                if let Some(target) = string_argument(&args, "name") {
+                    if call == ".testTarget" {
+                        test_targets.push(target);
+                    } else {
+                        targets.push(target);
+                    }
+                }
+            }
+        }
+
+        let mut metadata = serde_json::Map::new();
+        if !targets.is_empty() {
+            metadata.insert("targets".into(), serde_json::json!(targets));
+        }
+        if !test_targets.is_empty() {
+            metadata.insert("test_targets".into(), serde_json::json!(test_targets));
+        }
+        if let Some(tools) = extract_tools_version(&content) {
+            metadata.insert("swift_tools_version".into(), serde_json::json!(tools));
+        }
+
+        Ok(PackageInfo {
+            name,
+            path: relative_dir.to_string(),
+            kind: "swift",
+            version: None,
+            description: None,
+            metadata: (!metadata.is_empty()).then_some(serde_json::Value::Object(metadata)),
+            dependencies,
+        })
+    }
+}
+
+/// Read the `name:` of the `Package(...)` initializer from a Package.swift file.
+pub fn read_package_name(manifest_path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(manifest_path).ok()?;
+    extract_package_name(&strip_comments(&content))
+}
+
+fn extract_package_name(content: &str) -> Option<String> {
+    let args = call_arguments(content, "Package").into_iter().next()?;
+    string_argument(&args, "name")
+}
+
+/// `// swift-tools-version:5.9` lives in a comment, so read it from the raw first line.
+fn extract_tools_version(content: &str) -> Option<String> {
+    let re = Regex::new(r"(?m)^//\s*swift-tools-version\s*:\s*([0-9.]+)").unwrap();
+    re.captures(content).map(|c| c[1].to_string())
+}
+
+/// `.package(url: "https://github.com/apple/swift-log.git", from: "1.5.0")` → external `swift-log`;
+/// `.package(path: "../SharedKit")` → the package declared in that directory (internal).
+fn parse_package_dependency(args: &str, manifest_dir: &Path) -> Option<DepInfo> {
+    if let Some(path) = string_argument(args, "path") {
+        let name = string_argument(args, "name")
+            .or_else(|| read_package_name(&manifest_dir.join(&path).join("Package.swift")))
+            .or_else(|| last_path_component(&path))?;
+        return Some(DepInfo {
+            name,
+            version_req: None,
+            dep_kind: DepKind::Runtime,
+        });
+    }
+
+    let url = string_argument(args, "url")?;
+    let name = last_path_component(&url)?;
+    let name = name.strip_suffix(".git").unwrap_or(&name).to_string();
+    Some(DepInfo {
+        name,
+        version_req: version_requirement(args),
+        dep_kind: DepKind::Runtime,
+    })
+}
+
+/// Normalize SwiftPM version requirements to the `^`/`~` notation used for other ecosystems.
+fn version_requirement(args: &str) -> Option<String> {
+    let range_re = Regex::new(r#""([^"]+)"\s*(\.\.<|\.\.\.)\s*"([^"]+)""#).unwrap();
+    if let Some(c) = range_re.captures(args) {
+        let upper = if &c[2] == "..<" { "<" } else { "<=" };
+        return Some(format!(">={}, {}{}", &c[1], upper, &c[3]));
+    }
+
+    let labelled = [
+        ("upToNextMajor", "^"),
+        ("upToNextMinor", "~"),
+        ("from", "^"),
+        ("exact", ""),
+        ("branch", "branch:"),
+        ("revision", "revision:"),
+    ];
+    for (label, prefix) in labelled {
+        let re = Regex::new(&format!(
+            r#"\b{}\s*[:(]\s*(?:from\s*:\s*)?"([^"]+)""#,
+            label
+        ))
+        .unwrap();
+        if let Some(c) = re.captures(args) {
+            return Some(format!("{}{}", prefix, &c[1]));
+        }
+    }
+    None
+}
+
+/// Value of a `label: "string"` argument.
+fn string_argument(args: &str, label: &str) -> Option<String> {
+    let re = Regex::new(&format!(r#"\b{}\s*:\s*"([^"]*)""#, regex::escape(label))).unwrap();
+    re.captures(args).map(|c| c[1].to_string())
+}
+
+fn last_path_component(path: &str) -> Option<String> {
+    path.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .map(str::to_string)
+}
+
+/// Collect the argument text of every `callee(...)` call, matching parentheses so
+/// nested calls like `.upToNextMajor(from: "1.0.0")` stay inside their parent.
+fn call_arguments(content: &str, callee: &str) -> Vec<String> {
+    let pattern = format!(r"{}\s*\(", regex::escape(callee));
+    let re = Regex::new(&pattern).unwrap();
+    let bytes = content.as_bytes();
+    let mut calls = Vec::new();
+
+    for m in re.find_iter(content) {
+        // Skip longer identifiers ending in the callee (e.g. `.packageX(` or `MyPackage(`)
+        if m.start() > 0 {
+            let prev = bytes[m.start() - 1];
+            if prev.is_ascii_alphanumeric() || prev == b'_' {
+                continue;
+            }
+        }
+
+        let start = m.end();
+        let mut depth = 1;
+        let mut in_string = false;
+        let mut end = None;
+        for (i, &b) in bytes[start..].iter().enumerate() {
+            match b {
+                b'"' => in_string = !in_string,
+                b'(' if !in_string => depth += 1,
+                b')' if !in_string => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = Some(start + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(end) = end {
+            calls.push(content[start..end].to_string());
+        }
+    }
+
+    calls
+}
+
+/// Remove `//` line comments and `/* */` block comments outside string literals,
+/// keeping the `// swift-tools-version` header.
+fn strip_comments(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    let mut at_file_start = true;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                out.extend(chars.next());
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) if !at_file_start => {
+                while chars.peek().is_some_and(|&n| n != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for n in chars.by_ref() {
+                    if prev == '*' && n == '/' {
+                        break;
+                    }
+                    prev = n;
+                }
+            }
+            _ => out.push(c),
+        }
+        if c == '\n' {
+            at_file_start = false;
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const MANIFEST: &str = r#"// swift-tools-version:5.9
+import PackageDescription
+
+let package = Package(
+    name: "PaymentsKit",
+    platforms: [.iOS(.v16)],
+    products: [
+        .library(name: "PaymentsKit", targets: ["PaymentsKit"]),
+    ],
+    dependencies: [
+        .package(url: "https://github.com/apple/swift-log.git", from: "1.5.0"),
+        .package(url: "https://github.com/pointfreeco/swift-snapshot-testing", .upToNextMinor(from: "1.12.0")),
+        .package(url: "https://github.com/Alamofire/Alamofire.git", "5.0.0"..<"6.0.0"),
+        // .package(url: "https://github.com/example/disabled.git", from: "1.0.0"),
+        .package(url: "https://github.com/example/edge.git", branch: "main"),
+        .package(path: "../SharedKit"),
+    ],
+    targets: [
+        .target(
+            name: "PaymentsKit",
+            dependencies: [.product(name: "Logging", package: "swift-log"), "SharedKit"]
+        ),
+        .executableTarget(name: "payments-cli", dependencies: ["PaymentsKit"]),
+        .testTarget(name: "PaymentsKitTests", dependencies: ["PaymentsKit"]),
+    ]
+)
+"#;
+
+    #[test]
+    fn test_parse_package_swift() {
+        let dir = TempDir::new().unwrap();
+        let pkg_dir = dir.path().join("ios/PaymentsKit");
+        let shared_dir = dir.path().join("ios/SharedKit");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        fs::create_dir_all(&shared_dir).unwrap();
+        fs::write(pkg_dir.join("Package.swift"), MANIFEST).unwrap();
+        fs::write(
+            shared_dir.join("Package.swift"),
+            "let package = Package(name: \"Shared\", targets: [])",
+        )
+        .unwrap();
+
+        let info = SwiftPackageParser
+            .parse(&pkg_dir.join("Package.swift"), "ios/PaymentsKit")
+            .unwrap();
+        assert_eq!(info.name, "PaymentsKit");
+        assert_eq!(info.kind, "swift");
+        assert_eq!(info.path, "ios/PaymentsKit");
+
+        let deps: Vec<(&str, Option<&str>)> = info
+            .dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.version_req.as_deref()))
+            .collect();
+        assert_eq!(
+            deps,
+            vec![
+                ("swift-log", Some("^1.5.0")),
+                ("swift-snapshot-testing", Some("~1.12.0")),
+                ("Alamofire", Some(">=5.0.0, <6.0.0")),
+                ("edge", Some("branch:main")),
+                // Path dependency resolves to the name declared in its manifest
+                ("Shared", None),
+            ]
+        );
+
+        let meta = info.metadata.unwrap();
+        assert_eq!(
+            meta["targets"],
+            serde_json::json!(["PaymentsKit", "payments-cli"])
+        );
+        assert_eq!(
+            meta["test_targets"],
+            serde_json::json!(["PaymentsKitTests"])
+        );
+        assert_eq!(meta["swift_tools_version"], "5.9");
+    }
+
+    #[test]
+    fn test_path_dependency_without_manifest_uses_directory_name() {
+        let dir = TempDir::new().unwrap();
+        let manifest = dir.path().join("Package.swift");
+        fs::write(
+            &manifest,
+            r#"let package = Package(name: "App", dependencies: [.package(path: "Modules/Networking/")])"#,
+        )
+        .unwrap();
+
+        let info = SwiftPackageParser.parse(&manifest, "").unwrap();
+        assert_eq!(info.dependencies.len(), 1);
+        assert_eq!(info.dependencies[0].name, "Networking");
+        assert!(info.metadata.is_none());
+    }
+
+    #[test]
+    fn test_missing_package_name_is_error() {
+        let dir = TempDir::new().unwrap();
+        let manifest = dir.path().join("Package.swift");
+        fs::write(&manifest, "import PackageDescription\n").unwrap();
+        assert!(SwiftPackageParser.parse(&manifest, "").is_err());
+    }
+
+    #[test]
+    fn test_exact_and_revision_requirements() {
+        assert_eq!(
+            version_requirement(r#"url: "https://x/a.git", exact: "2.1.0""#).as_deref(),
+            Some("2.1.0")
+        );
+        assert_eq!(
+            version_requirement(r#"url: "https://x/a.git", revision: "abc123""#).as_deref(),
+            Some("revision:abc123")
+        );
+        assert_eq!(
+            version_requirement(r#"url: "https://x/a.git", .upToNextMajor(from: "3.0.0")"#)
+                .as_deref(),
+            Some("^3.0.0")
+        );
+    }
+}
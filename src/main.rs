@@ -1,7 +1,9 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use index::federation::Source;
+use std::path::{Path, PathBuf};
 
+mod cli;
 mod config;
 mod db;
 mod index;
@@ -30,11 +32,76 @@ enum Commands {
         #[arg(long)]
         db: Option<PathBuf>,
     },
-    /// Start the MCP server over stdio
+    /// Start the MCP server over stdio, or over HTTP with --http
     Serve {
+        /// Path to the index database (defaults to .shire/index.db, or the [[federation]]
+        /// list in shire.toml). Repeat to answer across several repos' indexes, optionally
+        /// naming each as NAME=PATH
+        #[arg(long, value_name = "[NAME=]PATH")]
+        db: Vec<String>,
+        /// Serve streamable HTTP at /mcp and legacy SSE at /sse on this address (e.g. 127.0.0.1:7878)
+        #[arg(long, value_name = "ADDR")]
+        http: Option<std::net::SocketAddr>,
+        /// Require `Authorization: Bearer <TOKEN>` on HTTP requests (defaults to $SHIRE_HTTP_TOKEN)
+        #[arg(long, requires = "http")]
+        token: Option<String>,
+    },
+    /// Query the index from the terminal (mirrors the MCP tools)
+    Query {
         /// Path to the index database (defaults to .shire/index.db)
+        #[arg(long, global = true)]
+        db: Option<PathBuf>,
+        /// Print results as JSON instead of a table
+        #[arg(long, global = true)]
+        json: bool,
+        /// Maximum number of results to list (defaults to all, or 20 for hotspots)
+        #[arg(long, global = true)]
+        limit: Option<usize>,
+        /// Skip this many results, to page through a long list
+        #[arg(long, global = true, default_value_t = 0)]
+        offset: usize,
+        #[command(subcommand)]
+        command: cli::query::QueryCommand,
+    },
+    /// Export index data (e.g. the dependency graph as DOT, Mermaid, GraphML or JSON)
+    Export {
+        /// Path to the index database (defaults to .shire/index.db)
+        #[arg(long, global = true)]
+        db: Option<PathBuf>,
+        #[command(subcommand)]
+        command: cli::export::ExportCommand,
+    },
+    /// Run checks against the index; exits non-zero when a check fails
+    Check {
+        /// Root directory of the repository, where shire.toml is read from (defaults to current directory)
+        #[arg(long, global = true, default_value = ".")]
+        root: PathBuf,
+        /// Path to the index database (defaults to <root>/.shire/index.db)
+        #[arg(long, global = true)]
+        db: Option<PathBuf>,
+        /// Print findings as JSON instead of text
+        #[arg(long, global = true)]
+        json: bool,
+        #[command(subcommand)]
+        command: cli::check::CheckCommand,
+    },
+    /// List packages affected by a git diff: owners of changed files plus their dependents
+    Affected {
+        /// Base git ref to diff against (e.g. origin/main)
+        #[arg(long)]
+        base: String,
+        /// Head git ref; when omitted, diffs against the working tree
+        #[arg(long)]
+        head: Option<String>,
+        /// Root directory of the repository (defaults to current directory)
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+        /// Path to the index database (defaults to <root>/.shire/index.db)
         #[arg(long)]
         db: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = cli::affected::AffectedFormat::Lines)]
+        format: cli::affected::AffectedFormat,
     },
     /// Start the watch daemon for automatic index rebuilds
     Watch {
@@ -50,6 +117,19 @@ enum Commands {
         /// Path to the index database (overrides shire.toml db_path)
         #[arg(long)]
         db: Option<PathBuf>,
+        /// Watch the filesystem for changes instead of waiting for rebuild signals
+        /// (same as `[watch] native = true`)
+        #[arg(long)]
+        native: bool,
+        /// Show whether the daemon is idle or building, queued rebuilds, the last error and recent builds
+        #[arg(long, conflicts_with_all = ["stop", "flush"])]
+        status: bool,
+        /// Wait until every rebuild queued so far has landed
+        #[arg(long, conflicts_with = "stop")]
+        flush: bool,
+        /// Print --status as JSON
+        #[arg(long, requires = "status")]
+        json: bool,
     },
     /// Signal the watch daemon to rebuild the index
     Rebuild {
@@ -62,9 +142,46 @@ enum Commands {
         /// Read Claude Code hook JSON from stdin to extract the changed file
         #[arg(long)]
         stdin: bool,
+        /// Block until the rebuild has landed; fails if the daemon isn't running or the build fails
+        #[arg(long)]
+        wait: bool,
     },
 }
 
+/// Resolve the index path for read-only commands (`serve`, `query`), failing
+/// early with a hint when the index hasn't been built yet.
+fn resolve_index_path(db: Option<PathBuf>) -> Result<PathBuf> {
+    let db_path = db.unwrap_or_else(|| PathBuf::from(".shire/index.db"));
+    if !db_path.exists() {
+        anyhow::bail!(
+            "Index not found at {}. Run `shire build` first.",
+            db_path.display()
+        );
+    }
+    Ok(db_path)
+}
+
+/// Indexes `shire serve` answers from: every `--db`, else the `[[federation]]`
+/// list in ./shire.toml, else ./.shire/index.db.
+fn serve_sources(db: Vec<String>) -> Result<Vec<Source>> {
+    let sources: Vec<Source> = if db.is_empty() {
+        config::load_config(Path::new("."))?
+            .federation
+            .into_iter()
+            .map(|f| Source::new(f.name, PathBuf::from(f.db)))
+            .collect()
+    } else {
+        db.iter().map(|spec| Source::parse(spec)).collect()
+    };
+    if sources.is_empty() {
+        return Ok(vec![Source::new(None, resolve_index_path(None)?)]);
+    }
+    for source in &sources {
+        resolve_index_path(Some(source.db_path.clone()))?;
+    }
+    Ok(sources)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
@@ -74,36 +191,89 @@ async fn main() -> Result<()> {
             let config = config::load_config(&root)?;
             index::build_index(&root, &config, force, db.as_deref())
         }
-        Commands::Serve { db } => {
-            let db_path = db.unwrap_or_else(|| PathBuf::from(".shire/index.db"));
-            if !db_path.exists() {
-                anyhow::bail!(
-                    "Index not found at {}. Run `shire build` first.",
-                    db_path.display()
-                );
+        Commands::Serve { db, http, token } => {
+            let sources = serve_sources(db)?;
+            match http {
+                Some(addr) => {
+                    let token = token
+                        .or_else(|| std::env::var(mcp::http::TOKEN_ENV).ok())
+                        .filter(|t| !t.is_empty());
+                    mcp::http::run_http_server(sources, addr, token).await
+                }
+                None => mcp::run_server(sources).await,
             }
-            mcp::run_server(&db_path).await
+        }
+        Commands::Query {
+            db,
+            json,
+            limit,
+            offset,
+            command,
+        } => {
+            let db_path = resolve_index_path(db)?;
+            let conn = db::open_readonly(&db_path)?;
+            let page = db::queries::Page { limit, offset };
+            cli::query::run(&conn, command, page, cli::OutputFormat::from_json_flag(json))
+        }
+        Commands::Export { db, command } => {
+            let db_path = resolve_index_path(db)?;
+            let conn = db::open_readonly(&db_path)?;
+            cli::export::run(&conn, command)
+        }
+        Commands::Check {
+            root,
+            db,
+            json,
+            command,
+        } => {
+            let config = config::load_config(&root)?;
+            let db_path = resolve_index_path(Some(db.unwrap_or_else(|| root.join(".shire/index.db"))))?;
+            let conn = db::open_readonly(&db_path)?;
+            cli::check::run(&conn, &config, command, cli::OutputFormat::from_json_flag(json))
+        }
+        Commands::Affected {
+            base,
+            head,
+            root,
+            db,
+            format,
+        } => {
+            let db_path = db.unwrap_or_else(|| root.join(".shire/index.db"));
+            let db_path = resolve_index_path(Some(db_path))?;
+            let conn = db::open_readonly(&db_path)?;
+            let files = cli::affected::changed_files(&root, &base, head.as_deref())?;
+            cli::affected::run(&conn, &files, format)
         }
         Commands::Watch {
             root,
             stop,
             foreground,
             db,
+            native,
+            status,
+            flush,
+            json,
         } => {
             let root = std::fs::canonicalize(&root)?;
             if stop {
                 watch::daemon::stop_daemon(&root)
+            } else if status {
+                watch::client::print_status(&root, cli::OutputFormat::from_json_flag(json))
+            } else if flush {
+                watch::client::flush(&root)
             } else if foreground {
-                let config = config::load_config(&root)?;
+                let mut config = config::load_config(&root)?;
+                config.watch.native |= native;
                 watch::run_daemon(root, config, db).await
             } else {
-                watch::daemon::start_daemon(&root, db.as_deref())
+                watch::daemon::start_daemon(&root, db.as_deref(), native)
             }
         }
         Commands::Rebuild {
             root,
             mut file,
             stdin,
+            wait,
         } => {
             let root = if stdin {
                 match watch::protocol::HookInput::from_stdin() {
@@ -123,7 +293,11 @@ async fn main() -> Result<()> {
                 root
             };
             let root = std::fs::canonicalize(&root)?;
-            watch::send_rebuild(&root, file)
+            if wait {
+                watch::client::rebuild_and_wait(&root, file)
+            } else {
+                watch::send_rebuild(&root, file)
+            }
         }
     }
 }
@@ -0,0 +1,170 @@
+use crate::index::history;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How old the index is and whether it was built at the repo's current HEAD.
+/// Attached to every tool response.
+#[derive(Debug, Serialize)]
+pub struct Freshness {
+    pub indexed_at: Option<String>,
+    /// Seconds since the index was built.
+    pub age_seconds: Option<i64>,
+    /// Commit the index was built at.
+    pub index_commit: Option<String>,
+    /// The repo's HEAD right now.
+    pub head_commit: Option<String>,
+    /// Whether the index was built at HEAD; `None` when either commit is unknown.
+    pub at_head: Option<bool>,
+}
+
+fn meta(conn: &Connection, key: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT value FROM shire_meta WHERE key = ?1",
+        [key],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// The repo's HEAD, shared by every session. Looking it up starts `git`, so it
+/// is done from the reload poll at most once per `max_age`, and tool responses
+/// only read the stored value.
+#[derive(Debug)]
+pub struct HeadCache {
+    max_age: Duration,
+    /// When HEAD was last looked up, and what it was.
+    state: Mutex<Option<(Instant, Option<String>)>>,
+}
+
+impl HeadCache {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Look HEAD up again unless another session did within `max_age`.
+    pub fn refresh(&self, conn: &Connection) {
+        {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            let last = state.as_ref().map(|(checked_at, _)| *checked_at);
+            if last.is_some_and(|t| t.elapsed() < self.max_age) {
+                return;
+            }
+            // Claim this lookup so concurrent polls skip it; keep the old value meanwhile
+            let previous = state.take().and_then(|(_, head)| head);
+            *state = Some((Instant::now(), previous));
+        }
+        let head = meta(conn, "repo_root").and_then(|root| history::head(Path::new(&root)));
+        if let Ok(mut state) = self.state.lock() {
+            *state = Some((Instant::now(), head));
+        }
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.state.lock().ok()?.as_ref()?.1.clone()
+    }
+}
+
+/// Freshness of the index, given the repo's HEAD as last looked up.
+pub fn freshness(conn: &Connection, head_commit: Option<String>) -> Freshness {
+    let indexed_at = meta(conn, "indexed_at");
+    let age_seconds = indexed_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| {
+            (chrono::Utc::now() - t.with_timezone(&chrono::Utc))
+                .num_seconds()
+                .max(0)
+        });
+    let index_commit = meta(conn, "git_commit");
+    let at_head = match (&index_commit, &head_commit) {
+        (Some(index), Some(head)) => Some(index == head),
+        _ => None,
+    };
+    Freshness {
+        indexed_at,
+        age_seconds,
+        index_commit,
+        head_commit,
+        at_head,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn set_meta(path: &Path, key: &str, value: &str) {
+        let conn = db::open_or_create(path).unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO shire_meta (key, value) VALUES (?1, ?2)",
+            [key, value],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_freshness_without_git() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("index.db");
+        let indexed_at = chrono::Utc::now().to_rfc3339();
+        set_meta(&db_path, "indexed_at", &indexed_at);
+        set_meta(&db_path, "git_commit", "abc123");
+        set_meta(&db_path, "repo_root", dir.path().to_str().unwrap());
+
+        let conn = db::open_readonly(&db_path).unwrap();
+        let head = HeadCache::new(Duration::from_secs(60));
+        head.refresh(&conn);
+        let f = freshness(&conn, head.get());
+        assert_eq!(f.indexed_at.as_deref(), Some(indexed_at.as_str()));
+        assert!(f.age_seconds.unwrap() < 60);
+        assert_eq!(f.index_commit.as_deref(), Some("abc123"));
+        assert!(f.head_commit.is_none());
+        assert!(f.at_head.is_none());
+
+        let f = freshness(&conn, Some("abc123".into()));
+        assert_eq!(f.at_head, Some(true));
+    }
+
+    #[test]
+    fn test_head_cache_reads_git_once_per_interval() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q"]);
+        git(&["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q", "--allow-empty", "-m", "one"]);
+        let db_path = dir.path().join("index.db");
+        set_meta(&db_path, "repo_root", dir.path().to_str().unwrap());
+        let conn = db::open_readonly(&db_path).unwrap();
+
+        let head = HeadCache::new(Duration::from_secs(60));
+        assert!(head.get().is_none(), "nothing looked up yet");
+        head.refresh(&conn);
+        let first = head.get().unwrap();
+        assert_eq!(history::head(dir.path()).as_deref(), Some(first.as_str()));
+
+        // A new commit isn't seen until the cached value is old enough
+        git(&["-c", "user.name=t", "-c", "user.email=t@t", "commit", "-q", "--allow-empty", "-m", "two"]);
+        head.refresh(&conn);
+        assert_eq!(head.get(), Some(first.clone()));
+
+        let head = HeadCache::new(Duration::ZERO);
+        head.refresh(&conn);
+        assert_ne!(head.get(), Some(first));
+    }
+}
@@ -0,0 +1,119 @@
+use super::tools::{SharedIndex, ShireService};
+use crate::index::federation::Source;
+use anyhow::{Context, Result};
+use axum::Router;
+use axum::extract::Request;
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use rmcp::transport::sse_server::{SseServer, SseServerConfig};
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Environment variable read for the bearer token when `--token` isn't given.
+pub const TOKEN_ENV: &str = "SHIRE_HTTP_TOKEN";
+
+/// Serve MCP over HTTP: streamable HTTP at `/mcp`, legacy SSE at `/sse` (with
+/// messages posted to `/message`). Every session shares one index and its
+/// connection pool.
+/// With a token, requests without `Authorization: Bearer <token>` get a 401.
+pub async fn run_http_server(
+    sources: Vec<Source>,
+    addr: SocketAddr,
+    token: Option<String>,
+) -> Result<()> {
+    let index = SharedIndex::open(sources)?;
+    let result = serve(&index, addr, token).await;
+    index.close();
+    result
+}
+
+async fn serve(index: &SharedIndex, addr: SocketAddr, token: Option<String>) -> Result<()> {
+    let ct = CancellationToken::new();
+    let streamable_index = index.clone();
+    let streamable = StreamableHttpService::new(
+        move || Ok(ShireService::session(&streamable_index)),
+        LocalSessionManager::default().into(),
+        StreamableHttpServerConfig::default(),
+    );
+
+    let (sse_server, sse_router) = SseServer::new(SseServerConfig {
+        bind: addr,
+        sse_path: "/sse".into(),
+        post_path: "/message".into(),
+        ct: ct.clone(),
+        sse_keep_alive: None,
+    });
+    let sse_index = index.clone();
+    sse_server.with_service(move || ShireService::session(&sse_index));
+
+    let mut router = Router::new()
+        .nest_service("/mcp", streamable)
+        .merge(sse_router);
+    match token {
+        Some(token) => {
+            let expected: Arc<str> = format!("Bearer {token}").into();
+            router = router.layer(middleware::from_fn(move |req, next| {
+                require_token(req, next, expected.clone())
+            }));
+        }
+        None if !addr.ip().is_loopback() => {
+            eprintln!(
+                "Warning: serving on {addr} without authentication; pass --token or set {TOKEN_ENV}"
+            );
+        }
+        None => {}
+    }
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind {addr}"))?;
+    eprintln!("shire MCP server listening on http://{addr}/mcp (legacy SSE at http://{addr}/sse)");
+
+    tokio::select! {
+        result = axum::serve(listener, router).into_future() => result?,
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    ct.cancel();
+    Ok(())
+}
+
+async fn require_token(req: Request, next: Next, expected: Arc<str>) -> Response {
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| constant_time_eq(v.as_bytes(), expected.as_bytes()));
+    if authorized {
+        next.run(req).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "missing or invalid bearer token",
+        )
+            .into_response()
+    }
+}
+
+/// Compare without an early exit, so response timing doesn't leak how much
+/// of the token a guess got right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"Bearer s3cret", b"Bearer s3cret"));
+        assert!(!constant_time_eq(b"Bearer s3cret", b"Bearer s3creT"));
+        assert!(!constant_time_eq(b"Bearer s3cret", b"Bearer s3cret!"));
+        assert!(!constant_time_eq(b"", b"Bearer s3cret"));
+    }
+}
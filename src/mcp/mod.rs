@@ -1,13 +1,81 @@
+pub mod freshness;
+pub mod http;
 pub mod prompts;
+pub mod resources;
 pub mod tools;
 
-use crate::db;
+use crate::index::federation::Source;
 use anyhow::Result;
-use rmcp::{model::*, service::RequestContext, tool_handler, RoleServer, ServiceExt, ServerHandler};
+use rmcp::handler::server::tool::ToolCallContext;
+use rmcp::service::NotificationContext;
+use rmcp::{model::*, service::RequestContext, Peer, RoleServer, ServiceExt, ServerHandler};
 use std::collections::HashMap;
-use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// How often the server checks for a rebuilt index between requests.
+pub(crate) const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+impl tools::ShireService {
+    /// Pick up a rebuilt index, and tell this session's client that its
+    /// earlier results may be stale.
+    async fn refresh(&self, peer: &Peer<RoleServer>) {
+        let _guard = self.reload_lock.lock().await;
+        // A federated index is re-merged first when one of its repos was rebuilt
+        let synced = match &self.federation {
+            Some(federation) => federation.sync().map(drop),
+            None => Ok(()),
+        };
+        let (level, data) = match synced.and_then(|()| self.pool.refresh()) {
+            Ok(generation) => {
+                if generation <= self.seen_generation.load(Ordering::Acquire) {
+                    return;
+                }
+                self.seen_generation.store(generation, Ordering::Release);
+                let Ok(conn) = self.pool.get() else {
+                    return;
+                };
+                let freshness = freshness::freshness(&conn, self.head.get());
+                drop(conn);
+                (
+                    LoggingLevel::Info,
+                    serde_json::json!({
+                        "message": "Index rebuilt; results from earlier calls may be stale",
+                        "freshness": freshness,
+                    }),
+                )
+            }
+            Err(e) => (
+                LoggingLevel::Warning,
+                serde_json::json!({ "message": format!("Failed to reload the index: {e}") }),
+            ),
+        };
+        if level == LoggingLevel::Info {
+            let _ = peer.notify_resource_list_changed().await;
+        }
+        let _ = peer
+            .notify_logging_message(LoggingMessageNotificationParam {
+                level,
+                logger: Some("shire".into()),
+                data,
+            })
+            .await;
+    }
+
+    /// Freshness of the index, as an extra content block for tool responses.
+    fn freshness_content(&self) -> Option<Content> {
+        #[derive(serde::Serialize)]
+        struct Meta {
+            freshness: freshness::Freshness,
+        }
+        let conn = self.pool.get().ok()?;
+        let meta = Meta {
+            freshness: freshness::freshness(&conn, self.head.get()),
+        };
+        Some(Content::text(serde_json::to_string(&meta).ok()?))
+    }
+}
 
-#[tool_handler]
 impl ServerHandler for tools::ShireService {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
@@ -15,6 +83,9 @@ impl ServerHandler for tools::ShireService {
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
                 .enable_prompts()
+                .enable_resources()
+                .enable_resources_list_changed()
+                .enable_logging()
                 .build(),
             server_info: Implementation {
                 name: "shire".into(),
@@ -25,12 +96,87 @@ impl ServerHandler for tools::ShireService {
                  Use search_packages to find packages, package_dependencies/package_dependents \
                  to navigate the graph, and dependency_graph for transitive lookups. \
                  Use prompts for semantic codebase exploration: 'explore' a concept, \
-                 'onboard' to get a repo overview, or 'impact-analysis' to understand blast radius."
+                 'onboard' to get a repo overview, or 'impact-analysis' to understand blast radius. \
+                 Resources expose packages, files and dependency graphs under shire:// URIs. \
+                 Every tool response ends with a 'freshness' block: when the index was built and \
+                 whether it matches the repo's HEAD."
                     .into(),
             ),
         }
     }
 
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        // Notice rebuilds while the client is idle, not just on its next request
+        let session = self.clone();
+        let peer = context.peer;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RELOAD_POLL_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if peer.is_transport_closed() {
+                    break;
+                }
+                if let Ok(conn) = session.pool.get() {
+                    session.head.refresh(&conn);
+                }
+                session.refresh(&peer).await;
+            }
+        });
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<CallToolResult, ErrorData> {
+        self.refresh(&context.peer).await;
+        let tcc = ToolCallContext::new(self, request, context);
+        let mut result = self.tool_router.call(tcc).await?;
+        result.content.extend(self.freshness_content());
+        Ok(result)
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
+    async fn list_resources(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ListResourcesResult, ErrorData> {
+        self.refresh(&context.peer).await;
+        let conn = self.conn()?;
+        let cursor = request.and_then(|r| r.cursor);
+        resources::list(&conn, cursor.as_deref()).map_err(resource_err)
+    }
+
+    fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> impl std::future::Future<Output = std::result::Result<ListResourceTemplatesResult, ErrorData>> + Send + '_ {
+        std::future::ready(Ok(ListResourceTemplatesResult {
+            resource_templates: resources::templates(),
+            next_cursor: None,
+        }))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<ReadResourceResult, ErrorData> {
+        self.refresh(&context.peer).await;
+        let conn = self.conn()?;
+        resources::read(&conn, &request.uri).map_err(resource_err)
+    }
+
     fn list_prompts(
         &self,
         _request: Option<PaginatedRequestParam>,
@@ -42,13 +188,14 @@ impl ServerHandler for tools::ShireService {
         }))
     }
 
-    fn get_prompt(
+    async fn get_prompt(
         &self,
         request: GetPromptRequestParam,
-        _context: RequestContext<RoleServer>,
-    ) -> impl std::future::Future<Output = std::result::Result<GetPromptResult, ErrorData>> + Send + '_ {
-        let result = (|| {
-            let conn = self.conn.lock().map_err(|e| tools::ShireService::mcp_err(e.to_string()))?;
+        context: RequestContext<RoleServer>,
+    ) -> std::result::Result<GetPromptResult, ErrorData> {
+        self.refresh(&context.peer).await;
+        (|| {
+            let conn = self.conn()?;
             let args: HashMap<String, String> = request
                 .arguments
                 .unwrap_or_default()
@@ -67,16 +214,31 @@ impl ServerHandler for tools::ShireService {
                     prompts::PromptError::NotFound(msg) => ErrorData::resource_not_found(msg, None),
                     prompts::PromptError::Internal(msg) => ErrorData::internal_error(msg, None),
                 })
-        })();
+        })()
+    }
+}
 
-        std::future::ready(result)
+fn resource_err(e: resources::ResourceError) -> ErrorData {
+    match e {
+        resources::ResourceError::InvalidParams(msg) => ErrorData::invalid_params(msg, None),
+        resources::ResourceError::NotFound(msg) => ErrorData::resource_not_found(msg, None),
+        resources::ResourceError::Internal(msg) => ErrorData::internal_error(msg, None),
     }
 }
 
-pub async fn run_server(db_path: &Path) -> Result<()> {
-    let conn = db::open_readonly(db_path)?;
-    let service = tools::ShireService::new(conn);
-    let server = service.serve(rmcp::transport::stdio()).await?;
-    server.waiting().await?;
-    Ok(())
+pub async fn run_server(sources: Vec<Source>) -> Result<()> {
+    let index = tools::SharedIndex::open(sources)?;
+    let result = async {
+        let server = tools::ShireService::session(&index)
+            .serve(rmcp::transport::stdio())
+            .await?;
+        tokio::select! {
+            result = server.waiting() => { result?; }
+            _ = tokio::signal::ctrl_c() => {}
+        }
+        Ok(())
+    }
+    .await;
+    index.close();
+    result
 }
@@ -1,10 +1,10 @@
-use crate::db::queries;
+use crate::db::queries::{self, Page};
 use rmcp::model::{
     GetPromptResult, Prompt, PromptArgument, PromptMessage, PromptMessageContent,
     PromptMessageRole,
 };
 use rusqlite::Connection;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 pub enum PromptError {
     InvalidParams(String),
@@ -99,9 +99,9 @@ fn require_arg<'a>(args: &'a HashMap<String, String>, key: &str) -> Result<&'a s
 fn handle_explore(conn: &Connection, args: &HashMap<String, String>) -> Result<GetPromptResult, PromptError> {
     let query = require_arg(args, "query")?;
 
-    let packages = queries::search_packages(conn, query).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let symbols = queries::search_symbols(conn, query, None, None).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let files = queries::search_files(conn, query, None, None).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let packages = queries::search_packages(conn, query, None, false, Page::default()).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let symbols = queries::search_symbols(conn, query, None, None, false, Page::default()).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let files = queries::search_files(conn, query, None, None, false, Page::default()).map_err(|e| PromptError::Internal(e.to_string()))?;
 
     let mut text = format!("# Codebase exploration: \"{query}\"\n\n");
 
@@ -198,10 +198,10 @@ fn handle_explore_package(conn: &Connection, args: &HashMap<String, String>) ->
         .map_err(|e| PromptError::Internal(e.to_string()))?
         .ok_or_else(|| PromptError::NotFound(format!("Package '{name}' not found")))?;
 
-    let internal_deps = queries::package_dependencies(conn, name, true).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let dependents = queries::package_dependents(conn, name).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let symbols = queries::get_package_symbols(conn, name, None).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let files = queries::list_package_files(conn, name, None).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let internal_deps = queries::package_dependencies(conn, name, true, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let dependents = queries::package_dependents(conn, name, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let symbols = queries::get_package_symbols(conn, name, None, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let files = queries::list_package_files(conn, name, None, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
 
     let mut text = format!("# Package: {}\n\n", pkg.name);
 
@@ -287,7 +287,7 @@ fn handle_explore_package(conn: &Connection, args: &HashMap<String, String>) ->
 fn handle_explore_area(conn: &Connection, args: &HashMap<String, String>) -> Result<GetPromptResult, PromptError> {
     let path = require_arg(args, "path")?;
 
-    let packages = queries::packages_by_path_prefix(conn, path).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let packages = queries::packages_by_path_prefix(conn, path, Page::default()).map_err(|e| PromptError::Internal(e.to_string()))?;
 
     let mut text = format!("# Area: `{path}`\n\n");
 
@@ -303,7 +303,7 @@ fn handle_explore_area(conn: &Connection, args: &HashMap<String, String>) -> Res
             }
 
             // Symbol summary per package
-            let symbols = queries::get_package_symbols(conn, &pkg.name, None).map_err(|e| PromptError::Internal(e.to_string()))?;
+            let symbols = queries::get_package_symbols(conn, &pkg.name, None, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
             if !symbols.is_empty() {
                 let mut kind_counts: HashMap<&str, usize> = HashMap::new();
                 for sym in &symbols {
@@ -316,7 +316,7 @@ fn handle_explore_area(conn: &Connection, args: &HashMap<String, String>) -> Res
             }
 
             // File count
-            let files = queries::list_package_files(conn, &pkg.name, None).map_err(|e| PromptError::Internal(e.to_string()))?;
+            let files = queries::list_package_files(conn, &pkg.name, None, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
             if !files.is_empty() {
                 text.push_str(&format!("- **Files:** {}\n", files.len()));
             }
@@ -335,8 +335,8 @@ fn handle_explore_area(conn: &Connection, args: &HashMap<String, String>) -> Res
 
 fn handle_onboard(conn: &Connection) -> Result<GetPromptResult, PromptError> {
     let status = queries::index_status(conn).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let all_packages = queries::list_packages(conn, None).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let ext_dist = queries::extension_distribution(conn).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let all_packages = queries::list_packages(conn, None, None, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let ext_dist = queries::extension_distribution(conn, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
 
     let mut text = String::from("# Repository Overview\n\n");
 
@@ -416,8 +416,8 @@ fn handle_impact_analysis(conn: &Connection, args: &HashMap<String, String>) ->
         .map_err(|e| PromptError::Internal(e.to_string()))?
         .ok_or_else(|| PromptError::NotFound(format!("Package '{name}' not found")))?;
 
-    let direct_dependents = queries::package_dependents(conn, name).map_err(|e| PromptError::Internal(e.to_string()))?;
-    let reverse_edges = queries::reverse_dependency_graph(conn, name, 10).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let direct_dependents = queries::package_dependents(conn, name, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let reverse_edges = queries::reverse_dependency_graph(conn, name, 10, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
 
     // Collect all unique transitively affected packages
     let mut all_affected: HashSet<&str> = HashSet::new();
@@ -473,6 +473,27 @@ fn handle_impact_analysis(conn: &Connection, args: &HashMap<String, String>) ->
         text.push('\n');
     }
 
+    // Owners of the changed package and everything it affects
+    let mut owners: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for owner in &pkg.owners {
+        owners.entry(owner.clone()).or_default().insert(pkg.name.clone());
+    }
+    for affected in &all_affected {
+        if let Some(p) = queries::get_package(conn, affected).map_err(|e| PromptError::Internal(e.to_string()))? {
+            for owner in p.owners {
+                owners.entry(owner).or_default().insert(p.name.clone());
+            }
+        }
+    }
+    if !owners.is_empty() {
+        text.push_str(if reverse_edges.is_empty() { "\n## Owners to notify\n\n" } else { "## Owners to notify\n\n" });
+        for (owner, packages) in &owners {
+            let packages: Vec<&str> = packages.iter().map(String::as_str).collect();
+            text.push_str(&format!("- **{owner}**: {}\n", packages.join(", ")));
+        }
+        text.push('\n');
+    }
+
     Ok(GetPromptResult {
         description: Some(format!("Impact analysis for \"{name}\"")),
         messages: vec![PromptMessage {
@@ -495,7 +516,7 @@ fn handle_understand_dependency(conn: &Connection, args: &HashMap<String, String
         .ok_or_else(|| PromptError::NotFound(format!("Package '{to}' not found")))?;
 
     // Get full dependency graph from `from` and filter to paths reaching `to`
-    let all_edges = queries::dependency_graph(conn, from, 10, false).map_err(|e| PromptError::Internal(e.to_string()))?;
+    let all_edges = queries::dependency_graph(conn, from, 10, false, Page::ALL).map_err(|e| PromptError::Internal(e.to_string()))?;
 
     // BFS backwards from `to` through the edges to find all paths
     let mut reaches_target: HashSet<&str> = HashSet::new();
@@ -540,6 +561,18 @@ fn handle_understand_dependency(conn: &Connection, args: &HashMap<String, String
         text.push_str("## No dependency path found\n\n");
         text.push_str(&format!("{from} does not depend on {to} (directly or transitively).\n"));
     } else {
+        let paths = queries::dependency_paths(conn, from, to, 1, 10, false)
+            .map_err(|e| PromptError::Internal(e.to_string()))?;
+        if let Some(shortest) = paths.first() {
+            text.push_str(&format!("## Shortest path ({} hops)\n\n", shortest.edges.len()));
+            let hops: Vec<String> = shortest
+                .edges
+                .iter()
+                .map(|e| format!(" → {} ({})", e.to, e.dep_kind))
+                .collect();
+            text.push_str(&format!("{from}{}\n\n", hops.concat()));
+        }
+
         text.push_str(&format!("## Dependency edges ({})\n\n", relevant_edges.len()));
         for edge in &relevant_edges {
             text.push_str(&format!("- {} → {} ({})\n", edge.from, edge.to, edge.dep_kind));
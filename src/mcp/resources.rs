@@ -0,0 +1,489 @@
+use crate::db::queries::{self, Page};
+use rmcp::model::{
+    AnnotateAble, ListResourcesResult, RawResource, RawResourceTemplate, ReadResourceResult,
+    ResourceContents, ResourceTemplate,
+};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Resources per `resources/list` page.
+pub const PAGE_SIZE: usize = 100;
+
+/// Larger files are truncated in `shire://file/...` resources.
+const MAX_FILE_BYTES: usize = 256 * 1024;
+
+const JSON: &str = "application/json";
+
+pub enum ResourceError {
+    InvalidParams(String),
+    NotFound(String),
+    Internal(String),
+}
+
+impl From<anyhow::Error> for ResourceError {
+    fn from(e: anyhow::Error) -> Self {
+        ResourceError::Internal(e.to_string())
+    }
+}
+
+/// A parsed `shire://` URI.
+#[derive(Debug, PartialEq)]
+enum ShireUri {
+    Package(String),
+    PackageFiles(String),
+    File(String),
+    Graph(String),
+}
+
+pub fn templates() -> Vec<ResourceTemplate> {
+    let template = |uri_template: &str, name: &str, description: &str| {
+        RawResourceTemplate {
+            uri_template: uri_template.into(),
+            name: name.into(),
+            description: Some(description.into()),
+            mime_type: Some(JSON.into()),
+        }
+        .no_annotation()
+    };
+    vec![
+        template(
+            "shire://package/{name}",
+            "package",
+            "Package metadata, owners, its dependencies and its dependents",
+        ),
+        template(
+            "shire://package/{name}/files",
+            "package-files",
+            "Every file belonging to a package, with extension and size",
+        ),
+        template(
+            "shire://file/{path}",
+            "file",
+            "Symbol outline and content of an indexed file (path relative to the repo root)",
+        ),
+        template(
+            "shire://graph/{name}",
+            "graph",
+            "Transitive internal dependency graph of a package, as edges",
+        ),
+    ]
+}
+
+/// Every package, then every file, one page at a time. The cursor is the
+/// offset of the next page.
+pub fn list(conn: &Connection, cursor: Option<&str>) -> Result<ListResourcesResult, ResourceError> {
+    let offset = match cursor {
+        Some(c) => c
+            .parse::<usize>()
+            .map_err(|_| ResourceError::InvalidParams(format!("Invalid cursor: {c}")))?,
+        None => 0,
+    };
+
+    let packages = queries::list_packages(conn, None, None, Page::new(PAGE_SIZE, offset))?;
+    let mut resources: Vec<_> = packages
+        .iter()
+        .map(|pkg| {
+            let mut resource = RawResource::new(package_uri(&pkg.name), pkg.name.clone());
+            resource.description = pkg.description.clone();
+            resource.mime_type = Some(JSON.into());
+            resource.no_annotation()
+        })
+        .collect();
+
+    // Files fill the rest of the page once the packages run out
+    let file_page = Page::new(PAGE_SIZE - resources.len(), offset.saturating_sub(packages.total));
+    let files = queries::list_files(conn, file_page)?;
+    let more = packages.has_more || files.has_more;
+    resources.extend(files.into_iter().map(|file| {
+        let mut resource = RawResource::new(file_uri(&file.path), file.path.clone());
+        resource.description = file.package.map(|p| format!("File in {p}"));
+        resource.mime_type = Some(JSON.into());
+        resource.size = u32::try_from(file.size_bytes).ok();
+        resource.no_annotation()
+    }));
+
+    Ok(ListResourcesResult {
+        next_cursor: more.then(|| (offset + resources.len()).to_string()),
+        resources,
+    })
+}
+
+pub fn read(conn: &Connection, uri: &str) -> Result<ReadResourceResult, ResourceError> {
+    let parsed = parse_uri(conn, uri)?;
+    let contents = match parsed {
+        ShireUri::Package(name) => read_package(conn, uri, &name)?,
+        ShireUri::PackageFiles(name) => {
+            require_package(conn, &name)?;
+            let files = queries::list_package_files(conn, &name, None, Page::ALL)?.results;
+            vec![json_contents(uri, &files)?]
+        }
+        ShireUri::File(path) => read_file(conn, uri, &path)?,
+        ShireUri::Graph(name) => {
+            require_package(conn, &name)?;
+            #[derive(Serialize)]
+            struct Graph {
+                root: String,
+                edges: Vec<queries::GraphEdge>,
+            }
+            let edges = queries::dependency_graph(conn, &name, u32::MAX, true, Page::ALL)?.results;
+            vec![json_contents(uri, &Graph { root: name, edges })?]
+        }
+    };
+    Ok(ReadResourceResult { contents })
+}
+
+fn read_package(
+    conn: &Connection,
+    uri: &str,
+    name: &str,
+) -> Result<Vec<ResourceContents>, ResourceError> {
+    #[derive(Serialize)]
+    struct PackageResource {
+        package: queries::PackageRow,
+        dependencies: Vec<queries::DependencyRow>,
+        dependents: Vec<queries::DependencyRow>,
+        files_uri: String,
+        graph_uri: String,
+    }
+    let package = require_package(conn, name)?;
+    let resource = PackageResource {
+        dependencies: queries::package_dependencies(conn, name, false, Page::ALL)?.results,
+        dependents: queries::package_dependents(conn, name, Page::ALL)?.results,
+        files_uri: format!("{}/files", package_uri(name)),
+        graph_uri: format!("shire://graph/{}", encode(name, false)),
+        package,
+    };
+    Ok(vec![json_contents(uri, &resource)?])
+}
+
+/// The file's symbol outline as JSON, followed by its text. Only files in
+/// the index can be read, so URIs can't reach outside the repo.
+fn read_file(
+    conn: &Connection,
+    uri: &str,
+    path: &str,
+) -> Result<Vec<ResourceContents>, ResourceError> {
+    #[derive(Serialize)]
+    struct Outline {
+        path: String,
+        package: Option<String>,
+        size_bytes: i64,
+        symbols: Vec<queries::SymbolRow>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        content_note: Option<String>,
+    }
+    let file = queries::get_file(conn, path)?
+        .ok_or_else(|| ResourceError::NotFound(format!("File '{path}' is not in the index")))?;
+    let on_disk = file_on_disk(conn, &file.path)?;
+
+    let (text, content_note) = match std::fs::read(on_disk) {
+        Err(e) => (None, Some(format!("unreadable: {e}"))),
+        Ok(bytes) => match String::from_utf8(bytes) {
+            Err(_) => (None, Some("binary file, content omitted".to_string())),
+            Ok(text) if text.len() > MAX_FILE_BYTES => {
+                let mut end = MAX_FILE_BYTES;
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                (
+                    Some(text[..end].to_string()),
+                    Some(format!("truncated to the first {end} bytes")),
+                )
+            }
+            Ok(text) => (Some(text), None),
+        },
+    };
+
+    let outline = Outline {
+        symbols: queries::get_file_symbols(conn, &file.path, None, Page::ALL)?.results,
+        path: file.path,
+        package: file.package,
+        size_bytes: file.size_bytes,
+        content_note,
+    };
+    let mut contents = vec![json_contents(uri, &outline)?];
+    if let Some(text) = text {
+        contents.push(ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some("text/plain".into()),
+            text,
+        });
+    }
+    Ok(contents)
+}
+
+/// Where an indexed file lives. In a federated index, paths start with the
+/// repo name, and the rest is relative to that repo's root.
+fn file_on_disk(conn: &Connection, path: &str) -> Result<PathBuf, ResourceError> {
+    let missing_root = || {
+        ResourceError::Internal(
+            "The index doesn't record its repo root; rebuild it to read file contents".into(),
+        )
+    };
+    let repos = queries::federated_repos(conn)?;
+    if !repos.is_empty() {
+        let (repo, rest) = path.split_once('/').unwrap_or((path, ""));
+        let root = repos
+            .into_iter()
+            .find(|r| r.name == repo)
+            .and_then(|r| r.repo_root)
+            .ok_or_else(missing_root)?;
+        return Ok(Path::new(&root).join(rest));
+    }
+    let root: String = conn
+        .query_row(
+            "SELECT value FROM shire_meta WHERE key = 'repo_root'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|_| missing_root())?;
+    Ok(Path::new(&root).join(path))
+}
+
+fn require_package(conn: &Connection, name: &str) -> Result<queries::PackageRow, ResourceError> {
+    queries::get_package(conn, name)?
+        .ok_or_else(|| ResourceError::NotFound(format!("Package '{name}' not found")))
+}
+
+fn json_contents(uri: &str, value: &impl Serialize) -> Result<ResourceContents, ResourceError> {
+    let text =
+        serde_json::to_string_pretty(value).map_err(|e| ResourceError::Internal(e.to_string()))?;
+    Ok(ResourceContents::TextResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some(JSON.into()),
+        text,
+    })
+}
+
+fn parse_uri(conn: &Connection, uri: &str) -> Result<ShireUri, ResourceError> {
+    let invalid = || ResourceError::InvalidParams(format!("Unknown resource URI: {uri}"));
+    let rest = uri.strip_prefix("shire://").ok_or_else(invalid)?;
+    let (kind, rest) = rest.split_once('/').ok_or_else(invalid)?;
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    match kind {
+        "package" => {
+            // A package named like "tools/files" is encoded as "tools%2Ffiles",
+            // but accept it raw too when no package matches the shorter name
+            if let Some(name) = rest.strip_suffix("/files") {
+                let name = decode(name).ok_or_else(invalid)?;
+                if queries::get_package(conn, &name)?.is_some() {
+                    return Ok(ShireUri::PackageFiles(name));
+                }
+            }
+            Ok(ShireUri::Package(decode(rest).ok_or_else(invalid)?))
+        }
+        "file" => Ok(ShireUri::File(decode(rest).ok_or_else(invalid)?)),
+        "graph" => Ok(ShireUri::Graph(decode(rest).ok_or_else(invalid)?)),
+        _ => Err(invalid()),
+    }
+}
+
+fn package_uri(name: &str) -> String {
+    format!("shire://package/{}", encode(name, false))
+}
+
+fn file_uri(path: &str) -> String {
+    format!("shire://file/{}", encode(path, true))
+}
+
+/// Percent-encode everything but unreserved characters, `@` and (for file
+/// paths) `/`.
+fn encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric()
+            || matches!(b, b'-' | b'.' | b'_' | b'~' | b'@')
+            || (keep_slash && b == b'/')
+        {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+fn decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn setup() -> (tempfile::TempDir, Connection) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let conn = db::open_or_create(&dir.path().join("index.db")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO packages (name, path, kind, description) VALUES
+                 ('@acme/api', 'services/api', 'npm', 'Public API'),
+                 ('shared', 'libs/shared', 'npm', NULL);
+             INSERT INTO dependencies (package, dependency, dep_kind, is_internal) VALUES
+                 ('@acme/api', 'shared', 'runtime', 1),
+                 ('@acme/api', 'express', 'runtime', 0);
+             INSERT INTO files (path, package, extension, size_bytes) VALUES
+                 ('services/api/index.ts', '@acme/api', 'ts', 26),
+                 ('libs/shared/util.ts', 'shared', 'ts', 10);
+             INSERT INTO symbols (name, kind, package, file_path, line, visibility)
+                 VALUES ('handler', 'function', '@acme/api', 'services/api/index.ts', 1, 'public');",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO shire_meta (key, value) VALUES ('repo_root', ?1)",
+            [dir.path().to_str().unwrap()],
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("services/api")).unwrap();
+        std::fs::write(
+            dir.path().join("services/api/index.ts"),
+            "export function handler() {}",
+        )
+        .unwrap();
+        (dir, conn)
+    }
+
+    fn text(result: &ReadResourceResult, i: usize) -> &str {
+        match &result.contents[i] {
+            ResourceContents::TextResourceContents { text, .. } => text,
+            _ => panic!("expected text contents"),
+        }
+    }
+
+    fn read_ok(conn: &Connection, uri: &str) -> ReadResourceResult {
+        read(conn, uri).unwrap_or_else(|_| panic!("failed to read {uri}"))
+    }
+
+    #[test]
+    fn test_list_pages_packages_then_files() {
+        let (_dir, conn) = setup();
+        let page = list(&conn, None).ok().unwrap();
+        let uris: Vec<_> = page.resources.iter().map(|r| r.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            [
+                "shire://package/@acme%2Fapi",
+                "shire://package/shared",
+                "shire://file/libs/shared/util.ts",
+                "shire://file/services/api/index.ts",
+            ]
+        );
+        assert!(page.next_cursor.is_none());
+
+        for i in 0..PAGE_SIZE {
+            conn.execute(
+                "INSERT INTO files (path, extension) VALUES (?1, 'txt')",
+                [format!("docs/{i:03}.txt")],
+            )
+            .unwrap();
+        }
+        let first = list(&conn, None).ok().unwrap();
+        assert_eq!(first.resources.len(), PAGE_SIZE);
+        let cursor = first.next_cursor.expect("a second page");
+        let second = list(&conn, Some(&cursor)).ok().unwrap();
+        assert_eq!(second.resources.len(), 4);
+        assert!(second.next_cursor.is_none());
+        assert_eq!(
+            second.resources[3].uri,
+            "shire://file/services/api/index.ts"
+        );
+        assert!(matches!(
+            list(&conn, Some("abc")),
+            Err(ResourceError::InvalidParams(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_package_and_files() {
+        let (_dir, conn) = setup();
+        let result = read_ok(&conn, "shire://package/@acme%2Fapi");
+        let json: serde_json::Value = serde_json::from_str(text(&result, 0)).unwrap();
+        assert_eq!(json["package"]["name"], "@acme/api");
+        assert_eq!(json["dependencies"].as_array().unwrap().len(), 2);
+        assert_eq!(json["files_uri"], "shire://package/@acme%2Fapi/files");
+
+        // Unencoded slashes in the name work too
+        let result = read_ok(&conn, "shire://package/@acme/api/files");
+        let json: serde_json::Value = serde_json::from_str(text(&result, 0)).unwrap();
+        assert_eq!(json[0]["path"], "services/api/index.ts");
+
+        let shared = read_ok(&conn, "shire://package/shared");
+        let json: serde_json::Value = serde_json::from_str(text(&shared, 0)).unwrap();
+        assert_eq!(json["dependents"][0]["package"], "@acme/api");
+
+        assert!(matches!(
+            read(&conn, "shire://package/missing"),
+            Err(ResourceError::NotFound(_))
+        ));
+        assert!(matches!(
+            read(&conn, "file:///etc/passwd"),
+            Err(ResourceError::InvalidParams(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_file_outline_and_content() {
+        let (_dir, conn) = setup();
+        let result = read_ok(&conn, "shire://file/services/api/index.ts");
+        assert_eq!(result.contents.len(), 2);
+        let outline: serde_json::Value = serde_json::from_str(text(&result, 0)).unwrap();
+        assert_eq!(outline["package"], "@acme/api");
+        assert_eq!(outline["symbols"][0]["name"], "handler");
+        assert_eq!(text(&result, 1), "export function handler() {}");
+
+        // Indexed but gone from disk: outline only
+        let result = read_ok(&conn, "shire://file/libs/shared/util.ts");
+        assert_eq!(result.contents.len(), 1);
+        let outline: serde_json::Value = serde_json::from_str(text(&result, 0)).unwrap();
+        assert!(
+            outline["content_note"]
+                .as_str()
+                .unwrap()
+                .starts_with("unreadable")
+        );
+
+        // Only indexed files can be read
+        assert!(matches!(
+            read(&conn, "shire://file/../../etc/passwd"),
+            Err(ResourceError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_graph() {
+        let (_dir, conn) = setup();
+        let result = read_ok(&conn, "shire://graph/@acme%2Fapi");
+        let json: serde_json::Value = serde_json::from_str(text(&result, 0)).unwrap();
+        assert_eq!(json["root"], "@acme/api");
+        let edges = json["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 1, "internal edges only");
+        assert_eq!(edges[0]["to"], "shared");
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        for s in ["@acme/api", "a b%c", "dir/ü.ts"] {
+            assert_eq!(decode(&encode(s, false)).as_deref(), Some(s));
+            assert_eq!(decode(&encode(s, true)).as_deref(), Some(s));
+        }
+        assert_eq!(encode("@acme/api", false), "@acme%2Fapi");
+        assert_eq!(encode("src/a b.ts", true), "src/a%20b.ts");
+        assert_eq!(decode("bad%zz"), None);
+    }
+}
@@ -1,28 +1,88 @@
-use crate::db::queries;
+use crate::db::pool::{Pool, PooledConnection};
+use crate::db::queries::{self, Page};
+use crate::index::federation::{Federation, Source};
+use crate::mcp::RELOAD_POLL_INTERVAL;
+use crate::mcp::freshness::HeadCache;
 use rmcp::{
     handler::server::{router::tool::ToolRouter, tool::Parameters},
     model::*,
     schemars, tool, tool_router,
 };
-use rusqlite::Connection;
 use serde::Deserialize;
 use std::borrow::Cow;
-use std::sync::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
 
-#[derive(Debug)]
+/// The index every session reads: one repo's, or several repos' merged.
+#[derive(Debug, Clone)]
+pub struct SharedIndex {
+    pub pool: Arc<Pool>,
+    pub federation: Option<Arc<Federation>>,
+    pub head: Arc<HeadCache>,
+}
+
+impl SharedIndex {
+    /// A single index is served as is; several are merged into one.
+    pub fn open(mut sources: Vec<Source>) -> anyhow::Result<Self> {
+        let index = if sources.len() == 1 {
+            let source = sources.remove(0);
+            Self {
+                pool: Arc::new(Pool::open(&source.db_path)?),
+                federation: None,
+                head: Arc::new(HeadCache::new(RELOAD_POLL_INTERVAL)),
+            }
+        } else {
+            let federation = Federation::open(sources)?;
+            Self {
+                pool: Arc::new(Pool::open(federation.merged_path())?),
+                federation: Some(Arc::new(federation)),
+                head: Arc::new(HeadCache::new(RELOAD_POLL_INTERVAL)),
+            }
+        };
+        index.head.refresh(&*index.pool.get()?);
+        Ok(index)
+    }
+
+    /// Remove anything the index left on disk; called when the server stops.
+    pub fn close(&self) {
+        if let Some(federation) = &self.federation {
+            federation.close();
+        }
+    }
+}
+
+/// One MCP session. Sessions share the index; the rest is per session, so
+/// each client is told about a rebuild exactly once.
+#[derive(Debug, Clone)]
 pub struct ShireService {
-    pub(crate) conn: Mutex<Connection>,
+    pub(crate) pool: Arc<Pool>,
+    pub(crate) federation: Option<Arc<Federation>>,
+    pub(crate) head: Arc<HeadCache>,
+    /// Pool generation this session's client last heard about.
+    pub(crate) seen_generation: Arc<AtomicU64>,
+    /// Held while reloading and notifying, so a response never overtakes the
+    /// reload notifications sent by the session's background poller.
+    pub(crate) reload_lock: Arc<tokio::sync::Mutex<()>>,
     pub tool_router: ToolRouter<ShireService>,
 }
 
 impl ShireService {
-    pub fn new(conn: Connection) -> Self {
+    /// A new session on a shared index.
+    pub fn session(index: &SharedIndex) -> Self {
         Self {
-            conn: Mutex::new(conn),
+            seen_generation: Arc::new(AtomicU64::new(index.pool.generation())),
+            reload_lock: Arc::new(tokio::sync::Mutex::new(())),
+            pool: index.pool.clone(),
+            federation: index.federation.clone(),
+            head: index.head.clone(),
             tool_router: Self::tool_router(),
         }
     }
 
+    pub(crate) fn conn(&self) -> Result<PooledConnection<'_>, ErrorData> {
+        self.pool.get().map_err(|e| Self::mcp_err(e.to_string()))
+    }
+
     pub(crate) fn mcp_err(msg: String) -> ErrorData {
         ErrorData {
             code: ErrorCode(-32603),
@@ -32,10 +92,42 @@ impl ShireService {
     }
 }
 
+/// Largest page a tool returns, however many results are asked for.
+pub const MAX_LIMIT: usize = 500;
+
+/// Paging shared by the list and search tools. Their responses are an
+/// envelope with `total`, `has_more` and `next_offset` around `results`.
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PageParams {
+    /// Maximum number of results to return (default 50, at most 500)
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    /// Number of results to skip; pass the previous response's next_offset to get the next page
+    #[serde(default)]
+    pub offset: usize,
+}
+
+fn default_limit() -> usize {
+    queries::DEFAULT_LIMIT
+}
+
+impl PageParams {
+    pub fn page(&self) -> Page {
+        Page::new(self.limit.min(MAX_LIMIT), self.offset)
+    }
+}
+
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SearchParams {
     /// Search query to find packages by name or description
     pub query: String,
+    /// Only return packages with this tag (from [[packages]] overrides in shire.toml)
+    pub tag: Option<String>,
+    /// Treat the query as raw SQLite FTS5 syntax: "exact phrase", prefix*, AND/OR/NOT, NEAR(a b), column:term (default false)
+    #[serde(default)]
+    pub raw: bool,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -51,12 +143,106 @@ pub struct DepsParams {
     /// If true, only return dependencies that are also packages in this repo
     #[serde(default)]
     pub internal_only: bool,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct DependentsParams {
     /// Package name to find dependents of
     pub name: String,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ResolvedDepsParams {
+    /// Package whose lockfile-resolved dependencies to list
+    pub package: Option<String>,
+    /// Dependency name to look up resolved versions of across packages
+    pub dependency: Option<String>,
+    /// If true, leave out transitive dependencies
+    #[serde(default)]
+    pub direct_only: bool,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct VersionDriftParams {
+    /// Only consider packages of this kind: "npm", "go", "cargo", "python", ...
+    pub kind: Option<String>,
+    /// Only report libraries with at least this many distinct version requirements (default 2)
+    #[serde(default = "default_min_versions")]
+    pub min_versions: usize,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+fn default_min_versions() -> usize {
+    2
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct WhoOwnsParams {
+    /// File or directory path relative to the repo root, or a package name
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct PackagesByOwnerParams {
+    /// Owner handle as written in CODEOWNERS or shire.toml, e.g. "@acme/payments" (case-insensitive)
+    pub owner: String,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct HotspotsParams {
+    /// Rank individual files instead of packages (default false)
+    #[serde(default)]
+    pub files: bool,
+    /// Only consider this package, or its files when files=true
+    pub package: Option<String>,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct StalePackagesParams {
+    /// Report packages whose last commit is older than this many days (default 365)
+    #[serde(default = "default_stale_days")]
+    pub days: u32,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+fn default_stale_days() -> u32 {
+    365
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CheckRulesParams {
+    /// Dependency edges to add hypothetically before checking, e.g. for a change you are about to make
+    #[serde(default)]
+    pub proposed: Vec<ProposedEdge>,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct ProposedEdge {
+    /// Package that would gain the dependency
+    pub from: String,
+    /// Internal package it would depend on
+    pub to: String,
+    /// Dependency kind: "runtime" (default), "dev", "peer", "build"
+    #[serde(default = "default_dep_kind")]
+    pub dep_kind: String,
+}
+
+fn default_dep_kind() -> String {
+    "runtime".into()
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -69,6 +255,8 @@ pub struct GraphParams {
     /// If true, only follow internal dependencies
     #[serde(default)]
     pub internal_only: bool,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 fn default_depth() -> u32 {
@@ -79,6 +267,10 @@ fn default_depth() -> u32 {
 pub struct ListParams {
     /// Filter by package kind: "npm", "go", "cargo", "python"
     pub kind: Option<String>,
+    /// Only return packages with this tag (from [[packages]] overrides in shire.toml)
+    pub tag: Option<String>,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -87,16 +279,23 @@ pub struct SearchSymbolsParams {
     pub query: String,
     /// Filter to symbols from a specific package
     pub package: Option<String>,
-    /// Filter by symbol kind: "function", "class", "struct", "interface", "type", "enum", "trait", "method", "constant"
+    /// Filter by symbol kind: "function", "class", "struct", "interface", "type", "enum", "trait", "method", "constant", "property"
     pub kind: Option<String>,
+    /// Treat the query as raw SQLite FTS5 syntax: "exact phrase", prefix*, AND/OR/NOT, NEAR(a b), column:term (default false)
+    #[serde(default)]
+    pub raw: bool,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetPackageSymbolsParams {
     /// Exact package name to get symbols for
     pub package: String,
-    /// Filter by symbol kind: "function", "class", "struct", "interface", "type", "enum", "trait", "method", "constant"
+    /// Filter by symbol kind: "function", "class", "struct", "interface", "type", "enum", "trait", "method", "constant", "property"
     pub kind: Option<String>,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -105,14 +304,18 @@ pub struct GetSymbolParams {
     pub name: String,
     /// Filter to a specific package
     pub package: Option<String>,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct GetFileSymbolsParams {
     /// File path relative to repo root (e.g., "services/auth/src/auth.ts")
     pub file_path: String,
-    /// Filter by symbol kind: "function", "class", "struct", "interface", "type", "enum", "trait", "method", "constant"
+    /// Filter by symbol kind: "function", "class", "struct", "interface", "type", "enum", "trait", "method", "constant", "property"
     pub kind: Option<String>,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -123,6 +326,11 @@ pub struct SearchFilesParams {
     pub package: Option<String>,
     /// Filter by file extension (e.g., "ts", "go", "rs")
     pub extension: Option<String>,
+    /// Treat the query as raw SQLite FTS5 syntax: "exact phrase", prefix*, AND/OR/NOT, NEAR(a b), column:term (default false)
+    #[serde(default)]
+    pub raw: bool,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -131,11 +339,63 @@ pub struct ListPackageFilesParams {
     pub package: String,
     /// Filter by file extension (e.g., "ts", "go", "rs")
     pub extension: Option<String>,
+    #[serde(flatten)]
+    pub page: PageParams,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DependencyPathParams {
+    /// Source package (the one that depends)
+    pub from: String,
+    /// Target package or external dependency name
+    pub to: String,
+    /// Maximum number of paths to return, shortest first (default 1 = shortest path only)
+    #[serde(default = "default_max_paths")]
+    pub max_paths: usize,
+    /// Maximum path length in hops (default 10)
+    #[serde(default = "default_path_depth")]
+    pub max_depth: u32,
+    /// If true, only follow internal dependencies
+    #[serde(default)]
+    pub internal_only: bool,
+}
+
+fn default_max_paths() -> usize {
+    1
+}
+
+fn default_path_depth() -> u32 {
+    10
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AffectedPackagesParams {
+    /// Changed file paths, relative to the repository root
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BuildOrderParams {
+    /// Only include these packages and their transitive internal dependencies (default: all packages)
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// If true, ignore dev dependency edges
+    #[serde(default)]
+    pub exclude_dev: bool,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct FindCyclesParams {
+    /// If true, ignore dev dependency edges when looking for cycles
+    #[serde(default)]
+    pub exclude_dev: bool,
+    #[serde(flatten)]
+    pub page: PageParams,
 }
 
 #[tool_router]
 impl ShireService {
-    #[tool(description = "Search packages by name or description using full-text search")]
+    #[tool(description = "Search packages by name or description using full-text search, best matches first, optionally filtered by tag. Words match as prefixes; set raw=true for FTS5 query syntax.")]
     fn search_packages(
         &self,
         Parameters(params): Parameters<SearchParams>,
@@ -145,8 +405,8 @@ impl ShireService {
                 "Search query must not be empty",
             )]));
         }
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
-        let results = queries::search_packages(&conn, &params.query)
+        let conn = self.conn()?;
+        let results = queries::search_packages(&conn, &params.query, params.tag.as_deref(), params.raw, params.page.page())
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
             .map_err(|e| Self::mcp_err(e.to_string()))?;
@@ -158,7 +418,7 @@ impl ShireService {
         &self,
         Parameters(params): Parameters<GetPackageParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let result = queries::get_package(&conn, &params.name)
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         match result {
@@ -179,8 +439,8 @@ impl ShireService {
         &self,
         Parameters(params): Parameters<DepsParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
-        let results = queries::package_dependencies(&conn, &params.name, params.internal_only)
+        let conn = self.conn()?;
+        let results = queries::package_dependencies(&conn, &params.name, params.internal_only, params.page.page())
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
             .map_err(|e| Self::mcp_err(e.to_string()))?;
@@ -192,8 +452,129 @@ impl ShireService {
         &self,
         Parameters(params): Parameters<DependentsParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
-        let results = queries::package_dependents(&conn, &params.name)
+        let conn = self.conn()?;
+        let results = queries::package_dependents(&conn, &params.name, params.page.page())
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&results)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Exact dependency versions resolved from lockfiles (Cargo.lock, package-lock.json, pnpm-lock.yaml, yarn.lock, go.sum, poetry.lock), including transitive external deps. Give a package to see what it actually gets, a dependency to see which versions packages get, or both.")]
+    fn resolved_dependencies(
+        &self,
+        Parameters(params): Parameters<ResolvedDepsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let results = queries::resolved_dependencies(
+            &conn,
+            params.package.as_deref(),
+            params.dependency.as_deref(),
+            params.direct_only,
+            params.page.page(),
+        )
+        .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&results)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Find external libraries declared with different version requirements across packages. Groups non-internal dependencies by name and ecosystem kind, listing each distinct version requirement and the packages using it, most fragmented libraries first.")]
+    fn version_drift(
+        &self,
+        Parameters(params): Parameters<VersionDriftParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let drift = queries::version_drift(&conn, params.kind.as_deref(), params.min_versions, params.page.page())
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&drift)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Check internal dependencies against the layering rules configured in shire.toml ([[rules]] with from/allow/deny selectors). Pass proposed edges to verify a dependency you are about to add. Returns the violations; a total of 0 means the rules hold.")]
+    fn check_rules(
+        &self,
+        Parameters(params): Parameters<CheckRulesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let rules = queries::stored_rules(&conn).map_err(|e| Self::mcp_err(e.to_string()))?;
+        if rules.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No layering rules configured. Add [[rules]] to shire.toml and rebuild the index.",
+            )]));
+        }
+        let page = params.page.page();
+        let proposed: Vec<queries::GraphEdge> = params
+            .proposed
+            .into_iter()
+            .map(|e| queries::GraphEdge {
+                from: e.from,
+                to: e.to,
+                dep_kind: e.dep_kind,
+            })
+            .collect();
+        let violations = queries::check_rules(&conn, &rules, &proposed, page)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&violations)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Find who owns a file, directory or package according to CODEOWNERS (last matching rule wins). Returns the path's owners, its containing package and that package's owners. Use this to work out which teams to notify about a change.")]
+    fn who_owns(
+        &self,
+        Parameters(params): Parameters<WhoOwnsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let result = queries::who_owns(&conn, &params.path)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        match result {
+            Some(ownership) => {
+                let json = serde_json::to_string_pretty(&ownership)
+                    .map_err(|e| Self::mcp_err(e.to_string()))?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            None => Ok(CallToolResult::success(vec![Content::text(format!(
+                "'{}' is not an indexed file, directory or package",
+                params.path
+            ))])),
+        }
+    }
+
+    #[tool(description = "List the packages owned by a team or user, from CODEOWNERS or owners set in shire.toml package overrides. Matching is case-insensitive.")]
+    fn packages_by_owner(
+        &self,
+        Parameters(params): Parameters<PackagesByOwnerParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let results = queries::packages_by_owner(&conn, &params.owner, params.page.page())
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&results)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Rank packages (or files) by git churn: commit count over the history window configured in shire.toml ([history] window_days, default 90), with last commit date and top authors. Useful for finding refactoring hot spots. Requires [history] enabled = true.")]
+    fn churn_hotspots(
+        &self,
+        Parameters(params): Parameters<HotspotsParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let results = queries::churn_hotspots(&conn, params.files, params.package.as_deref(), params.page.page())
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&results)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "List packages untouched in git for at least the given number of days (default 365), least recently changed first, with their last commit date and top authors. Requires [history] enabled = true in shire.toml.")]
+    fn stale_packages(
+        &self,
+        Parameters(params): Parameters<StalePackagesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let results = queries::stale_packages(&conn, params.days, params.page.page())
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
             .map_err(|e| Self::mcp_err(e.to_string()))?;
@@ -206,28 +587,28 @@ impl ShireService {
         Parameters(mut params): Parameters<GraphParams>,
     ) -> Result<CallToolResult, ErrorData> {
         params.depth = params.depth.min(20);
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
-        let edges = queries::dependency_graph(&conn, &params.name, params.depth, params.internal_only)
+        let conn = self.conn()?;
+        let edges = queries::dependency_graph(&conn, &params.name, params.depth, params.internal_only, params.page.page())
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&edges)
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "List all indexed packages, optionally filtered by kind (npm, go, cargo, python)")]
+    #[tool(description = "List all indexed packages, optionally filtered by kind (npm, go, cargo, python) and/or tag")]
     fn list_packages(
         &self,
         Parameters(params): Parameters<ListParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
-        let results = queries::list_packages(&conn, params.kind.as_deref())
+        let conn = self.conn()?;
+        let results = queries::list_packages(&conn, params.kind.as_deref(), params.tag.as_deref(), params.page.page())
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Search symbols (functions, classes, types, etc.) by name or signature using full-text search. Returns matching symbols with file location, signature, parameters, and return type.")]
+    #[tool(description = "Search symbols (functions, classes, types, etc.) by name or signature using full-text search, best matches first. Words match as prefixes and identifiers match by their camelCase/snake_case parts, so 'parse config' finds parseConfig and 'auth' finds AuthenticationService. Set raw=true for FTS5 query syntax. Returns matching symbols with file location, signature, parameters, and return type.")]
     fn search_symbols(
         &self,
         Parameters(params): Parameters<SearchSymbolsParams>,
@@ -237,12 +618,14 @@ impl ShireService {
                 "Search query must not be empty",
             )]));
         }
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let results = queries::search_symbols(
             &conn,
             &params.query,
             params.package.as_deref(),
             params.kind.as_deref(),
+            params.raw,
+            params.page.page(),
         )
         .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
@@ -255,11 +638,12 @@ impl ShireService {
         &self,
         Parameters(params): Parameters<GetPackageSymbolsParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let results = queries::get_package_symbols(
             &conn,
             &params.package,
             params.kind.as_deref(),
+            params.page.page(),
         )
         .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
@@ -272,11 +656,12 @@ impl ShireService {
         &self,
         Parameters(params): Parameters<GetSymbolParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let results = queries::get_symbol(
             &conn,
             &params.name,
             params.package.as_deref(),
+            params.page.page(),
         )
         .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
@@ -289,11 +674,12 @@ impl ShireService {
         &self,
         Parameters(params): Parameters<GetFileSymbolsParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let results = queries::get_file_symbols(
             &conn,
             &params.file_path,
             params.kind.as_deref(),
+            params.page.page(),
         )
         .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
@@ -301,7 +687,7 @@ impl ShireService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
-    #[tool(description = "Search files by path or name using full-text search. Useful for finding files like 'middleware', 'proto files', or files in a specific directory.")]
+    #[tool(description = "Search files by path or name using full-text search, best matches first. Useful for finding files like 'middleware', 'proto files', or files in a specific directory. Set raw=true for FTS5 query syntax.")]
     fn search_files(
         &self,
         Parameters(params): Parameters<SearchFilesParams>,
@@ -311,12 +697,14 @@ impl ShireService {
                 "Search query must not be empty",
             )]));
         }
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let results = queries::search_files(
             &conn,
             &params.query,
             params.package.as_deref(),
             params.extension.as_deref(),
+            params.raw,
+            params.page.page(),
         )
         .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
@@ -329,11 +717,12 @@ impl ShireService {
         &self,
         Parameters(params): Parameters<ListPackageFilesParams>,
     ) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let results = queries::list_package_files(
             &conn,
             &params.package,
             params.extension.as_deref(),
+            params.page.page(),
         )
         .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&results)
@@ -341,9 +730,76 @@ impl ShireService {
         Ok(CallToolResult::success(vec![Content::text(json)]))
     }
 
+    #[tool(description = "Explain why one package depends on another: returns the shortest dependency chain from 'from' to 'to' with the dep_kind of each edge. Set max_paths > 1 to get the k shortest paths. Returns {paths, truncated}: on a large, tangled graph the search stops early with truncated = true.")]
+    fn dependency_path(
+        &self,
+        Parameters(mut params): Parameters<DependencyPathParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        params.max_depth = params.max_depth.min(20);
+        params.max_paths = params.max_paths.min(50);
+        let conn = self.conn()?;
+        let paths = queries::dependency_paths(
+            &conn,
+            &params.from,
+            &params.to,
+            params.max_paths,
+            params.max_depth,
+            params.internal_only,
+        )
+        .map_err(|e| Self::mcp_err(e.to_string()))?;
+        if paths.is_empty() && !paths.truncated {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No dependency path from '{}' to '{}' within {} hops",
+                params.from, params.to, params.max_depth
+            ))]));
+        }
+        let json = serde_json::to_string_pretty(&paths)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Given a list of changed file paths (relative to the repo root), return the packages that own them ('changed'), those plus every internal package that transitively depends on them ('affected'), and files outside any package ('unowned_files'). Useful for deciding what to build or test.")]
+    fn affected_packages(
+        &self,
+        Parameters(params): Parameters<AffectedPackagesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let result = queries::affected_packages(&conn, &params.files)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&result)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Topologically sort internal packages so dependencies build first. Returns 'order' (sequential) and 'waves' (groups that can build in parallel). Optionally limited to the closure of the given packages. Fails with the offending cycle if the graph is not a DAG.")]
+    fn build_order(
+        &self,
+        Parameters(params): Parameters<BuildOrderParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let result = queries::build_order(&conn, &params.packages, params.exclude_dev)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&result)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
+    #[tool(description = "Find dependency cycles among internal packages. Each cycle lists its packages and the edges (with dep_kind) that form it. Set exclude_dev=true to ignore dev dependencies.")]
+    fn find_cycles(
+        &self,
+        Parameters(params): Parameters<FindCyclesParams>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let conn = self.conn()?;
+        let cycles = queries::find_cycles(&conn, params.exclude_dev, params.page.page())
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        let json = serde_json::to_string_pretty(&cycles)
+            .map_err(|e| Self::mcp_err(e.to_string()))?;
+        Ok(CallToolResult::success(vec![Content::text(json)]))
+    }
+
     #[tool(description = "Get index status: when it was built, git commit, package/symbol/file counts, and build duration in milliseconds")]
     fn index_status(&self) -> Result<CallToolResult, ErrorData> {
-        let conn = self.conn.lock().map_err(|e| Self::mcp_err(e.to_string()))?;
+        let conn = self.conn()?;
         let status = queries::index_status(&conn)
             .map_err(|e| Self::mcp_err(e.to_string()))?;
         let json = serde_json::to_string_pretty(&status)
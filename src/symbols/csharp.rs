@@ -0,0 +1,479 @@
+use super::{Parameter, SymbolInfo, SymbolKind};
+use tree_sitter::{Node, Parser};
+
+/// Extract public and protected symbols from C# source code.
+/// Types record their enclosing namespace (or outer type) as `parent_symbol`;
+/// members record their declaring type. Private and internal declarations are skipped.
+pub fn extract(source: &str, file_path: &str) -> Vec<SymbolInfo> {
+    let mut parser = Parser::new();
+    if parser
+        .set_language(&tree_sitter_c_sharp::LANGUAGE.into())
+        .is_err()
+    {
+        return Vec::new();
+    }
+
+    let tree = match parser.parse(source, None) {
+        Some(t) => t,
+        None => return Vec::new(),
+    };
+
+    let mut symbols = Vec::new();
+    let root = tree.root_node();
+
+    // A file-scoped namespace (`namespace Foo;`) applies to every declaration after it
+    let mut file_namespace: Option<String> = None;
+
+    for i in 0..root.child_count() {
+        let node = root.child(i).unwrap();
+        match node.kind() {
+            "file_scoped_namespace_declaration" => {
+                file_namespace = node_name(source, &node);
+            }
+            "namespace_declaration" => {
+                extract_namespace(
+                    source,
+                    file_path,
+                    &node,
+                    file_namespace.as_deref(),
+                    &mut symbols,
+                );
+            }
+            _ => extract_declaration(
+                source,
+                file_path,
+                &node,
+                file_namespace.as_deref(),
+                false,
+                &mut symbols,
+            ),
+        }
+    }
+
+    symbols
+}
+
+fn extract_namespace(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    outer: Option<&str>,
+    symbols: &mut Vec<SymbolInfo>,
+) {
+    let name = match (outer, node_name(source, node)) {
+        (Some(outer), Some(name)) => Some(format!("{}.{}", outer, name)),
+        (None, name) => name,
+        (outer, None) => outer.map(str::to_string),
+    };
+
+    let body = match node.child_by_field_name("body") {
+        Some(b) => b,
+        None => return,
+    };
+    for i in 0..body.child_count() {
+        let child = body.child(i).unwrap();
+        if child.kind() == "namespace_declaration" {
+            extract_namespace(source, file_path, &child, name.as_deref(), symbols);
+        } else {
+            extract_declaration(source, file_path, &child, name.as_deref(), false, symbols);
+        }
+    }
+}
+
+/// Extract a type or member declaration. `parent` is the enclosing namespace for
+/// top-level types, or the declaring type for members and nested types.
+fn extract_declaration(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    parent: Option<&str>,
+    in_interface: bool,
+    symbols: &mut Vec<SymbolInfo>,
+) {
+    let type_kind = match node.kind() {
+        "class_declaration" => Some(SymbolKind::Class),
+        "interface_declaration" => Some(SymbolKind::Interface),
+        "struct_declaration" => Some(SymbolKind::Struct),
+        "record_declaration" => Some(if has_child_kind(node, "struct") {
+            SymbolKind::Struct
+        } else {
+            SymbolKind::Class
+        }),
+        "enum_declaration" => Some(SymbolKind::Enum),
+        _ => None,
+    };
+
+    if let Some(kind) = type_kind {
+        extract_type(source, file_path, node, kind, parent, in_interface, symbols);
+        return;
+    }
+
+    let parent = match parent {
+        Some(p) => p,
+        None => return,
+    };
+    let sym = match node.kind() {
+        "method_declaration" => extract_method(source, file_path, node, parent, in_interface),
+        "property_declaration" => extract_property(source, file_path, node, parent, in_interface),
+        _ => None,
+    };
+    if let Some(sym) = sym {
+        symbols.push(sym);
+    }
+}
+
+fn extract_type(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    kind: SymbolKind,
+    parent: Option<&str>,
+    in_interface: bool,
+    symbols: &mut Vec<SymbolInfo>,
+) {
+    let visibility = match visibility(source, node, in_interface) {
+        Some(v) => v,
+        None => return,
+    };
+    let name = match node_name(source, node) {
+        Some(n) => n,
+        None => return,
+    };
+
+    symbols.push(SymbolInfo {
+        name: name.clone(),
+        kind,
+        signature: Some(build_signature(
+            source,
+            node,
+            &["declaration_list", "enum_member_declaration_list"],
+        )),
+        file_path: file_path.to_string(),
+        line: node.start_position().row + 1,
+        visibility,
+        parent_symbol: parent.map(str::to_string),
+        return_type: None,
+        parameters: None,
+    });
+
+    if kind == SymbolKind::Enum {
+        return;
+    }
+    if let Some(body) = node.child_by_field_name("body") {
+        let is_interface = kind == SymbolKind::Interface;
+        for i in 0..body.child_count() {
+            let child = body.child(i).unwrap();
+            extract_declaration(
+                source,
+                file_path,
+                &child,
+                Some(&name),
+                is_interface,
+                symbols,
+            );
+        }
+    }
+}
+
+fn extract_method(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    type_name: &str,
+    in_interface: bool,
+) -> Option<SymbolInfo> {
+    let visibility = visibility(source, node, in_interface)?;
+    let name = node_name(source, node)?;
+
+    let kind = if has_modifier(source, node, "static") {
+        SymbolKind::Function
+    } else {
+        SymbolKind::Method
+    };
+
+    Some(SymbolInfo {
+        name,
+        kind,
+        signature: Some(build_signature(
+            source,
+            node,
+            &["block", "arrow_expression_clause"],
+        )),
+        file_path: file_path.to_string(),
+        line: node.start_position().row + 1,
+        visibility,
+        parent_symbol: Some(type_name.to_string()),
+        return_type: field_text(source, node, "returns"),
+        parameters: Some(extract_parameters(source, node)),
+    })
+}
+
+fn extract_property(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    type_name: &str,
+    in_interface: bool,
+) -> Option<SymbolInfo> {
+    let visibility = visibility(source, node, in_interface)?;
+    let name = node_name(source, node)?;
+
+    Some(SymbolInfo {
+        name,
+        kind: SymbolKind::Property,
+        signature: Some(build_signature(
+            source,
+            node,
+            &["accessor_list", "arrow_expression_clause"],
+        )),
+        file_path: file_path.to_string(),
+        line: node.start_position().row + 1,
+        visibility,
+        parent_symbol: Some(type_name.to_string()),
+        return_type: field_text(source, node, "type"),
+        parameters: None,
+    })
+}
+
+fn extract_parameters(source: &str, node: &Node) -> Vec<Parameter> {
+    let params_node = match node.child_by_field_name("parameters") {
+        Some(n) => n,
+        None => return Vec::new(),
+    };
+
+    let mut params = Vec::new();
+    for i in 0..params_node.child_count() {
+        let child = params_node.child(i).unwrap();
+        if child.kind() == "parameter"
+            && let Some(name) = field_text(source, &child, "name")
+        {
+            params.push(Parameter {
+                name,
+                type_annotation: field_text(source, &child, "type"),
+            });
+        }
+    }
+    params
+}
+
+/// Effective visibility of a declaration, or `None` if it isn't part of the public API.
+/// Interface members without modifiers are implicitly public.
+fn visibility(source: &str, node: &Node, in_interface: bool) -> Option<String> {
+    let public = has_modifier(source, node, "public");
+    let protected = has_modifier(source, node, "protected");
+    let private = has_modifier(source, node, "private");
+    let internal = has_modifier(source, node, "internal");
+
+    if public || (in_interface && !private && !protected && !internal) {
+        Some("public".to_string())
+    } else if protected && !private {
+        Some("protected".to_string())
+    } else {
+        None
+    }
+}
+
+fn has_modifier(source: &str, node: &Node, modifier: &str) -> bool {
+    (0..node.child_count()).any(|i| {
+        let child = node.child(i).unwrap();
+        child.kind() == "modifier" && child.utf8_text(source.as_bytes()).unwrap_or("") == modifier
+    })
+}
+
+fn has_child_kind(node: &Node, kind: &str) -> bool {
+    (0..node.child_count()).any(|i| node.child(i).unwrap().kind() == kind)
+}
+
+fn node_name(source: &str, node: &Node) -> Option<String> {
+    field_text(source, node, "name")
+}
+
+fn field_text(source: &str, node: &Node, field: &str) -> Option<String> {
+    node.child_by_field_name(field)?
+        .utf8_text(source.as_bytes())
+        .ok()
+        .map(|s| s.to_string())
+}
+
+/// Build a signature from the first modifier/keyword (skipping attributes) up to the
+/// first child of one of `body_kinds`, with whitespace collapsed.
+fn build_signature(source: &str, node: &Node, body_kinds: &[&str]) -> String {
+    let mut start = node.start_byte();
+    let mut end = node.end_byte();
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if child.kind() == "attribute_list" {
+            start = child.end_byte();
+        } else if body_kinds.contains(&child.kind()) {
+            end = child.start_byte();
+            break;
+        }
+    }
+
+    let sig = &source[start.min(end)..end.min(source.len())];
+    sig.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_class_in_block_namespace() {
+        let source = r#"
+namespace Acme.Billing
+{
+    public class InvoiceService : IInvoiceService
+    {
+        private readonly int _count;
+    }
+
+    internal class Hidden {}
+}
+"#;
+        let symbols = extract(source, "InvoiceService.cs");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "InvoiceService");
+        assert_eq!(symbols[0].kind, SymbolKind::Class);
+        assert_eq!(symbols[0].parent_symbol.as_deref(), Some("Acme.Billing"));
+        assert_eq!(
+            symbols[0].signature.as_deref(),
+            Some("public class InvoiceService : IInvoiceService")
+        );
+    }
+
+    #[test]
+    fn test_extract_file_scoped_namespace_and_nested_namespace() {
+        let source = r#"
+namespace Acme.Core;
+
+public interface IClock
+{
+    DateTime Now { get; }
+    DateTime Add(DateTime value, TimeSpan delta);
+}
+"#;
+        let symbols = extract(source, "IClock.cs");
+        let iface = symbols.iter().find(|s| s.name == "IClock").unwrap();
+        assert_eq!(iface.kind, SymbolKind::Interface);
+        assert_eq!(iface.parent_symbol.as_deref(), Some("Acme.Core"));
+
+        // Interface members are implicitly public
+        let now = symbols.iter().find(|s| s.name == "Now").unwrap();
+        assert_eq!(now.kind, SymbolKind::Property);
+        assert_eq!(now.visibility, "public");
+        assert_eq!(now.return_type.as_deref(), Some("DateTime"));
+        let add = symbols.iter().find(|s| s.name == "Add").unwrap();
+        assert_eq!(add.parent_symbol.as_deref(), Some("IClock"));
+
+        let nested = r#"
+namespace Outer { namespace Inner { public enum Color { Red, Green } } }
+"#;
+        let symbols = extract(nested, "Color.cs");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].kind, SymbolKind::Enum);
+        assert_eq!(symbols[0].parent_symbol.as_deref(), Some("Outer.Inner"));
+    }
+
+    #[test]
+    fn test_extract_methods_with_params_and_return() {
+        let source = r#"
+public class Calculator
+{
+    [Obsolete]
+    public decimal Total(IEnumerable<decimal> items, int precision = 2)
+    {
+        return 0;
+    }
+
+    public static Calculator Create() => new Calculator();
+
+    protected virtual void OnChanged(string name) {}
+
+    private void Secret() {}
+
+    public string Name { get; set; }
+
+    internal int Count { get; }
+}
+"#;
+        let symbols = extract(source, "Calculator.cs");
+
+        let total = symbols.iter().find(|s| s.name == "Total").unwrap();
+        assert_eq!(total.kind, SymbolKind::Method);
+        assert_eq!(total.parent_symbol.as_deref(), Some("Calculator"));
+        assert_eq!(total.return_type.as_deref(), Some("decimal"));
+        assert_eq!(
+            total.signature.as_deref(),
+            Some("public decimal Total(IEnumerable<decimal> items, int precision = 2)")
+        );
+        let params = total.parameters.as_ref().unwrap();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].name, "items");
+        assert_eq!(
+            params[0].type_annotation.as_deref(),
+            Some("IEnumerable<decimal>")
+        );
+        assert_eq!(params[1].name, "precision");
+
+        let create = symbols.iter().find(|s| s.name == "Create").unwrap();
+        assert_eq!(create.kind, SymbolKind::Function);
+        assert_eq!(
+            create.signature.as_deref(),
+            Some("public static Calculator Create()")
+        );
+
+        let on_changed = symbols.iter().find(|s| s.name == "OnChanged").unwrap();
+        assert_eq!(on_changed.visibility, "protected");
+
+        let name = symbols.iter().find(|s| s.name == "Name").unwrap();
+        assert_eq!(name.kind, SymbolKind::Property);
+        assert_eq!(name.signature.as_deref(), Some("public string Name"));
+
+        assert!(
+            symbols
+                .iter()
+                .all(|s| s.name != "Secret" && s.name != "Count")
+        );
+    }
+
+    #[test]
+    fn test_extract_structs_records_and_nested_types() {
+        let source = r#"
+namespace Geo;
+
+public struct Point { public double X { get; init; } }
+public record Address(string Street, string City);
+public record struct Money(decimal Amount);
+public class Outer
+{
+    public class Inner {}
+    private class Skipped {}
+}
+"#;
+        let symbols = extract(source, "Types.cs");
+
+        let point = symbols.iter().find(|s| s.name == "Point").unwrap();
+        assert_eq!(point.kind, SymbolKind::Struct);
+        let x = symbols.iter().find(|s| s.name == "X").unwrap();
+        assert_eq!(x.parent_symbol.as_deref(), Some("Point"));
+
+        let address = symbols.iter().find(|s| s.name == "Address").unwrap();
+        assert_eq!(address.kind, SymbolKind::Class);
+        assert_eq!(address.parent_symbol.as_deref(), Some("Geo"));
+        let money = symbols.iter().find(|s| s.name == "Money").unwrap();
+        assert_eq!(money.kind, SymbolKind::Struct);
+
+        let inner = symbols.iter().find(|s| s.name == "Inner").unwrap();
+        assert_eq!(inner.parent_symbol.as_deref(), Some("Outer"));
+        assert!(symbols.iter().all(|s| s.name != "Skipped"));
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let source = "namespace A;\n\npublic class First {}\n\npublic class Second {}\n";
+        let symbols = extract(source, "Lines.cs");
+        assert_eq!(symbols[0].line, 3);
+        assert_eq!(symbols[1].line, 5);
+    }
+}
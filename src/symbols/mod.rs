@@ -1,3 +1,4 @@
+pub mod csharp;
 pub mod go;
 pub mod java;
 pub mod kotlin;
@@ -6,6 +7,7 @@ pub mod proto;
 pub mod python;
 pub mod ruby;
 pub mod rust_lang;
+pub mod swift;
 pub mod typescript;
 pub mod walker;
 
@@ -38,6 +40,7 @@ pub enum SymbolKind {
     Trait,
     Method,
     Constant,
+    Property,
 }
 
 impl SymbolKind {
@@ -52,6 +55,7 @@ impl SymbolKind {
             SymbolKind::Trait => "trait",
             SymbolKind::Method => "method",
             SymbolKind::Constant => "constant",
+            SymbolKind::Property => "property",
         }
     }
 
@@ -66,6 +70,7 @@ impl SymbolKind {
             "trait" => Some(SymbolKind::Trait),
             "method" => Some(SymbolKind::Method),
             "constant" => Some(SymbolKind::Constant),
+            "property" => Some(SymbolKind::Property),
             _ => None,
         }
     }
@@ -78,6 +83,42 @@ pub struct Parameter {
     pub type_annotation: Option<String>,
 }
 
+/// Split an identifier into its words on underscores, other punctuation and
+/// case changes: `parseConfig`, `parse_config` and `ParseCONFIG` all give
+/// `["parse", "config"]`. Acronyms stay whole (`HTTPServer` gives `http`, `server`).
+pub fn identifier_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, cur) = (chars[i - 1], chars[i]);
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            let boundary = (cur.is_uppercase() && (prev.is_lowercase() || prev.is_numeric()))
+                || (cur.is_uppercase() && prev.is_uppercase() && next_is_lower);
+            if boundary {
+                words.push(chars[start..i].iter().collect::<String>().to_lowercase());
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(chars[start..].iter().collect::<String>().to_lowercase());
+        }
+    }
+    words
+}
+
+/// Source extensions to extract, minus any excluded in config (e.g. ".proto").
+fn source_extensions(exclude_extensions: &[String]) -> Vec<&'static str> {
+    walker::all_extensions()
+        .into_iter()
+        .filter(|ext| {
+            let with_dot = format!(".{}", ext);
+            !exclude_extensions.contains(&with_dot)
+        })
+        .collect()
+}
+
 /// Extract symbols from all source files in a package directory.
 pub fn extract_symbols_for_package(
     repo_root: &Path,
@@ -90,51 +131,83 @@ pub fn extract_symbols_for_package(
         return Ok(Vec::new());
     }
 
-    let all_exts = walker::all_extensions();
-    let extensions: Vec<&str> = all_exts
-        .into_iter()
-        .filter(|ext| {
-            let with_dot = format!(".{}", ext);
-            !exclude_extensions.contains(&with_dot)
-        })
-        .collect();
+    let extensions = source_extensions(exclude_extensions);
     let source_files = walker::walk_source_files(&package_dir, &extensions)?;
 
     let mut symbols = Vec::new();
-
     for file_path in source_files {
-        let source = match std::fs::read_to_string(&file_path) {
-            Ok(s) => s,
-            Err(_) => continue, // skip binary/unreadable files
-        };
-
-        let relative_path = file_path
-            .strip_prefix(repo_root)
-            .unwrap_or(&file_path)
-            .to_string_lossy()
-            .to_string();
-
-        let ext = file_path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("");
-
-        let mut file_symbols = match ext {
-            "ts" | "tsx" => typescript::extract(&source, &relative_path, ext == "tsx"),
-            "js" | "jsx" => typescript::extract_js(&source, &relative_path),
-            "go" => go::extract(&source, &relative_path),
-            "rs" => rust_lang::extract(&source, &relative_path),
-            "py" => python::extract(&source, &relative_path),
-            "proto" => proto::extract(&source, &relative_path),
-            "java" => java::extract(&source, &relative_path),
-            "kt" => kotlin::extract(&source, &relative_path),
-            "pm" | "pl" => perl::extract(&source, &relative_path),
-            "rb" => ruby::extract(&source, &relative_path),
-            _ => Vec::new(),
-        };
-
-        symbols.append(&mut file_symbols);
+        symbols.append(&mut extract_file(repo_root, &file_path));
     }
 
     Ok(symbols)
 }
+
+/// Extract symbols from one file, as `extract_symbols_for_package` would for
+/// the package at `package_path`. Returns nothing for files that package's
+/// walk would skip.
+pub fn extract_symbols_for_file(
+    repo_root: &Path,
+    package_path: &str,
+    file_path: &Path,
+    exclude_extensions: &[String],
+) -> Vec<SymbolInfo> {
+    let package_dir = repo_root.join(package_path);
+    let extensions = source_extensions(exclude_extensions);
+    if !walker::is_source_file(&package_dir, file_path, &extensions) {
+        return Vec::new();
+    }
+    extract_file(repo_root, file_path)
+}
+
+fn extract_file(repo_root: &Path, file_path: &Path) -> Vec<SymbolInfo> {
+    let source = match std::fs::read_to_string(file_path) {
+        Ok(s) => s,
+        Err(_) => return Vec::new(), // skip binary/unreadable files
+    };
+
+    let relative_path = file_path
+        .strip_prefix(repo_root)
+        .unwrap_or(file_path)
+        .to_string_lossy()
+        .to_string();
+
+    let ext = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+
+    match ext {
+        "ts" | "tsx" => typescript::extract(&source, &relative_path, ext == "tsx"),
+        "js" | "jsx" => typescript::extract_js(&source, &relative_path),
+        "go" => go::extract(&source, &relative_path),
+        "rs" => rust_lang::extract(&source, &relative_path),
+        "py" => python::extract(&source, &relative_path),
+        "proto" => proto::extract(&source, &relative_path),
+        "java" => java::extract(&source, &relative_path),
+        "kt" => kotlin::extract(&source, &relative_path),
+        "pm" | "pl" => perl::extract(&source, &relative_path),
+        "rb" => ruby::extract(&source, &relative_path),
+        "cs" => csharp::extract(&source, &relative_path),
+        "swift" => swift::extract(&source, &relative_path),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_words() {
+        assert_eq!(identifier_words("parseConfig"), ["parse", "config"]);
+        assert_eq!(identifier_words("parse_config"), ["parse", "config"]);
+        assert_eq!(identifier_words("AuthenticationService"), ["authentication", "service"]);
+        assert_eq!(identifier_words("HTTPServer"), ["http", "server"]);
+        assert_eq!(identifier_words("parseJSONData"), ["parse", "json", "data"]);
+        assert_eq!(identifier_words("MAX_RETRY_COUNT"), ["max", "retry", "count"]);
+        assert_eq!(identifier_words("sha256Sum"), ["sha256", "sum"]);
+        assert_eq!(identifier_words("__init__"), ["init"]);
+        assert_eq!(identifier_words("auth"), ["auth"]);
+        assert!(identifier_words("").is_empty());
+    }
+}
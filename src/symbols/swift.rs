@@ -0,0 +1,539 @@
+use super::{Parameter, SymbolInfo, SymbolKind};
+use tree_sitter::{Node, Parser};
+
+/// Extract public and open symbols from Swift source code.
+/// Members record their declaring type (or the extended type, for extensions)
+/// as `parent_symbol`. Internal, fileprivate and private declarations are skipped.
+pub fn extract(source: &str, file_path: &str) -> Vec<SymbolInfo> {
+    let mut parser = Parser::new();
+    if parser
+        .set_language(&tree_sitter_swift::LANGUAGE.into())
+        .is_err()
+    {
+        return Vec::new();
+    }
+
+    let tree = match parser.parse(source, None) {
+        Some(t) => t,
+        None => return Vec::new(),
+    };
+
+    let mut symbols = Vec::new();
+    let root = tree.root_node();
+    for i in 0..root.child_count() {
+        let node = root.child(i).unwrap();
+        extract_declaration(source, file_path, &node, None, false, &mut symbols);
+    }
+
+    symbols
+}
+
+/// Extract a declaration. `parent` is the declaring type for members; `inherit_public`
+/// is set inside `public` protocols and extensions, whose members default to public.
+fn extract_declaration(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    parent: Option<&str>,
+    inherit_public: bool,
+    symbols: &mut Vec<SymbolInfo>,
+) {
+    match node.kind() {
+        "class_declaration" => extract_type(source, file_path, node, parent, symbols),
+        "protocol_declaration" => extract_protocol(source, file_path, node, parent, symbols),
+        "function_declaration" | "protocol_function_declaration" => {
+            if let Some(sym) = extract_function(source, file_path, node, parent, inherit_public) {
+                symbols.push(sym);
+            }
+        }
+        "property_declaration" | "protocol_property_declaration" => {
+            if let Some(parent) = parent
+                && let Some(sym) = extract_property(source, file_path, node, parent, inherit_public)
+            {
+                symbols.push(sym);
+            }
+        }
+        "typealias_declaration" => {
+            if let Some(visibility) = visibility(source, node, inherit_public)
+                && let Some(name) = field_text(source, node, "name")
+            {
+                symbols.push(SymbolInfo {
+                    name,
+                    kind: SymbolKind::Type,
+                    signature: Some(build_signature(source, node, &[])),
+                    file_path: file_path.to_string(),
+                    line: node.start_position().row + 1,
+                    visibility,
+                    parent_symbol: parent.map(str::to_string),
+                    return_type: None,
+                    parameters: None,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `class_declaration` covers classes, structs, enums, actors and extensions,
+/// distinguished by the `declaration_kind` keyword.
+fn extract_type(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    parent: Option<&str>,
+    symbols: &mut Vec<SymbolInfo>,
+) {
+    let declaration_kind = node
+        .child_by_field_name("declaration_kind")
+        .map(|k| k.kind())
+        .unwrap_or("class");
+    let name = match field_text(source, node, "name") {
+        Some(n) => n,
+        None => return,
+    };
+
+    // Extensions don't declare a new type; their members attach to the extended one
+    if declaration_kind == "extension" {
+        let public = visibility(source, node, false).is_some();
+        extract_members(source, file_path, node, &name, public, symbols);
+        return;
+    }
+
+    let visibility = match visibility(source, node, false) {
+        Some(v) => v,
+        None => return,
+    };
+    let kind = match declaration_kind {
+        "struct" => SymbolKind::Struct,
+        "enum" => SymbolKind::Enum,
+        _ => SymbolKind::Class,
+    };
+
+    symbols.push(SymbolInfo {
+        name: name.clone(),
+        kind,
+        signature: Some(build_signature(
+            source,
+            node,
+            &["class_body", "enum_class_body"],
+        )),
+        file_path: file_path.to_string(),
+        line: node.start_position().row + 1,
+        visibility,
+        parent_symbol: parent.map(str::to_string),
+        return_type: None,
+        parameters: None,
+    });
+
+    extract_members(source, file_path, node, &name, false, symbols);
+}
+
+fn extract_protocol(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    parent: Option<&str>,
+    symbols: &mut Vec<SymbolInfo>,
+) {
+    let visibility = match visibility(source, node, false) {
+        Some(v) => v,
+        None => return,
+    };
+    let name = match field_text(source, node, "name") {
+        Some(n) => n,
+        None => return,
+    };
+
+    symbols.push(SymbolInfo {
+        name: name.clone(),
+        kind: SymbolKind::Interface,
+        signature: Some(build_signature(source, node, &["protocol_body"])),
+        file_path: file_path.to_string(),
+        line: node.start_position().row + 1,
+        visibility,
+        parent_symbol: parent.map(str::to_string),
+        return_type: None,
+        parameters: None,
+    });
+
+    // Protocol requirements share the protocol's visibility
+    extract_members(source, file_path, node, &name, true, symbols);
+}
+
+fn extract_members(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    type_name: &str,
+    inherit_public: bool,
+    symbols: &mut Vec<SymbolInfo>,
+) {
+    if let Some(body) = node.child_by_field_name("body") {
+        for i in 0..body.child_count() {
+            let child = body.child(i).unwrap();
+            extract_declaration(
+                source,
+                file_path,
+                &child,
+                Some(type_name),
+                inherit_public,
+                symbols,
+            );
+        }
+    }
+}
+
+fn extract_function(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    parent: Option<&str>,
+    inherit_public: bool,
+) -> Option<SymbolInfo> {
+    let visibility = visibility(source, node, inherit_public)?;
+    let name = field_text(source, node, "name")?;
+
+    let kind = if parent.is_none() || has_modifier(source, node, &["static", "class"]) {
+        SymbolKind::Function
+    } else {
+        SymbolKind::Method
+    };
+
+    Some(SymbolInfo {
+        name,
+        kind,
+        signature: Some(build_signature(source, node, &["function_body"])),
+        file_path: file_path.to_string(),
+        line: node.start_position().row + 1,
+        visibility,
+        parent_symbol: parent.map(str::to_string),
+        return_type: return_type(source, node),
+        parameters: Some(extract_parameters(source, node)),
+    })
+}
+
+fn extract_property(
+    source: &str,
+    file_path: &str,
+    node: &Node,
+    type_name: &str,
+    inherit_public: bool,
+) -> Option<SymbolInfo> {
+    let visibility = visibility(source, node, inherit_public)?;
+    let pattern = child_of_kind(node, "pattern")?;
+    let name = pattern
+        .child_by_field_name("bound_identifier")
+        .or_else(|| child_of_kind(&pattern, "simple_identifier"))?
+        .utf8_text(source.as_bytes())
+        .ok()?
+        .to_string();
+
+    let return_type = child_of_kind(node, "type_annotation")
+        .and_then(|t| t.named_child(0))
+        .and_then(|t| t.utf8_text(source.as_bytes()).ok())
+        .map(str::to_string);
+
+    Some(SymbolInfo {
+        name,
+        kind: SymbolKind::Property,
+        signature: Some(build_signature(
+            source,
+            node,
+            &["=", "computed_property", "protocol_property_requirements"],
+        )),
+        file_path: file_path.to_string(),
+        line: node.start_position().row + 1,
+        visibility,
+        parent_symbol: Some(type_name.to_string()),
+        return_type,
+        parameters: None,
+    })
+}
+
+/// The type following `->`, if any.
+fn return_type(source: &str, node: &Node) -> Option<String> {
+    let mut after_arrow = false;
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if child.kind() == "->" {
+            after_arrow = true;
+        } else if after_arrow && child.is_named() {
+            return child.utf8_text(source.as_bytes()).ok().map(str::to_string);
+        }
+    }
+    None
+}
+
+/// Parameters use their internal name: `_ x: Int` and `label y: String` yield `x` and `y`.
+fn extract_parameters(source: &str, node: &Node) -> Vec<Parameter> {
+    let mut params = Vec::new();
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if child.kind() != "parameter" {
+            continue;
+        }
+        let mut name = None;
+        let mut type_annotation = None;
+        for j in 0..child.child_count() {
+            let part = child.child(j).unwrap();
+            if part.kind() == ":" {
+                type_annotation = child
+                    .child(j + 1)
+                    .and_then(|t| t.utf8_text(source.as_bytes()).ok())
+                    .map(str::to_string);
+                break;
+            }
+            if part.kind() == "simple_identifier" {
+                name = part.utf8_text(source.as_bytes()).ok().map(str::to_string);
+            }
+        }
+        if let Some(name) = name {
+            params.push(Parameter {
+                name,
+                type_annotation,
+            });
+        }
+    }
+    params
+}
+
+/// Effective visibility of a declaration, or `None` if it isn't part of the public API.
+/// Declarations without an access modifier are public only inside a public
+/// protocol or extension.
+fn visibility(source: &str, node: &Node, inherit_public: bool) -> Option<String> {
+    let modifier = child_of_kind(node, "modifiers").and_then(|modifiers| {
+        (0..modifiers.child_count())
+            .map(|i| modifiers.child(i).unwrap())
+            .find(|m| m.kind() == "visibility_modifier")
+    });
+
+    match modifier {
+        Some(m) => {
+            let text = m.utf8_text(source.as_bytes()).unwrap_or("");
+            if text.starts_with("open") {
+                Some("open".to_string())
+            } else if text.starts_with("public") {
+                Some("public".to_string())
+            } else {
+                None
+            }
+        }
+        None if inherit_public => Some("public".to_string()),
+        None => None,
+    }
+}
+
+fn has_modifier(source: &str, node: &Node, keywords: &[&str]) -> bool {
+    let Some(modifiers) = child_of_kind(node, "modifiers") else {
+        return false;
+    };
+    (0..modifiers.child_count()).any(|i| {
+        let text = modifiers
+            .child(i)
+            .unwrap()
+            .utf8_text(source.as_bytes())
+            .unwrap_or("");
+        keywords.contains(&text)
+    })
+}
+
+fn child_of_kind<'a>(node: &Node<'a>, kind: &str) -> Option<Node<'a>> {
+    (0..node.child_count())
+        .map(|i| node.child(i).unwrap())
+        .find(|c| c.kind() == kind)
+}
+
+fn field_text(source: &str, node: &Node, field: &str) -> Option<String> {
+    node.child_by_field_name(field)?
+        .utf8_text(source.as_bytes())
+        .ok()
+        .map(|s| s.to_string())
+}
+
+/// Build a signature from the first modifier/keyword (skipping attributes) up to the
+/// first child of one of `body_kinds`, with whitespace collapsed.
+fn build_signature(source: &str, node: &Node, body_kinds: &[&str]) -> String {
+    let mut start = node.start_byte();
+    let mut end = node.end_byte();
+    for i in 0..node.child_count() {
+        let child = node.child(i).unwrap();
+        if child.kind() == "modifiers" {
+            for j in 0..child.child_count() {
+                let modifier = child.child(j).unwrap();
+                if modifier.kind() == "attribute" {
+                    start = modifier.end_byte();
+                }
+            }
+        } else if child.kind() == "attribute" {
+            start = child.end_byte();
+        } else if body_kinds.contains(&child.kind()) {
+            end = child.start_byte();
+            break;
+        }
+    }
+
+    let sig = &source[start.min(end)..end.min(source.len())];
+    sig.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_public_types() {
+        let source = r#"
+import Foundation
+
+open class Base<T>: NSObject where T: Equatable {
+    public var title: String = ""
+    private func hidden() {}
+}
+
+public struct Point {
+    public let x: Double
+    var internalY: Double
+}
+
+public enum Color { case red, green }
+
+internal class Hidden {}
+class AlsoHidden {}
+"#;
+        let symbols = extract(source, "Types.swift");
+
+        let base = symbols.iter().find(|s| s.name == "Base").unwrap();
+        assert_eq!(base.kind, SymbolKind::Class);
+        assert_eq!(base.visibility, "open");
+        assert_eq!(
+            base.signature.as_deref(),
+            Some("open class Base<T>: NSObject where T: Equatable")
+        );
+
+        let point = symbols.iter().find(|s| s.name == "Point").unwrap();
+        assert_eq!(point.kind, SymbolKind::Struct);
+        let x = symbols.iter().find(|s| s.name == "x").unwrap();
+        assert_eq!(x.kind, SymbolKind::Property);
+        assert_eq!(x.parent_symbol.as_deref(), Some("Point"));
+        assert_eq!(x.return_type.as_deref(), Some("Double"));
+
+        let color = symbols.iter().find(|s| s.name == "Color").unwrap();
+        assert_eq!(color.kind, SymbolKind::Enum);
+
+        let title = symbols.iter().find(|s| s.name == "title").unwrap();
+        assert_eq!(title.signature.as_deref(), Some("public var title: String"));
+
+        for hidden in ["hidden", "internalY", "Hidden", "AlsoHidden"] {
+            assert!(symbols.iter().all(|s| s.name != hidden), "{hidden} leaked");
+        }
+    }
+
+    #[test]
+    fn test_extract_functions_with_params_and_return() {
+        let source = r#"
+public class Factory {
+    @discardableResult
+    public static func make(_ x: Int, label y: String = "") throws -> Factory {
+        Factory()
+    }
+
+    public func run() async {}
+}
+
+public func topLevel(a: Int) async -> [String] { [] }
+func internalHelper() {}
+"#;
+        let symbols = extract(source, "Factory.swift");
+
+        let make = symbols.iter().find(|s| s.name == "make").unwrap();
+        assert_eq!(make.kind, SymbolKind::Function);
+        assert_eq!(make.parent_symbol.as_deref(), Some("Factory"));
+        assert_eq!(make.return_type.as_deref(), Some("Factory"));
+        assert_eq!(
+            make.signature.as_deref(),
+            Some(r#"public static func make(_ x: Int, label y: String = "") throws -> Factory"#)
+        );
+        let params = make.parameters.as_ref().unwrap();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].name, "x");
+        assert_eq!(params[0].type_annotation.as_deref(), Some("Int"));
+        assert_eq!(params[1].name, "y");
+        assert_eq!(params[1].type_annotation.as_deref(), Some("String"));
+
+        let run = symbols.iter().find(|s| s.name == "run").unwrap();
+        assert_eq!(run.kind, SymbolKind::Method);
+        assert!(run.return_type.is_none());
+
+        let top = symbols.iter().find(|s| s.name == "topLevel").unwrap();
+        assert_eq!(top.kind, SymbolKind::Function);
+        assert!(top.parent_symbol.is_none());
+        assert_eq!(top.return_type.as_deref(), Some("[String]"));
+
+        assert!(symbols.iter().all(|s| s.name != "internalHelper"));
+    }
+
+    #[test]
+    fn test_extract_protocols() {
+        let source = r#"
+public protocol Greeter: AnyObject {
+    func greet(name: String) -> String
+    var count: Int { get }
+}
+
+protocol InternalOnly {
+    func secret()
+}
+"#;
+        let symbols = extract(source, "Greeter.swift");
+        assert_eq!(symbols.len(), 3);
+
+        let greeter = symbols.iter().find(|s| s.name == "Greeter").unwrap();
+        assert_eq!(greeter.kind, SymbolKind::Interface);
+        assert_eq!(
+            greeter.signature.as_deref(),
+            Some("public protocol Greeter: AnyObject")
+        );
+
+        // Requirements take the protocol's visibility
+        let greet = symbols.iter().find(|s| s.name == "greet").unwrap();
+        assert_eq!(greet.kind, SymbolKind::Method);
+        assert_eq!(greet.visibility, "public");
+        assert_eq!(greet.parent_symbol.as_deref(), Some("Greeter"));
+        assert_eq!(greet.return_type.as_deref(), Some("String"));
+
+        let count = symbols.iter().find(|s| s.name == "count").unwrap();
+        assert_eq!(count.kind, SymbolKind::Property);
+        assert_eq!(count.return_type.as_deref(), Some("Int"));
+    }
+
+    #[test]
+    fn test_extract_extension_members() {
+        let source = r#"
+public extension Point {
+    func norm() -> Double { 0 }
+    private func scratch() {}
+}
+
+extension Point: Codable {
+    public func encoded() -> Data { Data() }
+    func internalOnly() {}
+}
+"#;
+        let symbols = extract(source, "Point+Extensions.swift");
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["norm", "encoded"]);
+        assert!(
+            symbols
+                .iter()
+                .all(|s| s.parent_symbol.as_deref() == Some("Point"))
+        );
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let source = "public struct First {}\n\npublic struct Second {}\n";
+        let symbols = extract(source, "Lines.swift");
+        assert_eq!(symbols[0].line, 1);
+        assert_eq!(symbols[1].line, 3);
+    }
+}
@@ -35,6 +35,8 @@ pub fn extensions_for_kind(kind: &str) -> Vec<&'static str> {
         "maven" | "gradle" => vec!["java", "kt"],
         "perl" => vec!["pm", "pl"],
         "ruby" => vec!["rb"],
+        "dotnet" => vec!["cs"],
+        "swift" => vec!["swift"],
         _ => vec![],
     }
 }
@@ -50,6 +52,8 @@ pub fn all_extensions() -> Vec<&'static str> {
         "proto",                    // Protobuf
         "pm", "pl",                // Perl
         "rb",                      // Ruby
+        "cs",                      // C#
+        "swift",                   // Swift
     ]
 }
 
@@ -95,12 +99,7 @@ pub fn walk_source_files(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>
             .and_then(|f| f.to_str())
             .unwrap_or("");
 
-        // Skip known generated/test file patterns
-        if SKIP_FILES.contains(&filename) {
-            continue;
-        }
-
-        if SKIP_SUFFIXES.iter().any(|suffix| filename.ends_with(suffix)) {
+        if is_skipped_file(filename) {
             continue;
         }
 
@@ -111,6 +110,33 @@ pub fn walk_source_files(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>
     Ok(files)
 }
 
+/// Known generated/test file patterns.
+fn is_skipped_file(filename: &str) -> bool {
+    SKIP_FILES.contains(&filename) || SKIP_SUFFIXES.iter().any(|suffix| filename.ends_with(suffix))
+}
+
+/// Whether `walk_source_files(dir, extensions)` would include `path`, without
+/// walking: same extension, directory and generated-file filters.
+pub fn is_source_file(dir: &Path, path: &Path, extensions: &[&str]) -> bool {
+    let Ok(relative) = path.strip_prefix(dir) else {
+        return false;
+    };
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    if !extensions.contains(&ext) {
+        return false;
+    }
+    let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("");
+    if is_skipped_file(filename) {
+        return false;
+    }
+    let Some(parent) = relative.parent() else {
+        return false;
+    };
+    parent.iter().filter_map(|c| c.to_str()).all(|name| {
+        !name.starts_with('.') && !EXCLUDED_DIRS.contains(&name)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,6 +151,8 @@ mod tests {
         assert!(exts.contains(&"py"));
         assert!(exts.contains(&"java"));
         assert!(exts.contains(&"proto"));
+        assert!(exts.contains(&"cs"));
+        assert!(exts.contains(&"swift"));
     }
 
     #[test]
@@ -135,6 +163,8 @@ mod tests {
         assert_eq!(extensions_for_kind("python"), vec!["py"]);
         assert_eq!(extensions_for_kind("maven"), vec!["java", "kt"]);
         assert_eq!(extensions_for_kind("gradle"), vec!["java", "kt"]);
+        assert_eq!(extensions_for_kind("dotnet"), vec!["cs"]);
+        assert_eq!(extensions_for_kind("swift"), vec!["swift"]);
         assert!(extensions_for_kind("unknown").is_empty());
     }
 
@@ -184,6 +214,19 @@ mod tests {
         assert!(files[0].ends_with("handler.go"));
     }
 
+    #[test]
+    fn test_is_source_file_matches_walk() {
+        let dir = Path::new("/repo/services/auth");
+        let exts = ["ts", "go"];
+        assert!(is_source_file(dir, &dir.join("src/index.ts"), &exts));
+        assert!(!is_source_file(dir, &dir.join("README.md"), &exts));
+        assert!(!is_source_file(dir, &dir.join("node_modules/x/index.ts"), &exts));
+        assert!(!is_source_file(dir, &dir.join(".cache/index.ts"), &exts));
+        assert!(!is_source_file(dir, &dir.join("src/api.generated.ts"), &exts));
+        assert!(!is_source_file(dir, &dir.join("main_test.go"), &exts));
+        assert!(!is_source_file(dir, Path::new("/repo/other/index.ts"), &exts));
+    }
+
     #[test]
     fn test_walk_skips_build_rs() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -0,0 +1,154 @@
+use super::daemon;
+use super::protocol::{BuildRecord, DaemonStatus, Request, Response};
+use crate::cli;
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+/// Send one request to the daemon and wait for its answer.
+/// Returns `None` if the daemon is not running.
+pub fn request(root: &Path, request: &Request) -> Result<Option<Response>> {
+    let Ok(mut stream) = UnixStream::connect(daemon::sock_path(root)) else {
+        return Ok(None);
+    };
+    let mut payload = serde_json::to_string(request).context("failed to serialize request")?;
+    payload.push('\n');
+    stream
+        .write_all(payload.as_bytes())
+        .context("failed to send request to the watch daemon")?;
+
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .context("failed to read the watch daemon's response")?;
+    if line.is_empty() {
+        bail!("watch daemon closed the connection without answering");
+    }
+    let response = serde_json::from_str(&line).context("invalid response from the watch daemon")?;
+    Ok(Some(response))
+}
+
+fn request_running(root: &Path, req: &Request) -> Result<Response> {
+    match request(root, req)? {
+        Some(Response::Error { message }) => bail!("watch daemon: {message}"),
+        Some(response) => Ok(response),
+        None => bail!(
+            "watch daemon is not running for {} (start it with `shire watch`)",
+            root.display()
+        ),
+    }
+}
+
+/// Queue a rebuild and block until it lands (`shire rebuild --wait`).
+pub fn rebuild_and_wait(root: &Path, files: Vec<PathBuf>) -> Result<()> {
+    let response = request_running(root, &Request::Rebuild { files, wait: true })?;
+    match response {
+        Response::Done { build: Some(build) } => report_build(&build),
+        Response::Done { build: None } => {
+            eprintln!("No relevant files changed; the index is current.");
+            Ok(())
+        }
+        other => bail!("unexpected response from the watch daemon: {other:?}"),
+    }
+}
+
+/// Block until every rebuild queued so far has landed (`shire watch --flush`).
+pub fn flush(root: &Path) -> Result<()> {
+    match request_running(root, &Request::Flush)? {
+        Response::Done { build: Some(build) } if build.error.is_some() => report_build(&build),
+        Response::Done { .. } => Ok(()),
+        other => bail!("unexpected response from the watch daemon: {other:?}"),
+    }
+}
+
+fn report_build(build: &BuildRecord) -> Result<()> {
+    match &build.error {
+        Some(error) => bail!("Rebuild failed: {error}"),
+        None => {
+            eprintln!(
+                "Rebuild completed ({}, {}ms).",
+                build.mode, build.duration_ms
+            );
+            Ok(())
+        }
+    }
+}
+
+/// How many builds `shire watch --status` lists.
+const STATUS_HISTORY: usize = 10;
+
+/// Print the daemon's status, last error and recent builds (`shire watch --status`).
+pub fn print_status(root: &Path, format: cli::OutputFormat) -> Result<()> {
+    let Response::Status(status) = request_running(root, &Request::Status)? else {
+        bail!("unexpected response from the watch daemon");
+    };
+    let Response::LastError { build: last_error } = request_running(root, &Request::LastError)?
+    else {
+        bail!("unexpected response from the watch daemon");
+    };
+    let Response::History { builds } = request_running(
+        root,
+        &Request::History {
+            limit: Some(STATUS_HISTORY),
+        },
+    )?
+    else {
+        bail!("unexpected response from the watch daemon");
+    };
+
+    if format == cli::OutputFormat::Json {
+        #[derive(Serialize)]
+        struct StatusReport<'a> {
+            status: &'a DaemonStatus,
+            last_error: &'a Option<BuildRecord>,
+            history: &'a [BuildRecord],
+        }
+        return cli::print_json(&StatusReport {
+            status: &status,
+            last_error: &last_error,
+            history: &builds,
+        });
+    }
+
+    let watching = if status.native {
+        ", native watching"
+    } else {
+        ""
+    };
+    println!("Daemon:   running (pid {}{watching})", status.pid);
+    println!("Root:     {}", status.root.display());
+    println!("Started:  {}", status.started_at);
+    match &status.building_since {
+        Some(since) => println!("State:    {} (since {since})", status.phase),
+        None => println!("State:    {}", status.phase),
+    }
+    println!("Pending:  {} rebuild signal(s)", status.pending);
+    println!("Builds:   {} ({} failed)", status.builds, status.failures);
+    if let Some(error) = &last_error {
+        println!(
+            "Last error: {} at {}",
+            error.error.as_deref().unwrap_or("-"),
+            error.started_at
+        );
+    }
+
+    if !builds.is_empty() {
+        println!();
+        let rows: Vec<Vec<String>> = builds
+            .iter()
+            .map(|b| {
+                vec![
+                    b.started_at.clone(),
+                    b.mode.to_string(),
+                    format!("{}ms", b.duration_ms),
+                    b.changed_files.to_string(),
+                    b.error.clone().unwrap_or_else(|| "ok".to_string()),
+                ]
+            })
+            .collect();
+        cli::print_table(&["STARTED", "MODE", "DURATION", "FILES", "RESULT"], &rows);
+    }
+    Ok(())
+}
@@ -35,7 +35,7 @@ pub fn is_running(root: &Path) -> bool {
 
 /// Start the daemon by re-exec'ing this binary with `watch --foreground`.
 /// Idempotent: returns Ok(()) if already running.
-pub fn start_daemon(root: &Path, db: Option<&Path>) -> Result<()> {
+pub fn start_daemon(root: &Path, db: Option<&Path>, native: bool) -> Result<()> {
     if is_running(root) {
         return Ok(());
     }
@@ -57,6 +57,9 @@ pub fn start_daemon(root: &Path, db: Option<&Path>) -> Result<()> {
     if let Some(db_path) = db {
         cmd.arg("--db").arg(db_path);
     }
+    if native {
+        cmd.arg("--native");
+    }
 
     cmd.stdin(std::process::Stdio::null())
         .stdout(std::process::Stdio::null())
@@ -1,16 +1,20 @@
+pub mod client;
 pub mod daemon;
+pub mod native;
 pub mod protocol;
+pub mod state;
 
 use crate::config::Config;
 use crate::index;
 use crate::symbols::walker;
 use anyhow::{Context, Result};
-use protocol::RebuildMessage;
+use protocol::{BuildMode, BuildRecord, Request, Response};
+use state::{DaemonState, Shared};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::net::UnixListener;
-use tokio::sync::mpsc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
 
 /// Check whether a file is relevant to the index.
 /// Must be inside the repo root AND be a manifest, source, or config file.
@@ -36,7 +40,10 @@ fn is_relevant(
     }
 
     // Manifest file (package.json, go.mod, Cargo.toml, etc.)
-    if manifest_names.contains(filename) {
+    if manifest_names
+        .iter()
+        .any(|p| index::manifest::filename_matches(p, filename))
+    {
         return true;
     }
 
@@ -58,7 +65,7 @@ pub fn send_rebuild(root: &Path, files: Vec<PathBuf>) -> Result<()> {
         return Ok(());
     }
 
-    let msg = RebuildMessage { files };
+    let msg = Request::Rebuild { files, wait: false };
     let mut payload = serde_json::to_string(&msg).context("failed to serialize rebuild message")?;
     payload.push('\n');
 
@@ -73,8 +80,127 @@ pub fn send_rebuild(root: &Path, files: Vec<PathBuf>) -> Result<()> {
     }
 }
 
+/// Reply channel for a client waiting on a rebuild or flush.
+type Waiter = oneshot::Sender<Option<BuildRecord>>;
+
+/// Work item for the build loop.
+pub enum Signal {
+    Rebuild {
+        files: Vec<PathBuf>,
+        waiter: Option<Waiter>,
+    },
+    Flush(Waiter),
+}
+
+/// Sends signals to the build loop, keeping the shared pending count in step.
+#[derive(Clone)]
+pub struct SignalSender {
+    tx: mpsc::UnboundedSender<Signal>,
+    state: Shared,
+}
+
+impl SignalSender {
+    pub fn send(&self, signal: Signal) {
+        if matches!(signal, Signal::Rebuild { .. }) {
+            self.state.lock().unwrap().signal_queued();
+        }
+        let _ = self.tx.send(signal);
+    }
+}
+
+/// Rebuild signals collected over one debounce window.
+#[derive(Default)]
+struct Window {
+    files: Vec<PathBuf>,
+    /// Any signal without files makes the rebuild unconditional.
+    unconditional: bool,
+    signals: usize,
+    waiters: Vec<Waiter>,
+}
+
+impl Window {
+    fn push(&mut self, signal: Signal, state: &Shared) {
+        match signal {
+            Signal::Rebuild { files, waiter } => {
+                state.lock().unwrap().signal_received();
+                self.signals += 1;
+                self.unconditional |= files.is_empty();
+                self.files.extend(files);
+                self.waiters.extend(waiter);
+            }
+            Signal::Flush(waiter) => self.waiters.push(waiter),
+        }
+    }
+
+    fn reply(self, build: Option<BuildRecord>) {
+        for waiter in self.waiters {
+            let _ = waiter.send(build.clone());
+        }
+    }
+}
+
+/// Answer the requests on one socket connection, one response line per request line.
+async fn handle_connection(stream: UnixStream, signals: SignalSender, state: Shared) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = match Request::parse(&line) {
+            Ok(request) => handle_request(request, &signals, &state).await,
+            Err(e) => {
+                eprintln!("[watch] invalid message: {e}");
+                Response::Error {
+                    message: format!("invalid request: {e}"),
+                }
+            }
+        };
+        let Ok(mut payload) = serde_json::to_string(&response) else {
+            continue;
+        };
+        payload.push('\n');
+        // Fire-and-forget clients hang up without reading the answer
+        if writer.write_all(payload.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn handle_request(request: Request, signals: &SignalSender, state: &Shared) -> Response {
+    let (waiter, done) = oneshot::channel();
+    match request {
+        Request::Status => return Response::Status(state.lock().unwrap().status()),
+        Request::LastError => {
+            return Response::LastError {
+                build: state.lock().unwrap().last_error(),
+            };
+        }
+        Request::History { limit } => {
+            return Response::History {
+                builds: state.lock().unwrap().history(limit),
+            };
+        }
+        Request::Rebuild { files, wait: false } => {
+            signals.send(Signal::Rebuild {
+                files,
+                waiter: None,
+            });
+            return Response::Queued;
+        }
+        Request::Rebuild { files, wait: true } => signals.send(Signal::Rebuild {
+            files,
+            waiter: Some(waiter),
+        }),
+        Request::Flush => signals.send(Signal::Flush(waiter)),
+    }
+    match done.await {
+        Ok(build) => Response::Done { build },
+        Err(_) => Response::Error {
+            message: "daemon shut down before the index was current".to_string(),
+        },
+    }
+}
+
 /// Run the daemon event loop (called with --foreground).
-/// Binds UDS, accepts rebuild signals, debounces, and runs build_index.
+/// Binds UDS, answers requests, debounces rebuild signals, and runs build_index.
 pub async fn run_daemon(
     root: PathBuf,
     config: Config,
@@ -88,31 +214,27 @@ pub async fn run_daemon(
     let listener = UnixListener::bind(&sock)
         .context("failed to bind Unix socket")?;
 
-    let (tx, mut rx) = mpsc::unbounded_channel::<RebuildMessage>();
+    let state = DaemonState::new(root.clone(), config.watch.native);
+    let (tx, mut rx) = mpsc::unbounded_channel::<Signal>();
+    let signals = SignalSender {
+        tx,
+        state: state.clone(),
+    };
 
     eprintln!("[watch] daemon started, listening on {}", sock.display());
 
     // Spawn connection acceptor task
-    let tx_clone = tx.clone();
+    let accept_signals = signals.clone();
+    let accept_state = state.clone();
     let accept_handle = tokio::spawn(async move {
         loop {
             match listener.accept().await {
                 Ok((stream, _)) => {
-                    let tx = tx_clone.clone();
-                    tokio::spawn(async move {
-                        let reader = BufReader::new(stream);
-                        let mut lines = reader.lines();
-                        while let Ok(Some(line)) = lines.next_line().await {
-                            match serde_json::from_str::<RebuildMessage>(&line) {
-                                Ok(msg) => {
-                                    let _ = tx.send(msg);
-                                }
-                                Err(e) => {
-                                    eprintln!("[watch] invalid message: {e}");
-                                }
-                            }
-                        }
-                    });
+                    tokio::spawn(handle_connection(
+                        stream,
+                        accept_signals.clone(),
+                        accept_state.clone(),
+                    ));
                 }
                 Err(e) => {
                     eprintln!("[watch] accept error: {e}");
@@ -121,6 +243,16 @@ pub async fn run_daemon(
         }
     });
 
+    // Native filesystem watching feeds the same channel as the socket
+    let native_handle = if config.watch.native {
+        let (count, handle) = native::spawn(root.clone(), &config, signals.clone())?;
+        eprintln!("[watch] watching {count} directories for changes");
+        Some(handle)
+    } else {
+        None
+    };
+    drop(signals);
+
     // Debounce loop
     let debounce = std::time::Duration::from_millis(config.watch.debounce_ms);
 
@@ -140,9 +272,15 @@ pub async fn run_daemon(
     loop {
         // Wait for first signal or shutdown
         tokio::select! {
-            Some(first_msg) = rx.recv() => {
-                // Accumulate files across the debounce window
-                let mut all_files: Vec<PathBuf> = first_msg.files;
+            Some(first) = rx.recv() => {
+                // Nothing is pending, so a flush can be answered right away
+                if let Signal::Flush(waiter) = first {
+                    let _ = waiter.send(state.lock().unwrap().last_build());
+                    continue;
+                }
+
+                let mut window = Window::default();
+                window.push(first, &state);
 
                 // Got a rebuild signal, start debounce window
                 let deadline = tokio::time::Instant::now() + debounce;
@@ -150,9 +288,7 @@ pub async fn run_daemon(
                 // Drain any additional signals during debounce window
                 loop {
                     tokio::select! {
-                        Some(msg) = rx.recv() => {
-                            all_files.extend(msg.files);
-                        }
+                        Some(signal) = rx.recv() => window.push(signal, &state),
                         _ = tokio::time::sleep_until(deadline) => {
                             break;
                         }
@@ -161,41 +297,94 @@ pub async fn run_daemon(
 
                 // If files were specified, check relevance before rebuilding.
                 // Empty file list = unconditional rebuild (manual `shire rebuild`).
-                if !all_files.is_empty() {
-                    let dominated_by_irrelevant = all_files
+                if !window.unconditional {
+                    let dominated_by_irrelevant = window
+                        .files
                         .iter()
                         .all(|f| !is_relevant(f, &root, &manifest_names, &source_exts));
                     if dominated_by_irrelevant {
-                        let names: Vec<_> = all_files
+                        let names: Vec<_> = window
+                            .files
                             .iter()
                             .filter_map(|f| f.file_name().and_then(|n| n.to_str()))
                             .collect();
                         eprintln!("[watch] skipping rebuild — no relevant files: {}", names.join(", "));
+                        state.lock().unwrap().window_skipped();
+                        window.reply(None);
                         continue;
                     }
                 }
 
-                // Run build
+                // Run build: per-file update when the changed files allow it,
+                // full build otherwise
                 let build_root = root.clone();
                 let build_config = config.clone();
                 let build_db = db_override.clone();
+                let changed = if window.unconditional {
+                    Vec::new()
+                } else {
+                    std::mem::take(&mut window.files)
+                };
+                let changed_files = changed.len();
 
                 eprintln!("[watch] triggering rebuild...");
+                let started_at = state.lock().unwrap().build_started();
+                let timer = std::time::Instant::now();
                 let result = tokio::task::spawn_blocking(move || {
+                    if !changed.is_empty()
+                        && let Some(summary) = index::incremental::update_files(
+                            &build_root,
+                            &build_config,
+                            build_db.as_deref(),
+                            &changed,
+                        )?
+                    {
+                        return Ok(Some(summary));
+                    }
                     index::build_index(
                         &build_root,
                         &build_config,
                         false,
                         build_db.as_deref(),
                     )
+                    .map(|()| None)
                 })
                 .await;
 
-                match result {
-                    Ok(Ok(())) => eprintln!("[watch] rebuild completed"),
-                    Ok(Err(e)) => eprintln!("[watch] rebuild failed: {e}"),
-                    Err(e) => eprintln!("[watch] rebuild task panicked: {e}"),
-                }
+                let (mode, error) = match result {
+                    Ok(Ok(Some(summary))) => {
+                        eprintln!(
+                            "[watch] updated {} file(s), removed {}, {} symbol(s) in {}ms",
+                            summary.files_updated,
+                            summary.files_removed,
+                            summary.symbols,
+                            summary.duration.as_millis()
+                        );
+                        (BuildMode::Files, None)
+                    }
+                    Ok(Ok(None)) => {
+                        eprintln!("[watch] rebuild completed");
+                        (BuildMode::Full, None)
+                    }
+                    Ok(Err(e)) => {
+                        eprintln!("[watch] rebuild failed: {e}");
+                        (BuildMode::Full, Some(format!("{e:#}")))
+                    }
+                    Err(e) => {
+                        eprintln!("[watch] rebuild task panicked: {e}");
+                        (BuildMode::Full, Some(format!("rebuild task panicked: {e}")))
+                    }
+                };
+                let record = BuildRecord {
+                    started_at,
+                    duration_ms: timer.elapsed().as_millis() as u64,
+                    mode,
+                    changed_files,
+                    signals: window.signals,
+                    error,
+                };
+                state.lock().unwrap().build_finished(record.clone());
+                window.reply(Some(record));
             }
             _ = sigterm.recv() => {
                 eprintln!("[watch] received SIGTERM, shutting down");
@@ -210,6 +399,9 @@ pub async fn run_daemon(
 
     // Cleanup
     accept_handle.abort();
+    if let Some(handle) = native_handle {
+        handle.abort();
+    }
     let _ = std::fs::remove_file(&sock);
     let _ = std::fs::remove_file(daemon::pid_path(&root));
     eprintln!("[watch] daemon stopped");
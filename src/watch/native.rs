@@ -0,0 +1,348 @@
+use super::protocol::RebuildMessage;
+use super::{Signal, SignalSender, is_relevant};
+use crate::config::Config;
+use crate::symbols::walker;
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use ignore::gitignore::Gitignore;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+/// Decides which paths the native watcher cares about, mirroring the index
+/// walker: hidden entries, `discovery.exclude` directories and gitignored
+/// paths are skipped.
+struct PathFilter {
+    root: PathBuf,
+    exclude: HashSet<String>,
+    manifests: Vec<String>,
+    source_exts: HashSet<&'static str>,
+    /// Parsed `.gitignore` per directory that has one.
+    gitignores: HashMap<PathBuf, Gitignore>,
+}
+
+impl PathFilter {
+    fn new(root: &Path, config: &Config) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            exclude: config.discovery.exclude.iter().cloned().collect(),
+            manifests: config.discovery.manifests.clone(),
+            source_exts: walker::all_extensions().into_iter().collect(),
+            gitignores: HashMap::new(),
+        }
+    }
+
+    /// (Re)load the `.gitignore` in `dir`, if any.
+    fn load_gitignore(&mut self, dir: &Path) {
+        let file = dir.join(".gitignore");
+        if file.is_file() {
+            let (gitignore, err) = Gitignore::new(&file);
+            if let Some(e) = err {
+                eprintln!("[watch] {}: {e}", file.display());
+            }
+            self.gitignores.insert(dir.to_path_buf(), gitignore);
+        } else {
+            self.gitignores.remove(dir);
+        }
+    }
+
+    /// Whether the index walker would skip this path.
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Ok(rel) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        let components: Vec<&str> = rel.iter().filter_map(|c| c.to_str()).collect();
+        for (i, name) in components.iter().enumerate() {
+            let component_is_dir = is_dir || i + 1 < components.len();
+            if name.starts_with('.') || (component_is_dir && self.exclude.contains(*name)) {
+                return true;
+            }
+        }
+
+        // The deepest .gitignore with a matching pattern decides
+        let mut dir = path.parent();
+        while let Some(d) = dir {
+            if let Some(gitignore) = self.gitignores.get(d) {
+                let matched = gitignore.matched_path_or_any_parents(path, is_dir);
+                if matched.is_ignore() {
+                    return true;
+                }
+                if matched.is_whitelist() {
+                    return false;
+                }
+            }
+            if d == self.root {
+                break;
+            }
+            dir = d.parent();
+        }
+        false
+    }
+
+    fn is_relevant(&self, path: &Path) -> bool {
+        let manifest_names: HashSet<&str> = self.manifests.iter().map(|s| s.as_str()).collect();
+        is_relevant(path, &self.root, &manifest_names, &self.source_exts)
+    }
+}
+
+/// One non-recursive watch per directory the index would walk, so excluded
+/// and gitignored trees (node_modules, target, ...) cost no inotify watches.
+struct WatchedTree {
+    watcher: RecommendedWatcher,
+    watched: HashSet<PathBuf>,
+    filter: PathFilter,
+}
+
+impl WatchedTree {
+    /// Watch `dir` and every directory below it that the walker would visit.
+    /// Returns the relevant files found, so a directory moved into the repo
+    /// still triggers a rebuild.
+    fn add_tree(&mut self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let exclude = self.filter.exclude.clone();
+        let walker = WalkBuilder::new(dir)
+            .hidden(true)
+            .require_git(false)
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+                match entry.file_name().to_str() {
+                    Some(name) if is_dir => !exclude.contains(name),
+                    _ => true,
+                }
+            })
+            .build();
+
+        let mut files = Vec::new();
+        for entry in walker {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let path = entry.path();
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                if self.watched.contains(path) {
+                    continue;
+                }
+                self.watcher
+                    .watch(path, RecursiveMode::NonRecursive)
+                    .with_context(|| {
+                        format!(
+                            "failed to watch {} (inotify watch limit reached? raise fs.inotify.max_user_watches)",
+                            path.display()
+                        )
+                    })?;
+                self.watched.insert(path.to_path_buf());
+                self.filter.load_gitignore(path);
+            } else if self.filter.is_relevant(path) {
+                files.push(path.to_path_buf());
+            }
+        }
+        Ok(files)
+    }
+
+    /// Turn a filesystem event into a rebuild request, if it matters.
+    /// An empty file list asks for an unconditional rebuild: used when a
+    /// watched directory disappears, a `.gitignore` changes or events were lost.
+    fn handle(&mut self, event: Event) -> Option<RebuildMessage> {
+        if event.need_rescan() {
+            return Some(RebuildMessage { files: Vec::new() });
+        }
+        if matches!(event.kind, EventKind::Access(_)) {
+            return None;
+        }
+
+        let mut files = Vec::new();
+        let mut structural = false;
+        for path in event.paths {
+            if path.file_name().is_some_and(|f| f == ".gitignore") {
+                if let Some(dir) = path.parent().filter(|d| self.watched.contains(*d)) {
+                    self.filter.load_gitignore(dir);
+                    structural = true;
+                }
+                continue;
+            }
+
+            if self.watched.contains(&path) {
+                if !path.is_dir() {
+                    // Removed or moved away; inotify drops the watch itself
+                    let _ = self.watcher.unwatch(&path);
+                    self.watched.retain(|d| !d.starts_with(&path));
+                    self.filter.gitignores.retain(|d, _| !d.starts_with(&path));
+                    structural = true;
+                }
+                continue;
+            }
+
+            if path.is_dir() {
+                if !self.filter.is_ignored(&path, true) {
+                    match self.add_tree(&path) {
+                        Ok(found) => files.extend(found),
+                        Err(e) => eprintln!("[watch] {e:#}"),
+                    }
+                }
+            } else if !self.filter.is_ignored(&path, false) && self.filter.is_relevant(&path) {
+                files.push(path);
+            }
+        }
+
+        if structural {
+            Some(RebuildMessage { files: Vec::new() })
+        } else if files.is_empty() {
+            None
+        } else {
+            Some(RebuildMessage { files })
+        }
+    }
+}
+
+/// Start watching `root` natively and forward relevant changes to the daemon's
+/// debounce loop. Returns the number of directories watched and the task that
+/// owns the watcher; aborting the task stops watching.
+pub fn spawn(
+    root: PathBuf,
+    config: &Config,
+    signals: SignalSender,
+) -> Result<(usize, tokio::task::JoinHandle<()>)> {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel::<notify::Result<Event>>();
+    let watcher = notify::recommended_watcher(move |event| {
+        let _ = event_tx.send(event);
+    })
+    .context("failed to create filesystem watcher")?;
+
+    let mut tree = WatchedTree {
+        watcher,
+        watched: HashSet::new(),
+        filter: PathFilter::new(&root, config),
+    };
+    tree.add_tree(&root)?;
+    let count = tree.watched.len();
+
+    let handle = tokio::spawn(async move {
+        while let Some(event) = event_rx.recv().await {
+            match event {
+                Ok(event) => {
+                    if let Some(msg) = tree.handle(event) {
+                        signals.send(Signal::Rebuild {
+                            files: msg.files,
+                            waiter: None,
+                        });
+                    }
+                }
+                Err(e) => eprintln!("[watch] filesystem watcher error: {e}"),
+            }
+        }
+    });
+    Ok((count, handle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, RemoveKind};
+    use std::fs;
+
+    fn test_tree(root: &Path) -> WatchedTree {
+        let config: Config = toml::from_str("[discovery]\nexclude = [\"node_modules\"]\n").unwrap();
+        let watcher = notify::recommended_watcher(|_: notify::Result<Event>| {}).unwrap();
+        let mut tree = WatchedTree {
+            watcher,
+            watched: HashSet::new(),
+            filter: PathFilter::new(root, &config),
+        };
+        tree.add_tree(root).unwrap();
+        tree
+    }
+
+    fn event(kind: EventKind, path: PathBuf) -> Event {
+        Event::new(kind).add_path(path)
+    }
+
+    #[test]
+    fn test_watches_only_walked_directories() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(root.join("services/auth/src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/lodash")).unwrap();
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".gitignore"), "dist/\n*.generated.ts\n").unwrap();
+
+        let tree = test_tree(&root);
+        assert!(tree.watched.contains(&root.join("services/auth/src")));
+        assert!(!tree.watched.contains(&root.join("node_modules")));
+        assert!(!tree.watched.contains(&root.join("node_modules/lodash")));
+        assert!(!tree.watched.contains(&root.join("dist")));
+        assert!(!tree.watched.contains(&root.join(".git")));
+    }
+
+    #[test]
+    fn test_filters_ignored_and_irrelevant_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(root.join("services/auth")).unwrap();
+        fs::write(root.join(".gitignore"), "*.generated.ts\n").unwrap();
+        let mut tree = test_tree(&root);
+
+        let source = root.join("services/auth/index.ts");
+        fs::write(&source, "export {}\n").unwrap();
+        let msg = tree
+            .handle(event(EventKind::Create(CreateKind::File), source.clone()))
+            .unwrap();
+        assert_eq!(msg.files, vec![source]);
+
+        let generated = root.join("services/auth/api.generated.ts");
+        fs::write(&generated, "export {}\n").unwrap();
+        assert!(
+            tree.handle(event(EventKind::Create(CreateKind::File), generated))
+                .is_none()
+        );
+
+        let notes = root.join("services/auth/notes.txt");
+        fs::write(&notes, "todo\n").unwrap();
+        assert!(
+            tree.handle(event(EventKind::Create(CreateKind::File), notes))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_new_and_removed_directories() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        let mut tree = test_tree(&root);
+
+        // A package directory appearing at once (e.g. git checkout, mv)
+        let pkg = root.join("packages/new-lib");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(pkg.join("package.json"), "{}").unwrap();
+        let msg = tree
+            .handle(event(
+                EventKind::Create(CreateKind::Folder),
+                root.join("packages"),
+            ))
+            .unwrap();
+        assert_eq!(msg.files, vec![pkg.join("package.json")]);
+        assert!(tree.watched.contains(&pkg));
+
+        // Removing it asks for an unconditional rebuild
+        fs::remove_dir_all(root.join("packages")).unwrap();
+        let msg = tree
+            .handle(event(
+                EventKind::Remove(RemoveKind::Folder),
+                root.join("packages"),
+            ))
+            .unwrap();
+        assert!(msg.files.is_empty());
+        assert!(!tree.watched.contains(&pkg));
+
+        // Excluded directories are never watched
+        fs::create_dir_all(root.join("node_modules/x")).unwrap();
+        assert!(
+            tree.handle(event(
+                EventKind::Create(CreateKind::Folder),
+                root.join("node_modules")
+            ))
+            .is_none()
+        );
+        assert!(!tree.watched.contains(&root.join("node_modules")));
+    }
+}
@@ -8,6 +8,131 @@ pub struct RebuildMessage {
     pub files: Vec<PathBuf>,
 }
 
+/// Request sent over UDS, one JSON object per line. The daemon answers every
+/// request with one [`Response`] line.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Queue a rebuild. With `wait`, the answer is held until the rebuild lands.
+    Rebuild {
+        #[serde(default)]
+        files: Vec<PathBuf>,
+        #[serde(default)]
+        wait: bool,
+    },
+    /// Whether the daemon is idle, debouncing or building, and what is queued.
+    Status,
+    /// The most recent failed build, if any.
+    LastError,
+    /// Recent builds, newest first.
+    History {
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    /// Answered once every rebuild queued before it has landed.
+    Flush,
+}
+
+impl Request {
+    /// Parse one request line. An untagged `{"files": [...]}`, as sent by
+    /// older clients, is a rebuild that doesn't wait.
+    pub fn parse(line: &str) -> serde_json::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(line)?;
+        if value.get("type").is_some() {
+            serde_json::from_value(value)
+        } else {
+            let msg: RebuildMessage = serde_json::from_value(value)?;
+            Ok(Request::Rebuild {
+                files: msg.files,
+                wait: false,
+            })
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    /// A rebuild was queued without waiting for it.
+    Queued,
+    Status(DaemonStatus),
+    LastError { build: Option<BuildRecord> },
+    History { builds: Vec<BuildRecord> },
+    /// The index is current. `build` is the build that covered the request,
+    /// or `None` when there was nothing to rebuild.
+    Done { build: Option<BuildRecord> },
+    Error { message: String },
+}
+
+/// What the build loop is doing right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Idle,
+    /// Collecting rebuild signals until the debounce window closes.
+    Debouncing,
+    Building,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Phase::Idle => "idle",
+            Phase::Debouncing => "debouncing",
+            Phase::Building => "building",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub root: PathBuf,
+    /// Whether the daemon watches the filesystem itself (`--native`).
+    pub native: bool,
+    pub started_at: String,
+    pub phase: Phase,
+    /// Rebuild signals received but not yet covered by a started build.
+    pub pending: usize,
+    /// When the running build started, if one is running.
+    pub building_since: Option<String>,
+    pub builds: usize,
+    pub failures: usize,
+    pub last_build: Option<BuildRecord>,
+}
+
+/// How a build was run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildMode {
+    /// Per-file update of just the changed files.
+    Files,
+    /// Full incremental build (walk, manifests, symbols, ...).
+    Full,
+}
+
+impl std::fmt::Display for BuildMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BuildMode::Files => "files",
+            BuildMode::Full => "full",
+        })
+    }
+}
+
+/// One finished build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildRecord {
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub mode: BuildMode,
+    /// Changed files that triggered the build; 0 for unconditional rebuilds.
+    pub changed_files: usize,
+    /// Rebuild signals the build covered.
+    pub signals: usize,
+    pub error: Option<String>,
+}
+
 /// Claude Code hook JSON received on stdin for PostToolUse events.
 #[derive(Debug, Deserialize)]
 pub struct HookInput {
@@ -107,6 +232,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_requests() {
+        assert!(matches!(
+            Request::parse(r#"{"type":"rebuild","files":["a.ts"],"wait":true}"#).unwrap(),
+            Request::Rebuild { files, wait: true } if files == [PathBuf::from("a.ts")]
+        ));
+        assert!(matches!(
+            Request::parse(r#"{"type":"history"}"#).unwrap(),
+            Request::History { limit: None }
+        ));
+        assert!(matches!(
+            Request::parse(r#"{"type":"flush"}"#).unwrap(),
+            Request::Flush
+        ));
+        assert!(Request::parse(r#"{"type":"reindex"}"#).is_err());
+        assert!(Request::parse("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_legacy_rebuild_message() {
+        assert!(matches!(
+            Request::parse(r#"{"files":["a.ts","b.go"]}"#).unwrap(),
+            Request::Rebuild { files, wait: false } if files.len() == 2
+        ));
+        assert!(matches!(
+            Request::parse("{}").unwrap(),
+            Request::Rebuild { files, wait: false } if files.is_empty()
+        ));
+    }
+
     #[test]
     fn test_edit_always_rebuilds() {
         assert!(hook("Edit", None).should_rebuild());
@@ -0,0 +1,187 @@
+use super::protocol::{BuildRecord, DaemonStatus, Phase};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// How many finished builds the daemon remembers for `history`.
+const HISTORY_LEN: usize = 50;
+
+/// Daemon state shared by the socket handlers, the native watcher and the
+/// build loop. Socket handlers answer status queries from it directly, so they
+/// never wait on a running build.
+pub type Shared = Arc<Mutex<DaemonState>>;
+
+pub struct DaemonState {
+    root: PathBuf,
+    native: bool,
+    started_at: String,
+    phase: Phase,
+    building_since: Option<String>,
+    /// Rebuild signals sent but not yet picked up by the build loop.
+    queued: usize,
+    /// Rebuild signals collected in the current debounce window.
+    window: usize,
+    builds: usize,
+    failures: usize,
+    /// Finished builds, newest first.
+    history: VecDeque<BuildRecord>,
+    last_error: Option<BuildRecord>,
+}
+
+impl DaemonState {
+    pub fn new(root: PathBuf, native: bool) -> Shared {
+        Arc::new(Mutex::new(Self {
+            root,
+            native,
+            started_at: now(),
+            phase: Phase::Idle,
+            building_since: None,
+            queued: 0,
+            window: 0,
+            builds: 0,
+            failures: 0,
+            history: VecDeque::new(),
+            last_error: None,
+        }))
+    }
+
+    pub fn status(&self) -> DaemonStatus {
+        DaemonStatus {
+            pid: std::process::id(),
+            root: self.root.clone(),
+            native: self.native,
+            started_at: self.started_at.clone(),
+            phase: self.phase,
+            pending: self.queued + self.window,
+            building_since: self.building_since.clone(),
+            builds: self.builds,
+            failures: self.failures,
+            last_build: self.history.front().cloned(),
+        }
+    }
+
+    pub fn last_build(&self) -> Option<BuildRecord> {
+        self.history.front().cloned()
+    }
+
+    pub fn last_error(&self) -> Option<BuildRecord> {
+        self.last_error.clone()
+    }
+
+    pub fn history(&self, limit: Option<usize>) -> Vec<BuildRecord> {
+        self.history
+            .iter()
+            .take(limit.unwrap_or(HISTORY_LEN))
+            .cloned()
+            .collect()
+    }
+
+    /// A rebuild signal was sent to the build loop.
+    pub fn signal_queued(&mut self) {
+        self.queued += 1;
+    }
+
+    /// The build loop picked up a rebuild signal for its debounce window.
+    pub fn signal_received(&mut self) {
+        self.queued = self.queued.saturating_sub(1);
+        self.window += 1;
+        self.phase = Phase::Debouncing;
+    }
+
+    /// The debounce window closed without anything worth rebuilding.
+    pub fn window_skipped(&mut self) {
+        self.window = 0;
+        self.phase = Phase::Idle;
+    }
+
+    /// The debounce window closed and a build started. Returns the start time.
+    pub fn build_started(&mut self) -> String {
+        let started_at = now();
+        self.window = 0;
+        self.phase = Phase::Building;
+        self.building_since = Some(started_at.clone());
+        started_at
+    }
+
+    pub fn build_finished(&mut self, record: BuildRecord) {
+        self.phase = Phase::Idle;
+        self.building_since = None;
+        self.builds += 1;
+        if record.error.is_some() {
+            self.failures += 1;
+            self.last_error = Some(record.clone());
+        }
+        self.history.push_front(record);
+        self.history.truncate(HISTORY_LEN);
+    }
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watch::protocol::BuildMode;
+
+    fn record(error: Option<&str>) -> BuildRecord {
+        BuildRecord {
+            started_at: now(),
+            duration_ms: 5,
+            mode: BuildMode::Full,
+            changed_files: 0,
+            signals: 1,
+            error: error.map(|e| e.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_pending_and_phase() {
+        let state = DaemonState::new(PathBuf::from("/repo"), false);
+        let mut state = state.lock().unwrap();
+        state.signal_queued();
+        state.signal_queued();
+        assert_eq!(state.status().pending, 2);
+        assert_eq!(state.status().phase, Phase::Idle);
+
+        state.signal_received();
+        assert_eq!(state.status().pending, 2);
+        assert_eq!(state.status().phase, Phase::Debouncing);
+
+        state.build_started();
+        let status = state.status();
+        assert_eq!(
+            status.pending, 1,
+            "the signal still in the channel stays pending"
+        );
+        assert_eq!(status.phase, Phase::Building);
+        assert!(status.building_since.is_some());
+
+        state.build_finished(record(None));
+        let status = state.status();
+        assert_eq!(status.phase, Phase::Idle);
+        assert_eq!(status.builds, 1);
+        assert!(status.last_build.is_some());
+    }
+
+    #[test]
+    fn test_history_and_last_error() {
+        let state = DaemonState::new(PathBuf::from("/repo"), false);
+        let mut state = state.lock().unwrap();
+        state.build_finished(record(Some("boom")));
+        state.build_finished(record(None));
+
+        assert_eq!(state.status().failures, 1);
+        assert_eq!(state.last_error().unwrap().error.as_deref(), Some("boom"));
+        let history = state.history(None);
+        assert_eq!(history.len(), 2);
+        assert!(history[0].error.is_none(), "newest first");
+        assert_eq!(state.history(Some(1)).len(), 1);
+
+        for _ in 0..HISTORY_LEN {
+            state.build_finished(record(None));
+        }
+        assert_eq!(state.history(None).len(), HISTORY_LEN);
+    }
+}
@@ -579,6 +579,26 @@ fn test_symbol_fts_search() {
         results
     );
 
+    // ...and by its parts, through `shire query`
+    let query = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "--json", "symbols", "validate tok"])
+        .output()
+        .unwrap();
+    let found: serde_json::Value = serde_json::from_slice(&query.stdout).unwrap();
+    assert_eq!(found["results"][0]["name"], "validateToken", "got: {found}");
+
+    let query = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "symbols", "--raw", "name:validate*"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&query.stdout).contains("validateToken"));
+    let query = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "symbols", "--raw", "\"unterminated"])
+        .output()
+        .unwrap();
+    assert!(!query.status.success());
+    assert!(String::from_utf8_lossy(&query.stderr).contains("Invalid FTS5 query"));
+
     // Symbol count should be in build output
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(
@@ -1795,3 +1815,909 @@ exclude_extensions = [".proto"]
         .unwrap();
     assert_eq!(count, 0, "Proto symbols should be excluded by config");
 }
+
+fn build_fixture(bin: &Path, dir: &Path) {
+    let output = Command::new(bin)
+        .args(["build", "--root", dir.to_str().unwrap()])
+        .output()
+        .expect("Failed to run shire build");
+    assert!(
+        output.status.success(),
+        "Build failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_query_dependents_json() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let db_path = dir.path().join(".shire/index.db");
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "--json", "dependents", "auth-service"])
+        .output()
+        .expect("Failed to run shire query");
+    assert!(
+        output.status.success(),
+        "Query failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rows = rows["results"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["package"], "payments");
+    assert_eq!(rows[0]["is_internal"], true);
+}
+
+#[test]
+fn test_query_resolved_json() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    fs::write(
+        dir.path().join("services/auth/package-lock.json"),
+        r#"{
+  "name": "auth-service",
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "auth-service", "dependencies": { "express": "^4.18" }, "devDependencies": { "jest": "^29" } },
+    "node_modules/express": { "version": "4.18.2", "dependencies": { "accepts": "~1.3.8" } },
+    "node_modules/accepts": { "version": "1.3.8" },
+    "node_modules/jest": { "version": "29.7.0", "dev": true }
+  }
+}"#,
+    )
+    .unwrap();
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let db_path = dir.path().join(".shire/index.db");
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "--json", "resolved", "--dependency", "accepts"])
+        .output()
+        .expect("Failed to run shire query resolved");
+    assert!(
+        output.status.success(),
+        "Query failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let rows: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let rows = rows["results"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["package"], "auth-service");
+    assert_eq!(rows[0]["version"], "1.3.8");
+    assert_eq!(rows[0]["is_direct"], false);
+    assert_eq!(rows[0]["lockfile"], "services/auth/package-lock.json");
+}
+
+#[test]
+fn test_query_who_owns_and_packages_by_owner() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    fs::write(
+        dir.path().join("CODEOWNERS"),
+        "# Platform owns everything by default\n* @acme/platform\nservices/auth/ @acme/identity\n",
+    )
+    .unwrap();
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let db_path = dir.path().join(".shire/index.db");
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "--json", "who-owns", "services/auth/package.json"])
+        .output()
+        .expect("Failed to run shire query who-owns");
+    assert!(
+        output.status.success(),
+        "Query failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let ownership: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(ownership["owners"], serde_json::json!(["@acme/identity"]));
+    assert_eq!(ownership["package"], "auth-service");
+    assert_eq!(ownership["package_owners"], serde_json::json!(["@acme/identity"]));
+
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "--json", "packages-by-owner", "@acme/identity"])
+        .output()
+        .expect("Failed to run shire query packages-by-owner");
+    assert!(output.status.success());
+    let packages: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = packages["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["auth-service"]);
+}
+
+#[test]
+fn test_query_hotspots_requires_history() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let db_path = dir.path().join(".shire/index.db");
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "hotspots"])
+        .output()
+        .expect("Failed to run shire query hotspots");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[history] enabled = true"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_query_and_tools_paginate() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+    let db_path = dir.path().join(".shire/index.db");
+
+    let list = |extra: &[&str]| -> serde_json::Value {
+        let output = Command::new(&bin)
+            .args(["query", "--db", db_path.to_str().unwrap(), "--json", "list"])
+            .args(extra)
+            .output()
+            .expect("Failed to run shire query list");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+    let all = list(&[]);
+    let total = all["total"].as_u64().unwrap();
+    assert!(total > 3);
+    assert_eq!(all["has_more"], false);
+    assert_eq!(all["results"].as_array().unwrap().len() as u64, total);
+
+    let page = list(&["--limit", "2", "--offset", "1"]);
+    assert_eq!(page["total"], total);
+    assert_eq!(page["has_more"], true);
+    assert_eq!(page["next_offset"], 3);
+    assert_eq!(page["results"][0], all["results"][1]);
+    assert_eq!(page["results"].as_array().unwrap().len(), 2);
+
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "list", "--limit", "2"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("Showing 1-2 of {total}; pass --offset 2 for more.")), "stdout: {stdout}");
+
+    // MCP tools return the same envelope
+    let mut client = McpClient::start(&bin, &db_path);
+    let (result, _) = client.request(
+        "tools/call",
+        serde_json::json!({ "name": "list_packages", "arguments": { "limit": 1, "offset": 2 } }),
+    );
+    let body: serde_json::Value = serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(body["total"], total);
+    assert_eq!(body["offset"], 2);
+    assert_eq!(body["limit"], 1);
+    assert_eq!(body["next_offset"], 3);
+    assert_eq!(body["results"][0], all["results"][2]);
+}
+
+#[test]
+fn test_query_path_json() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let db_path = dir.path().join(".shire/index.db");
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "--json", "path", "payments", "express"])
+        .output()
+        .expect("Failed to run shire query path");
+    assert!(
+        output.status.success(),
+        "Query failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["truncated"], false);
+    let paths = result["paths"].as_array().unwrap();
+    assert_eq!(paths.len(), 1);
+    assert_eq!(
+        paths[0]["packages"],
+        serde_json::json!(["payments", "auth-service", "express"])
+    );
+    assert_eq!(paths[0]["edges"][1]["dep_kind"], "runtime");
+}
+
+#[test]
+fn test_affected_from_git_diff() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {:?} failed", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "init"]);
+
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    fs::write(
+        dir.path().join("packages/shared-types/index.ts"),
+        "export type Id = string;\n",
+    )
+    .unwrap();
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "change shared-types"]);
+
+    let output = Command::new(&bin)
+        .args([
+            "affected",
+            "--root",
+            dir.path().to_str().unwrap(),
+            "--base",
+            "HEAD~1",
+            "--head",
+            "HEAD",
+            "--format",
+            "json",
+        ])
+        .output()
+        .expect("Failed to run shire affected");
+    assert!(
+        output.status.success(),
+        "Affected failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let affected: Vec<String> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(affected, vec!["auth-service", "hybrid-js", "payments", "shared-types"]);
+
+    // Uncommitted change to a leaf package, diffed against the working tree
+    let manifest = dir.path().join("services/payments/package.json");
+    let mut content = fs::read_to_string(&manifest).unwrap();
+    content.push('\n');
+    fs::write(&manifest, content).unwrap();
+    let output = Command::new(&bin)
+        .args(["affected", "--root", dir.path().to_str().unwrap(), "--base", "HEAD"])
+        .output()
+        .expect("Failed to run shire affected");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "payments\n");
+}
+
+#[test]
+fn test_query_build_order_waves() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let db_path = dir.path().join(".shire/index.db");
+    let output = Command::new(&bin)
+        .args(["query", "--db", db_path.to_str().unwrap(), "--json", "build-order", "payments", "--waves"])
+        .output()
+        .expect("Failed to run shire query build-order");
+    assert!(
+        output.status.success(),
+        "Query failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let waves: Vec<Vec<String>> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        waves,
+        vec![vec!["shared-types"], vec!["auth-service"], vec!["payments"]]
+    );
+}
+
+#[test]
+fn test_query_table_output_and_missing_package() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let db_path = dir.path().join(".shire/index.db");
+    let output = Command::new(&bin)
+        .args(["query", "symbols", "validateToken", "--db", db_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to run shire query");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("NAME"), "Expected table header, got: {stdout}");
+    assert!(stdout.contains("services/auth/src/auth.ts:1"), "got: {stdout}");
+
+    let output = Command::new(&bin)
+        .args(["query", "package", "does-not-exist", "--db", db_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to run shire query");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not found"));
+}
+
+#[test]
+fn test_export_graph_formats() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+    let db_path = dir.path().join(".shire/index.db");
+
+    let output = Command::new(&bin)
+        .args([
+            "export", "graph", "--db", db_path.to_str().unwrap(),
+            "--format", "dot", "--root", "payments", "--internal-only", "--cluster", "path",
+        ])
+        .output()
+        .expect("Failed to run shire export");
+    assert!(
+        output.status.success(),
+        "Export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let dot = String::from_utf8_lossy(&output.stdout);
+    assert!(dot.contains("\"payments\" -> \"auth-service\";"), "got: {dot}");
+    assert!(dot.contains("\"auth-service\" -> \"shared-types\";"), "got: {dot}");
+    assert!(dot.contains("label=\"services\";"), "got: {dot}");
+    assert!(!dot.contains("express"), "internal-only should drop externals: {dot}");
+
+    let output = Command::new(&bin)
+        .args([
+            "export", "graph", "--db", db_path.to_str().unwrap(),
+            "--format", "json", "--dep-kind", "dev",
+        ])
+        .output()
+        .expect("Failed to run shire export");
+    assert!(output.status.success());
+    let graph: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let edges = graph["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0]["to"], "jest");
+}
+
+#[test]
+fn test_check_cycles() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let bin = cargo_bin();
+
+    let a = dir.path().join("packages/a");
+    let b = dir.path().join("packages/b");
+    fs::create_dir_all(&a).unwrap();
+    fs::create_dir_all(&b).unwrap();
+    fs::write(
+        a.join("package.json"),
+        r#"{"name": "pkg-a", "version": "1.0.0", "dependencies": {"pkg-b": "^1.0"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        b.join("package.json"),
+        r#"{"name": "pkg-b", "version": "1.0.0", "devDependencies": {"pkg-a": "^1.0"}}"#,
+    )
+    .unwrap();
+    build_fixture(&bin, dir.path());
+    let db_path = dir.path().join(".shire/index.db");
+
+    let output = Command::new(&bin)
+        .args(["check", "cycles", "--db", db_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to run shire check");
+    assert!(!output.status.success(), "cycle should fail the check");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("pkg-a, pkg-b"), "got: {stdout}");
+    assert!(stdout.contains("pkg-b -> pkg-a (dev)"), "got: {stdout}");
+
+    // The back edge is a dev dependency, so excluding dev edges passes
+    let output = Command::new(&bin)
+        .args(["check", "cycles", "--exclude-dev", "--db", db_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to run shire check");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_check_drift_fails_for_single_version_library() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    // auth-service declares express ^4.18
+    let billing = dir.path().join("services/billing");
+    fs::create_dir_all(&billing).unwrap();
+    fs::write(
+        billing.join("package.json"),
+        r#"{"name": "billing", "version": "1.0.0", "dependencies": {"express": "^4.17"}}"#,
+    )
+    .unwrap();
+    build_fixture(&bin, dir.path());
+    let root = dir.path().to_str().unwrap();
+
+    // Nothing configured yet
+    let output = Command::new(&bin)
+        .args(["check", "drift", "--root", root])
+        .output()
+        .expect("Failed to run shire check");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[drift] single_version"));
+
+    fs::write(dir.path().join("shire.toml"), "[drift]\nsingle_version = [\"express\"]\n").unwrap();
+    let output = Command::new(&bin)
+        .args(["check", "drift", "--root", root])
+        .output()
+        .expect("Failed to run shire check");
+    assert!(!output.status.success(), "express drift should fail the check");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("express (npm): 2 versions across 2 packages"), "got: {stdout}");
+    assert!(stdout.contains("^4.17: billing"), "got: {stdout}");
+
+    // A library declared with one version passes
+    fs::write(dir.path().join("shire.toml"), "[drift]\nsingle_version = [\"jest\"]\n").unwrap();
+    let output = Command::new(&bin)
+        .args(["check", "drift", "--root", root])
+        .output()
+        .expect("Failed to run shire check");
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_check_rules() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    fs::write(
+        dir.path().join("shire.toml"),
+        r#"
+[[rules]]
+name = "services-are-independent"
+description = "Services talk over the network, not through imports"
+from = { path = "services/**", kind = "npm" }
+deny = [{ path = "services/**" }]
+"#,
+    )
+    .unwrap();
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+    let root = dir.path().to_str().unwrap();
+
+    let output = Command::new(&bin)
+        .args(["check", "rules", "--root", root])
+        .output()
+        .expect("Failed to run shire check");
+    assert!(!output.status.success(), "payments -> auth-service should fail the check");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Rule services-are-independent: Services talk over the network"),
+        "got: {stdout}"
+    );
+    assert!(stdout.contains("payments -> auth-service (runtime): denied"), "got: {stdout}");
+
+    let output = Command::new(&bin)
+        .args(["check", "rules", "--root", root, "--json"])
+        .output()
+        .expect("Failed to run shire check");
+    let violations: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(violations.as_array().unwrap().len(), 1);
+    assert_eq!(violations[0]["reason"], "denied");
+}
+
+/// Stops the watch daemon when dropped, so a failing assertion doesn't leak it.
+struct DaemonGuard<'a> {
+    bin: &'a Path,
+    root: &'a str,
+}
+
+impl Drop for DaemonGuard<'_> {
+    fn drop(&mut self) {
+        let _ = Command::new(self.bin)
+            .args(["watch", "--root", self.root, "--stop"])
+            .output();
+    }
+}
+
+#[test]
+fn test_watch_status_and_rebuild_wait() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    fs::write(dir.path().join("shire.toml"), "[watch]\ndebounce_ms = 100\n").unwrap();
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+    let root = dir.path().to_str().unwrap();
+
+    // Without a daemon, --status and --wait fail instead of silently doing nothing
+    let output = Command::new(&bin)
+        .args(["watch", "--root", root, "--status"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("watch daemon is not running"));
+
+    let output = Command::new(&bin)
+        .args(["watch", "--root", root])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let _guard = DaemonGuard { bin: &bin, root };
+    let sock = dir.path().join(".shire/watch.sock");
+    for _ in 0..50 {
+        if sock.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert!(sock.exists(), "daemon never bound its socket");
+
+    let source = dir.path().join("services/auth/src/session.ts");
+    fs::write(&source, "export function refreshSession() {}\n").unwrap();
+    let output = Command::new(&bin)
+        .args(["rebuild", "--root", root, "--wait", "--file", source.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "rebuild --wait failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // The rebuild has landed by the time --wait returns
+    let output = Command::new(&bin)
+        .args(["query", "--db", dir.path().join(".shire/index.db").to_str().unwrap(), "--json"])
+        .args(["symbol", "refreshSession"])
+        .output()
+        .unwrap();
+    let symbols: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(symbols["total"], 1, "got: {symbols}");
+
+    let output = Command::new(&bin)
+        .args(["watch", "--root", root, "--flush"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = Command::new(&bin)
+        .args(["watch", "--root", root, "--status", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["status"]["phase"], "idle");
+    assert_eq!(report["status"]["pending"], 0);
+    assert_eq!(report["status"]["builds"], 1);
+    assert!(report["last_error"].is_null());
+    assert_eq!(report["history"][0]["changed_files"], 1);
+    assert!(report["history"][0]["error"].is_null());
+}
+
+/// Minimal MCP client over `shire serve`'s stdio, for end-to-end checks.
+struct McpClient {
+    child: std::process::Child,
+    stdout: std::io::BufReader<std::process::ChildStdout>,
+    next_id: u64,
+}
+
+impl McpClient {
+    fn start(bin: &Path, db: &Path) -> Self {
+        Self::start_with_args(bin, &["--db", db.to_str().unwrap()])
+    }
+
+    fn start_with_args(bin: &Path, args: &[&str]) -> Self {
+        let mut child = Command::new(bin)
+            .arg("serve")
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .expect("Failed to run shire serve");
+        let stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+        let mut client = McpClient {
+            child,
+            stdout,
+            next_id: 0,
+        };
+        client.request(
+            "initialize",
+            serde_json::json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "clientInfo": { "name": "test", "version": "0" }
+            }),
+        );
+        client.send(serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+        client
+    }
+
+    fn send(&mut self, message: serde_json::Value) {
+        let stdin = self.child.stdin.as_mut().unwrap();
+        writeln!(stdin, "{message}").unwrap();
+        stdin.flush().unwrap();
+    }
+
+    /// Send a request and return (result, notifications received before it).
+    fn request(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> (serde_json::Value, Vec<serde_json::Value>) {
+        use std::io::BufRead;
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        let mut notifications = Vec::new();
+        loop {
+            let mut line = String::new();
+            assert!(self.stdout.read_line(&mut line).unwrap() > 0, "server exited");
+            let message: serde_json::Value = serde_json::from_str(&line).unwrap();
+            if message["id"] == id {
+                return (message["result"].clone(), notifications);
+            }
+            notifications.push(message);
+        }
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_serve_reloads_after_rebuild_with_freshness() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+    let db = dir.path().join(".shire/index.db");
+
+    let mut client = McpClient::start(&bin, &db);
+    let (result, _) = client.request(
+        "tools/call",
+        serde_json::json!({ "name": "search_packages", "arguments": { "query": "billing" } }),
+    );
+    let content = result["content"].as_array().unwrap();
+    assert_eq!(content.len(), 2, "tool output plus freshness: {result}");
+    let meta: serde_json::Value = serde_json::from_str(content[1]["text"].as_str().unwrap()).unwrap();
+    let first_indexed_at = meta["freshness"]["indexed_at"].clone();
+    assert!(first_indexed_at.is_string());
+    assert!(meta["freshness"]["age_seconds"].as_i64().unwrap() >= 0);
+    assert!(!content[0]["text"].as_str().unwrap().contains("billing-service"));
+
+    // Rebuild with a new package while the server is running
+    let billing = dir.path().join("services/billing");
+    fs::create_dir_all(&billing).unwrap();
+    fs::write(
+        billing.join("package.json"),
+        r#"{"name": "billing-service", "version": "0.1.0", "description": "Invoices and billing"}"#,
+    )
+    .unwrap();
+    build_fixture(&bin, dir.path());
+
+    let (result, notifications) = client.request(
+        "tools/call",
+        serde_json::json!({ "name": "search_packages", "arguments": { "query": "billing" } }),
+    );
+    let content = result["content"].as_array().unwrap();
+    assert!(
+        content[0]["text"].as_str().unwrap().contains("billing-service"),
+        "server should read the rebuilt index: {result}"
+    );
+    let meta: serde_json::Value = serde_json::from_str(content[1]["text"].as_str().unwrap()).unwrap();
+    assert_ne!(meta["freshness"]["indexed_at"], first_indexed_at);
+
+    let methods: Vec<&str> = notifications
+        .iter()
+        .filter_map(|n| n["method"].as_str())
+        .collect();
+    assert!(methods.contains(&"notifications/resources/list_changed"), "got: {methods:?}");
+    assert!(methods.contains(&"notifications/message"), "got: {methods:?}");
+}
+
+/// Send a raw HTTP/1.1 request and return the whole response (headers and body).
+fn http_request(addr: &str, request: &str) -> String {
+    use std::io::Read;
+    let mut stream = std::net::TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = Vec::new();
+    // Streams may stay open; whatever arrived before the timeout is enough
+    let _ = stream.read_to_end(&mut response);
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+fn mcp_post(addr: &str, token: Option<&str>, body: &str) -> String {
+    let auth = token
+        .map(|t| format!("Authorization: Bearer {t}\r\n"))
+        .unwrap_or_default();
+    http_request(
+        addr,
+        &format!(
+            "POST /mcp HTTP/1.1\r\nHost: {addr}\r\n{auth}Content-Type: application/json\r\n\
+             Accept: application/json, text/event-stream\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        ),
+    )
+}
+
+#[test]
+fn test_serve_http_with_bearer_token() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+
+    let addr = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+    let mut server = Command::new(&bin)
+        .args([
+            "serve",
+            "--db",
+            dir.path().join(".shire/index.db").to_str().unwrap(),
+            "--http",
+            &addr,
+            "--token",
+            "s3cret",
+        ])
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let up = (0..50).any(|_| {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        std::net::TcpStream::connect(&addr).is_ok()
+    });
+
+    let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#;
+    let unauthorized = mcp_post(&addr, None, initialize);
+    let wrong_token = mcp_post(&addr, Some("guess"), initialize);
+    let authorized = mcp_post(&addr, Some("s3cret"), initialize);
+    let sse = http_request(
+        &addr,
+        &format!("GET /sse HTTP/1.1\r\nHost: {addr}\r\nAuthorization: Bearer s3cret\r\n\r\n"),
+    );
+    let _ = server.kill();
+    let _ = server.wait();
+
+    assert!(up, "server never started listening on {addr}");
+    assert!(unauthorized.starts_with("HTTP/1.1 401"), "got: {unauthorized}");
+    assert!(wrong_token.starts_with("HTTP/1.1 401"), "got: {wrong_token}");
+    assert!(authorized.starts_with("HTTP/1.1 200"), "got: {authorized}");
+    assert!(authorized.to_lowercase().contains("mcp-session-id:"), "got: {authorized}");
+    assert!(authorized.contains(r#""serverInfo":{"name":"shire""#), "got: {authorized}");
+    assert!(sse.contains("event: endpoint"), "got: {sse}");
+}
+
+#[test]
+fn test_serve_resources() {
+    let dir = tempfile::TempDir::new().unwrap();
+    create_fixture_monorepo(dir.path());
+    let bin = cargo_bin();
+    build_fixture(&bin, dir.path());
+    let mut client = McpClient::start(&bin, &dir.path().join(".shire/index.db"));
+
+    let (result, _) = client.request("resources/templates/list", serde_json::json!({}));
+    let templates: Vec<_> = result["resourceTemplates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["uriTemplate"].as_str().unwrap().to_string())
+        .collect();
+    assert!(templates.contains(&"shire://package/{name}/files".to_string()));
+    assert!(templates.contains(&"shire://file/{path}".to_string()));
+
+    let (result, _) = client.request("resources/list", serde_json::json!({}));
+    let uris: Vec<_> = result["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["uri"].as_str().unwrap().to_string())
+        .collect();
+    assert!(uris.contains(&"shire://package/payments".to_string()));
+    assert!(uris.contains(&"shire://file/services/auth/src/auth.ts".to_string()));
+
+    let (result, _) = client.request(
+        "resources/read",
+        serde_json::json!({ "uri": "shire://package/payments" }),
+    );
+    let package: serde_json::Value =
+        serde_json::from_str(result["contents"][0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(package["package"]["name"], "payments");
+    assert_eq!(package["dependencies"].as_array().unwrap().len(), 2);
+
+    let (result, _) = client.request(
+        "resources/read",
+        serde_json::json!({ "uri": "shire://file/services/auth/src/auth.ts" }),
+    );
+    let contents = result["contents"].as_array().unwrap();
+    assert_eq!(contents.len(), 2, "outline plus file text: {result}");
+    let outline: serde_json::Value = serde_json::from_str(contents[0]["text"].as_str().unwrap()).unwrap();
+    assert_eq!(outline["package"], "auth-service");
+    assert!(!outline["symbols"].as_array().unwrap().is_empty());
+    assert_eq!(
+        contents[1]["text"].as_str().unwrap(),
+        fs::read_to_string(dir.path().join("services/auth/src/auth.ts")).unwrap()
+    );
+
+    let (result, _) = client.request(
+        "resources/read",
+        serde_json::json!({ "uri": "shire://graph/payments" }),
+    );
+    let graph: serde_json::Value =
+        serde_json::from_str(result["contents"][0]["text"].as_str().unwrap()).unwrap();
+    assert!(graph["edges"].as_array().unwrap().iter().any(|e| e["to"] == "shared-types"));
+}
+
+#[test]
+fn test_serve_federated_indexes() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let bin = cargo_bin();
+    let auth = dir.path().join("auth");
+    fs::create_dir_all(auth.join("crypto")).unwrap();
+    fs::write(
+        auth.join("package.json"),
+        r#"{"name": "@acme/auth", "version": "1.0.0", "dependencies": {"@acme/crypto": "^1.0"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        auth.join("crypto/package.json"),
+        r#"{"name": "@acme/crypto", "version": "1.0.0"}"#,
+    )
+    .unwrap();
+    let web = dir.path().join("web");
+    fs::create_dir_all(&web).unwrap();
+    fs::write(
+        web.join("package.json"),
+        r#"{"name": "web-app", "version": "0.1.0", "dependencies": {"@acme/auth": "^1.0", "react": "^18"}}"#,
+    )
+    .unwrap();
+    build_fixture(&bin, &auth);
+    build_fixture(&bin, &web);
+
+    let web_db = web.join(".shire/index.db");
+    let auth_db = format!("identity={}", auth.join(".shire/index.db").display());
+    let mut client =
+        McpClient::start_with_args(&bin, &["--db", web_db.to_str().unwrap(), "--db", &auth_db]);
+    let mut call = |name: &str, arguments: serde_json::Value| -> serde_json::Value {
+        let (result, _) = client.request(
+            "tools/call",
+            serde_json::json!({ "name": name, "arguments": arguments }),
+        );
+        serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap()
+    };
+
+    let package = call("get_package", serde_json::json!({ "name": "@acme/auth" }));
+    assert_eq!(package["repo"], "identity");
+    assert_eq!(package["path"], "identity");
+    let packages = call("list_packages", serde_json::json!({}));
+    assert_eq!(packages["total"], 3);
+    assert!(packages["results"].as_array().unwrap().iter().any(|p| p["repo"] == "web"));
+
+    // A dependency on a package published from another repo is internal
+    let deps = call(
+        "package_dependencies",
+        serde_json::json!({ "name": "web-app", "internal_only": true }),
+    );
+    assert_eq!(deps["results"].as_array().unwrap().len(), 1);
+    assert_eq!(deps["results"][0]["dependency"], "@acme/auth");
+    let graph = call(
+        "dependency_graph",
+        serde_json::json!({ "name": "web-app", "internal_only": true }),
+    );
+    assert!(graph["results"].as_array().unwrap().iter().any(|e| e["from"] == "@acme/auth" && e["to"] == "@acme/crypto"));
+
+    let status = call("index_status", serde_json::json!({}));
+    let repos: Vec<_> = status["repos"].as_array().unwrap().iter().map(|r| r["name"].clone()).collect();
+    assert_eq!(repos, ["identity", "web"]);
+}